@@ -1,66 +1,43 @@
-/**
+/*
  * entropy-analyzer/src/main.rs
  *
  * Implementação da Seção 5.3: Quantificando a Unicidade com Entropia de Shannon
  * Análise de fingerprints coletados para medir eficácia do sistema
  */
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use serde_json;
+use wasm_fingerprint::FingerprintData;
 
 /// Estrutura para representar um fingerprint do log
+///
+/// `data` é convertido para a versão atual do esquema compartilhado com o
+/// coletor Wasm (`wasm_fingerprint::migrate`), qualquer que seja a versão
+/// em que foi registrado.
 #[derive(Debug, Deserialize, Serialize)]
 struct FingerprintEntry {
     id: String,
     #[serde(rename = "sessionId")]
     session_id: String,
     #[serde(rename = "clientTimestamp")]
-    client_timestamp: Option<String>,
+    client_timestamp: Option<serde_json::Value>,
     #[serde(rename = "serverTimestamp")]
     server_timestamp: String,
+    #[serde(deserialize_with = "deserialize_fingerprint")]
     data: FingerprintData,
     metadata: Option<Metadata>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct FingerprintData {
-    #[serde(rename = "sessionId")]
-    session_id: String,
-    timestamp: Option<String>,
-    #[serde(rename = "proposalA")]
-    proposal_a: Option<ProposalA>,
-    #[serde(rename = "proposalB")]
-    proposal_b: Option<ProposalB>,
-    #[serde(rename = "browserAttributes")]
-    browser_attributes: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct ProposalA {
-    canvas: Option<String>,
-    webgl: Option<String>,
-    audio: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct ProposalB {
-    #[serde(rename = "portContention")]
-    port_contention: Option<String>,
-    distinguishers: Option<Vec<Distinguisher>>,
-    #[serde(rename = "hardwareProfile")]
-    hardware_profile: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct Distinguisher {
-    pair: Vec<String>,
-    ratio: f64,
-    category: Option<String>,
+fn deserialize_fingerprint<'de, D>(deserializer: D) -> Result<FingerprintData, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    wasm_fingerprint::migrate(value).map_err(serde::de::Error::custom)
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -138,6 +115,20 @@ enum Commands {
         #[arg(short, long, default_value = "../data/fingerprints.log")]
         file: String,
     },
+
+    /// Valida as entradas do log contra o esquema atual
+    Validate {
+        /// Caminho para o arquivo de log
+        #[arg(short, long, default_value = "../data/fingerprints.log")]
+        file: String,
+    },
+
+    /// Imprime o JSON Schema da versão atual do fingerprint
+    Schema {
+        /// Grava o esquema no arquivo em vez de imprimir
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -153,6 +144,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Stats { file } => {
             print_statistics(&file)?;
         }
+        Commands::Validate { file } => {
+            validate_log(&file)?;
+        }
+        Commands::Schema { output } => {
+            export_schema(output.as_deref())?;
+        }
     }
 
     Ok(())
@@ -161,7 +158,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// Analisa fingerprints e calcula entropia
 fn analyze_fingerprints(filepath: &str, verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
     println!("📊 Analisando fingerprints de: {}", filepath);
-    println!("─".repeat(60));
+    println!("{}", "─".repeat(60));
 
     let entries = read_fingerprints(Path::new(filepath))?;
 
@@ -174,7 +171,7 @@ fn analyze_fingerprints(filepath: &str, verbose: bool) -> Result<(), Box<dyn std
 
     // Imprime resultados
     println!("\n📈 RESULTADOS DA ANÁLISE");
-    println!("═".repeat(60));
+    println!("{}", "═".repeat(60));
 
     println!("\n📝 Estatísticas Básicas:");
     println!("  • Total de entradas: {}", results.total_entries);
@@ -296,27 +293,32 @@ fn calculate_shannon_entropy_from_ids(entries: &[FingerprintEntry]) -> f64 {
 
 /// Analisa entropia de cada componente
 fn analyze_components(entries: &[FingerprintEntry]) -> ComponentAnalysis {
-    let canvas_values: Vec<_> = entries.iter()
-        .filter_map(|e| e.data.proposal_a.as_ref()?.canvas.as_ref())
-        .collect();
-
-    let webgl_values: Vec<_> = entries.iter()
-        .filter_map(|e| e.data.proposal_a.as_ref()?.webgl.as_ref())
-        .collect();
-
-    let port_contention_values: Vec<_> = entries.iter()
-        .filter_map(|e| e.data.proposal_b.as_ref()?.port_contention.as_ref())
-        .collect();
-
     ComponentAnalysis {
-        canvas_entropy: calculate_entropy_from_values(&canvas_values),
-        webgl_entropy: calculate_entropy_from_values(&webgl_values),
-        audio_entropy: 0.0, // Simplificado para o exemplo
-        port_contention_entropy: calculate_entropy_from_values(&port_contention_values),
+        canvas_entropy: component_entropy(entries, "canvas", |d| &d.canvas_fingerprint.hash),
+        webgl_entropy: component_entropy(entries, "webgl", |d| &d.webgl_fingerprint.hash),
+        audio_entropy: component_entropy(entries, "audio", |d| &d.audio_fingerprint.hash),
+        port_contention_entropy: component_entropy(entries, "port_contention", |d| {
+            &d.hardware_profile.port_contention_hash
+        }),
         combined_entropy: calculate_shannon_entropy_from_ids(entries),
     }
 }
 
+/// Entropia de um componente, considerando apenas as entradas em que ele
+/// foi coletado com sucesso
+fn component_entropy<F>(entries: &[FingerprintEntry], name: &str, value: F) -> f64
+where
+    F: Fn(&FingerprintData) -> &String,
+{
+    let values: Vec<_> = entries.iter()
+        .filter(|e| e.data.component_ok(name))
+        .map(|e| value(&e.data))
+        .filter(|v| !v.is_empty())
+        .collect();
+
+    calculate_entropy_from_values(&values)
+}
+
 /// Calcula entropia de uma lista de valores
 fn calculate_entropy_from_values<T: std::hash::Hash + Eq>(values: &[T]) -> f64 {
     if values.is_empty() {
@@ -387,7 +389,7 @@ fn compare_datasets(file_a: &str, file_b: &str) -> Result<(), Box<dyn std::error
     println!("🔍 Comparando datasets:");
     println!("  Dataset A: {}", file_a);
     println!("  Dataset B: {}", file_b);
-    println!("─".repeat(60));
+    println!("{}", "─".repeat(60));
 
     let entries_a = read_fingerprints(Path::new(file_a))?;
     let entries_b = read_fingerprints(Path::new(file_b))?;
@@ -396,9 +398,9 @@ fn compare_datasets(file_a: &str, file_b: &str) -> Result<(), Box<dyn std::error
     let results_b = calculate_analysis(&entries_b);
 
     println!("\n📊 COMPARAÇÃO DE ENTROPIA");
-    println!("═".repeat(60));
+    println!("{}", "═".repeat(60));
     println!("{:<30} {:>10} {:>10}", "Métrica", "Dataset A", "Dataset B");
-    println!("─".repeat(60));
+    println!("{}", "─".repeat(60));
     println!("{:<30} {:>10} {:>10}", "Total de entradas", results_a.total_entries, results_b.total_entries);
     println!("{:<30} {:>10} {:>10}", "Fingerprints únicos", results_a.unique_fingerprints, results_b.unique_fingerprints);
     println!("{:<30} {:>10.4} {:>10.4}", "Entropia de Shannon (bits)", results_a.shannon_entropy, results_b.shannon_entropy);
@@ -446,4 +448,62 @@ fn save_report(results: &AnalysisResults, filepath: &str) -> Result<(), Box<dyn
     let json = serde_json::to_string_pretty(results)?;
     std::fs::write(filepath, json)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Valida cada entrada do log, indicando a versão de esquema encontrada e
+/// as entradas que não podem ser migradas
+fn validate_log(filepath: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔎 Validando fingerprints de: {}", filepath);
+    println!("{}", "─".repeat(60));
+
+    let file = File::open(filepath)?;
+    let reader = BufReader::new(file);
+    let mut versions: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut invalid = 0;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = serde_json::from_str::<serde_json::Value>(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|entry| {
+                let data = entry.get("data").cloned().ok_or("Entrada sem campo data")?;
+                let version = wasm_fingerprint::schema_version_of(&data)?;
+                wasm_fingerprint::migrate(data).map(|_| version)
+            });
+
+        match result {
+            Ok(version) => *versions.entry(version).or_insert(0) += 1,
+            Err(e) => {
+                invalid += 1;
+                println!("  ❌ Linha {}: {}", index + 1, e);
+            }
+        }
+    }
+
+    println!("\n📋 Versões de esquema (atual: {}):", wasm_fingerprint::SCHEMA_VERSION);
+    for (version, count) in &versions {
+        println!("  • v{}: {} entradas", version, count);
+    }
+    println!("  • inválidas: {}", invalid);
+
+    Ok(())
+}
+
+/// Exporta o JSON Schema gerado a partir dos tipos do coletor
+fn export_schema(output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = serde_json::to_string_pretty(&wasm_fingerprint::json_schema())?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, schema + "\n")?;
+            println!("💾 Esquema salvo em: {}", path);
+        }
+        None => println!("{}", schema),
+    }
+
+    Ok(())
+}
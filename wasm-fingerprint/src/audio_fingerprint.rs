@@ -1,8 +1,17 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{OfflineAudioContext, AudioBuffer};
+use web_sys::{
+    AudioBuffer, AudioNode, BaseAudioContext, BiquadFilterType, DynamicsCompressorNode,
+    OfflineAudioContext, OscillatorType,
+};
 use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+use crate::source::{ComponentStatus, Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::{AudioFingerprintData, AudioVariantData};
+use crate::config::{AudioConfig, AudioGraph};
+use crate::spectral::{SpectralFeatures, SPECTRAL_BANDS};
+use crate::utils::sha256_hex;
 
 /// Função exportada para JavaScript - Audio Fingerprinting conforme o guia
 #[wasm_bindgen]
@@ -13,51 +22,253 @@ pub async fn get_audio_fingerprint() -> Result<f32, JsValue> {
 /// Função exportada para JavaScript - Audio Fingerprinting com hash SHA-256
 #[wasm_bindgen]
 pub async fn get_audio_fingerprint_hash() -> Result<String, JsValue> {
-    AudioFingerprint::collect().await
+    Ok(AudioFingerprint::collect().await?)
 }
 
-pub struct AudioFingerprint;
+/// Função exportada para JavaScript - vetor de características espectrais
+/// (centróide, rolloff, distorção harmônica e magnitudes das bandas)
+#[wasm_bindgen]
+pub async fn get_audio_features() -> Result<Vec<f64>, JsValue> {
+    Ok(AudioFingerprint::collect_with(&AudioConfig::default()).await?.feature_vector)
+}
+
+#[derive(Default)]
+pub struct AudioFingerprint {
+    config: AudioConfig,
+}
+
+/// Buffer renderizado por uma variante
+struct RenderedGraph {
+    samples: Vec<f32>,
+    sample_rate: f32,
+    /// Bandas do `AnalyserNode`, apenas na variante `Analyser`
+    analyser_bands: Vec<f64>,
+}
 
 impl AudioFingerprint {
+    /// Posições do buffer amostradas diretamente
+    const SAMPLE_POINTS: [usize; 6] = [100, 500, 1000, 2000, 3000, 4000];
+
+    const ANALYSER_FFT_SIZE: u32 = 2048;
+
+    /// Duração da resposta ao impulso do `ConvolverNode`, em segundos
+    const IMPULSE_SECONDS: f32 = 0.05;
+
+    pub fn new(config: AudioConfig) -> Self {
+        AudioFingerprint { config }
+    }
+
     /// Implementação completa com hash SHA-256
-    pub async fn collect() -> Result<String, JsValue> {
-        // Configuração conforme o guia: 1 canal, 5000 amostras, 44100 Hz
-        let context = OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(
-            1, 5000, 44100.0
+    pub async fn collect() -> Result<String, SourceError> {
+        Ok(Self::collect_with(&AudioConfig::default()).await?.hash)
+    }
+
+    /// Renderiza o grafo principal e as variantes configuradas, e retorna o
+    /// hash junto das métricas usadas para calculá-lo. Só a falha do grafo
+    /// principal falha a coleta; a de uma variante fica em `status`
+    pub async fn collect_with(config: &AudioConfig) -> Result<AudioFingerprintData, SourceError> {
+        let base = Self::render(config, AudioGraph::TriangleCompressor).await?;
+        let mut data = Self::analyze(&base.samples, base.sample_rate, config.oscillator_frequency);
+
+        for &graph in &config.graphs {
+            let variant = if graph == AudioGraph::TriangleCompressor {
+                AudioVariantData {
+                    hash: data.hash.clone(),
+                    feature_vector: data.feature_vector.clone(),
+                    status: ComponentStatus::Ok,
+                }
+            } else {
+                // Uma variante bloqueada ou silenciosa não invalida as demais
+                match Self::render(config, graph).await {
+                    Ok(rendered) => Self::analyze_variant(&rendered, config.oscillator_frequency),
+                    Err(error) => AudioVariantData {
+                        status: error.into(),
+                        ..AudioVariantData::default()
+                    },
+                }
+            };
+            data.variants.insert(graph.name().to_string(), variant);
+        }
+
+        Ok(data)
+    }
+
+    /// Monta e renderiza o grafo de uma variante; falha com
+    /// `SourceError::Silent` se o buffer renderizado for todo zero
+    async fn render(config: &AudioConfig, graph: AudioGraph) -> Result<RenderedGraph, SourceError> {
+        let offline = OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(
+            1, config.length, config.sample_rate
         )?;
+        // O grafo é montado pela interface comum a todos os contextos
+        let context: &BaseAudioContext = &offline;
+        let duration = config.length as f64 / config.sample_rate as f64;
 
-        // Criar oscilador
+        // Criar oscilador (10kHz por padrão)
         let oscillator = context.create_oscillator()?;
+        oscillator.set_type(match graph {
+            AudioGraph::SineCompressor => OscillatorType::Sine,
+            AudioGraph::SquareCompressor | AudioGraph::GainAutomation | AudioGraph::Convolver => {
+                OscillatorType::Square
+            }
+            AudioGraph::SawtoothCompressor | AudioGraph::BiquadFilter => OscillatorType::Sawtooth,
+            AudioGraph::TriangleCompressor | AudioGraph::Analyser => OscillatorType::Triangle,
+        });
+        oscillator.frequency().set_value(config.oscillator_frequency);
 
-        // Configurar tipo triangular e frequência 10kHz
-        oscillator.set_type(web_sys::OscillatorType::Triangle);
-        oscillator.frequency().set_value(10000.0);
+        let mut analyser = None;
+        let output: AudioNode = match graph {
+            AudioGraph::TriangleCompressor
+            | AudioGraph::SineCompressor
+            | AudioGraph::SquareCompressor
+            | AudioGraph::SawtoothCompressor => {
+                let compressor = Self::compressor(context)?;
+                oscillator.connect_with_audio_node(&compressor)?;
+                compressor.into()
+            }
+            AudioGraph::BiquadFilter => {
+                // Passa-banda estreito logo abaixo da fundamental
+                let filter = context.create_biquad_filter()?;
+                filter.set_type(BiquadFilterType::Bandpass);
+                filter.frequency().set_value(config.oscillator_frequency * 0.9);
+                filter.q().set_value(8.0);
+                oscillator.connect_with_audio_node(&filter)?;
+                filter.into()
+            }
+            AudioGraph::GainAutomation => {
+                let gain = context.create_gain()?;
+                let param = gain.gain();
+                param.set_value_at_time(0.0, 0.0)?;
+                param.linear_ramp_to_value_at_time(1.0, duration * 0.5)?;
+                param.exponential_ramp_to_value_at_time(0.01, duration)?;
+                oscillator.connect_with_audio_node(&gain)?;
+                gain.into()
+            }
+            AudioGraph::Analyser => {
+                let node = context.create_analyser()?;
+                node.set_fft_size(Self::ANALYSER_FFT_SIZE);
+                oscillator.connect_with_audio_node(&node)?;
+                analyser = Some(node.clone());
+                node.into()
+            }
+            AudioGraph::Convolver => {
+                let convolver = context.create_convolver()?;
+                convolver.set_normalize(false);
+                convolver.set_buffer(Some(&Self::impulse_response(context, config.sample_rate)?));
+                oscillator.connect_with_audio_node(&convolver)?;
+                convolver.into()
+            }
+        };
+
+        // Só o que chega ao destino do contexto é renderizado no buffer
+        output.connect_with_audio_node(&context.destination())?;
+
+        // Iniciar e renderizar
+        oscillator.start_with_when(0.0)?;
+        let buffer = Self::start_rendering(&offline).await?;
+
+        // Espectro visto pelo AnalyserNode ao final da renderização
+        let analyser_bands = match analyser {
+            Some(node) => {
+                let mut bins = vec![0u8; node.frequency_bin_count() as usize];
+                node.get_byte_frequency_data(&mut bins);
+                Self::summarize_bins(&bins)
+            }
+            None => Vec::new(),
+        };
+
+        Ok(RenderedGraph {
+            samples: Self::channel_data(&buffer)?,
+            sample_rate: buffer.sample_rate(),
+            analyser_bands,
+        })
+    }
+
+    async fn start_rendering(context: &OfflineAudioContext) -> Result<AudioBuffer, JsValue> {
+        let result = JsFuture::from(context.start_rendering()?).await?;
+        result.dyn_into::<AudioBuffer>()
+    }
+
+    /// Amostras do primeiro canal; um buffer todo em zero indica que o grafo
+    /// não chegou ao destino (ou que o navegador bloqueou o áudio) e não deve
+    /// ser transformado em hash
+    fn channel_data(buffer: &AudioBuffer) -> Result<Vec<f32>, SourceError> {
+        Self::audible(buffer.get_channel_data(0)?)
+    }
+
+    /// `SourceError::Silent` se todas as amostras forem zero
+    fn audible(samples: Vec<f32>) -> Result<Vec<f32>, SourceError> {
+        if samples.iter().all(|&x| x == 0.0) {
+            return Err(SourceError::Silent);
+        }
+        Ok(samples)
+    }
 
-        // Criar compressor dinâmico com parâmetros específicos
+    /// Compressor dinâmico com os parâmetros do guia
+    fn compressor(context: &BaseAudioContext) -> Result<DynamicsCompressorNode, JsValue> {
         let compressor = context.create_dynamics_compressor()?;
         compressor.threshold().set_value(-50.0);
         compressor.knee().set_value(40.0);
         compressor.ratio().set_value(12.0);
         compressor.attack().set_value(0.0);
         compressor.release().set_value(0.25);
+        Ok(compressor)
+    }
 
-        // Conectar grafo de áudio
-        // Note: OfflineAudioContext doesn't have destination() in web-sys
-        // We connect directly to the compressor which is the final node
-        oscillator.connect_with_audio_node(&compressor)?;
+    /// Resposta ao impulso sintética no formato do `ConvolverNode`
+    fn impulse_response(context: &BaseAudioContext, sample_rate: f32) -> Result<AudioBuffer, JsValue> {
+        let impulse = Self::impulse_samples(sample_rate);
+        let buffer = context.create_buffer(1, impulse.len() as u32, sample_rate)?;
+        buffer.copy_to_channel(&impulse, 0)?;
+        Ok(buffer)
+    }
 
-        // For OfflineAudioContext, the compressor is effectively our destination
-        // No need to explicitly connect to destination
+    /// Amostras da resposta ao impulso: ruído pseudoaleatório determinístico
+    /// com decaimento exponencial, com `IMPULSE_SECONDS` de duração
+    fn impulse_samples(sample_rate: f32) -> Vec<f32> {
+        let length = (sample_rate * Self::IMPULSE_SECONDS) as u32;
+        let mut state: u32 = 0x2545_F491;
+        (0..length)
+            .map(|i| {
+                // LCG de Numerical Recipes
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                noise * (-(i as f32) / length as f32 * 5.0).exp()
+            })
+            .collect()
+    }
 
-        // Iniciar e renderizar
-        oscillator.start_with_when(0.0)?;
-        let promise = context.start_rendering()?;
-        let result = JsFuture::from(promise).await?;
-        let buffer = result.dyn_into::<AudioBuffer>()?;
+    /// Média dos bins do AnalyserNode em `SPECTRAL_BANDS` bandas, em 0..1
+    fn summarize_bins(bins: &[u8]) -> Vec<f64> {
+        let band_size = (bins.len() / SPECTRAL_BANDS).max(1);
+        bins.chunks(band_size)
+            .take(SPECTRAL_BANDS)
+            .map(|band| band.iter().map(|&b| b as f64).sum::<f64>() / (band.len() as f64 * 255.0))
+            .collect()
+    }
 
-        // Processar buffer para criar fingerprint
-        let channel_data = buffer.get_channel_data(0)?;
+    /// Sub-hash e vetor de características de uma variante
+    fn analyze_variant(rendered: &RenderedGraph, fundamental: f32) -> AudioVariantData {
+        let analysis = Self::analyze(&rendered.samples, rendered.sample_rate, fundamental);
+        if rendered.analyser_bands.is_empty() {
+            return AudioVariantData {
+                hash: analysis.hash,
+                feature_vector: analysis.feature_vector,
+                status: ComponentStatus::Ok,
+            };
+        }
 
+        let mut feature_vector = analysis.feature_vector;
+        feature_vector.extend(&rendered.analyser_bands);
+        AudioVariantData {
+            hash: sha256_hex(&format!("{}|analyser:{:?}", analysis.hash, rendered.analyser_bands)),
+            feature_vector,
+            status: ComponentStatus::Ok,
+        }
+    }
+
+    /// Calcula as métricas e o hash a partir das amostras renderizadas;
+    /// `fundamental` é a frequência do oscilador usada na renderização
+    pub fn analyze(channel_data: &[f32], sample_rate: f32, fundamental: f32) -> AudioFingerprintData {
         // Calcular métricas detalhadas
         let sum: f32 = channel_data.iter().map(|x| x.abs()).sum();
         let avg = sum / channel_data.len() as f32;
@@ -65,7 +276,7 @@ impl AudioFingerprint {
         let min_val = channel_data.iter().fold(0.0f32, |acc, &x| acc.min(x.abs()));
 
         // Amostrar pontos específicos para maior entropia
-        let samples: Vec<i32> = vec![100, 500, 1000, 2000, 3000, 4000]
+        let samples: Vec<i32> = Self::SAMPLE_POINTS
             .iter()
             .filter_map(|&idx| {
                 if idx < channel_data.len() {
@@ -96,39 +307,185 @@ impl AudioFingerprint {
         hasher.update(fingerprint_data.as_bytes());
         let hash_result = hasher.finalize();
 
-        Ok(format!("{:x}", hash_result))
+        let spectral = SpectralFeatures::analyze(channel_data, sample_rate, fundamental);
+
+        AudioFingerprintData {
+            hash: format!("{:x}", hash_result),
+            sample_rate: sample_rate.round() as i32,
+            buffer_length: channel_data.len() as u32,
+            sum: sum as f64,
+            avg: avg as f64,
+            max: max_val as f64,
+            samples,
+            frequency_bins: frequency_bins.into_iter().map(f64::from).collect(),
+            feature_vector: spectral.feature_vector(),
+            spectral,
+            variants: BTreeMap::new(),
+        }
     }
 
     /// Implementação simples que retorna apenas a soma (conforme guia original)
     pub async fn collect_simple() -> Result<f32, JsValue> {
-        let context = OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(
+        let offline = OfflineAudioContext::new_with_number_of_channels_and_length_and_sample_rate(
             1, 5000, 44100.0
         )?;
+        let context: &BaseAudioContext = &offline;
 
         let oscillator = context.create_oscillator()?;
         oscillator.set_type(web_sys::OscillatorType::Triangle);
         oscillator.frequency().set_value(10000.0);
 
-        let compressor = context.create_dynamics_compressor()?;
-        compressor.threshold().set_value(-50.0);
-        compressor.knee().set_value(40.0);
-        compressor.ratio().set_value(12.0);
-        compressor.attack().set_value(0.0);
-        compressor.release().set_value(0.25);
+        let compressor = Self::compressor(context)?;
 
-        // Connect audio nodes (compressor is final node for OfflineAudioContext)
         oscillator.connect_with_audio_node(&compressor)?;
+        compressor.connect_with_audio_node(&context.destination())?;
 
         oscillator.start_with_when(0.0)?;
-        let promise = context.start_rendering()?;
-        let result = JsFuture::from(promise).await?;
-        let buffer = result.dyn_into::<AudioBuffer>()?;
+        let buffer = Self::start_rendering(&offline).await?;
 
-        let channel_data = buffer.get_channel_data(0)?;
+        let channel_data = Self::channel_data(&buffer)?;
         let sum: f32 = channel_data.iter().map(|x| x.abs()).sum();
 
         Ok(sum)
     }
+}
+
+impl FingerprintSource for AudioFingerprint {
+    fn name(&self) -> &str {
+        "audio"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Stable
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Audio
+    }
+
+    fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            if js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("OfflineAudioContext"))
+                .map(|has| !has)
+                .unwrap_or(true)
+            {
+                return Err(SourceError::Unsupported);
+            }
+
+            let audio = AudioFingerprint::collect_with(&self.config).await?;
+            Ok(SourceOutput::Audio(audio))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    /// Senoide de `frequency` Hz com `length` amostras
+    fn sine(frequency: f32, length: usize) -> Vec<f32> {
+        (0..length)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn analyze_summarizes_the_buffer() {
+        let samples = sine(1000.0, 5000);
+        let data = AudioFingerprint::analyze(&samples, SAMPLE_RATE, 1000.0);
+
+        assert_eq!(data.sample_rate, 44100);
+        assert_eq!(data.buffer_length, 5000);
+        assert!((data.max - 0.5).abs() < 1e-3);
+        assert!((data.avg - data.sum / 5000.0).abs() < 1e-6);
+        // Média de |sen| é 2/π da amplitude
+        assert!((data.avg - 0.5 * 2.0 / std::f64::consts::PI).abs() < 1e-2);
+        let expected: Vec<i32> = AudioFingerprint::SAMPLE_POINTS
+            .iter()
+            .map(|&i| (samples[i] * 1000000.0) as i32)
+            .collect();
+        assert_eq!(data.samples, expected);
+        assert_eq!(data.frequency_bins.len(), 10);
+        assert!(!data.feature_vector.is_empty());
+        assert!(data.variants.is_empty());
+    }
+
+    #[test]
+    fn analyze_hash_is_deterministic_and_sensitive() {
+        let samples = sine(1000.0, 5000);
+        let data = AudioFingerprint::analyze(&samples, SAMPLE_RATE, 1000.0);
+        assert_eq!(data.hash, AudioFingerprint::analyze(&samples, SAMPLE_RATE, 1000.0).hash);
+
+        let mut changed = samples.clone();
+        changed[1000] += 1e-3;
+        assert_ne!(data.hash, AudioFingerprint::analyze(&changed, SAMPLE_RATE, 1000.0).hash);
+    }
+
+    #[test]
+    fn short_buffers_skip_missing_sample_points() {
+        let data = AudioFingerprint::analyze(&sine(1000.0, 600), SAMPLE_RATE, 1000.0);
+        assert_eq!(data.samples.len(), 2);
+    }
+
+    #[test]
+    fn analyser_bands_extend_the_variant() {
+        let rendered = RenderedGraph {
+            samples: sine(1000.0, 5000),
+            sample_rate: SAMPLE_RATE,
+            analyser_bands: Vec::new(),
+        };
+        let plain = AudioFingerprint::analyze_variant(&rendered, 1000.0);
+        let with_bands = AudioFingerprint::analyze_variant(
+            &RenderedGraph { analyser_bands: vec![0.5; SPECTRAL_BANDS], ..rendered },
+            1000.0,
+        );
+
+        assert_eq!(plain.status, ComponentStatus::Ok);
+        assert_eq!(with_bands.feature_vector.len(), plain.feature_vector.len() + SPECTRAL_BANDS);
+        assert_ne!(plain.hash, with_bands.hash);
+    }
+
+    #[test]
+    fn bins_are_averaged_into_bands() {
+        let mut bins = vec![0u8; 1024];
+        bins[..512].fill(255);
+        let bands = AudioFingerprint::summarize_bins(&bins);
+
+        assert_eq!(bands.len(), SPECTRAL_BANDS);
+        assert!(bands[..SPECTRAL_BANDS / 2].iter().all(|&b| b == 1.0));
+        assert!(bands[SPECTRAL_BANDS / 2..].iter().all(|&b| b == 0.0));
+
+        // Menos bins que bandas: um bin por banda
+        assert_eq!(AudioFingerprint::summarize_bins(&[0, 51, 255]), vec![0.0, 0.2, 1.0]);
+        assert!(AudioFingerprint::summarize_bins(&[]).is_empty());
+    }
+
+    #[test]
+    fn impulse_is_deterministic_decaying_noise() {
+        let impulse = AudioFingerprint::impulse_samples(SAMPLE_RATE);
 
+        assert_eq!(impulse.len(), 2205);
+        assert_eq!(impulse, AudioFingerprint::impulse_samples(SAMPLE_RATE));
+        assert!(impulse.iter().all(|x| x.abs() <= 1.0));
 
-}
\ No newline at end of file
+        let energy = |part: &[f32]| part.iter().map(|x| x * x).sum::<f32>();
+        let quarter = impulse.len() / 4;
+        assert!(energy(&impulse[..quarter]) > 10.0 * energy(&impulse[impulse.len() - quarter..]));
+    }
+
+    #[test]
+    fn silent_buffer_is_reported() {
+        let error = AudioFingerprint::audible(vec![0.0, -0.0, 0.0]).unwrap_err();
+        assert_eq!(error, SourceError::Silent);
+        assert_eq!(ComponentStatus::from(error), ComponentStatus::Silent);
+        assert_eq!(AudioFingerprint::audible(Vec::new()), Err(SourceError::Silent));
+    }
+
+    #[test]
+    fn audible_buffer_is_kept() {
+        let samples = vec![0.0, 1e-7, 0.0];
+        assert_eq!(AudioFingerprint::audible(samples.clone()), Ok(samples));
+    }
+}
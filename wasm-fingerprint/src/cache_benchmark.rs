@@ -0,0 +1,408 @@
+//! Detecção da hierarquia de cache por pointer chasing
+//!
+//! Uma lista encadeada embaralhada é montada na memória linear do Wasm e
+//! percorrida com `p = buffer[p]`: cada acesso depende do anterior, então o
+//! tempo por acesso é a latência do nível de cache onde o working set cabe.
+//! Varrendo o tamanho do working set, a latência sobe em degraus nas
+//! capacidades de L1, L2 e L3. Varrendo o passo entre acessos dentro de
+//! blocos, ela para de subir no tamanho de linha.
+//!
+//! A ordem é embaralhada primeiro dentro de cada página e depois entre
+//! páginas, para que misses de TLB não apareçam como um nível de cache
+//! a mais.
+
+use std::hint::black_box;
+use wasm_bindgen::prelude::*;
+use crate::config::{CacheConfig, TimingConfig};
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::timing::{AdaptiveTimer, BenchmarkClock, Clock};
+use crate::{CacheProfile, LatencySample};
+
+/// Tamanho de página assumido para o embaralhamento, em bytes
+const PAGE_SIZE: usize = 4096;
+
+/// Distância entre os nós da varredura de working set; no máximo uma
+/// linha de cache, para que todas as linhas do buffer sejam tocadas
+const NODE_SPACING: usize = 64;
+
+/// Bloco percorrido em ordem na varredura de passos; maior que qualquer
+/// tamanho de linha esperado
+const LINE_BLOCK: usize = 512;
+
+/// Passos testados para o tamanho de linha, em bytes
+const STRIDES: [usize; 7] = [4, 8, 16, 32, 64, 128, 256];
+
+/// Working set da varredura de passos quando o L1 não foi detectado
+const DEFAULT_LINE_PROBE_SIZE: usize = 128 * 1024;
+
+/// Aumento de latência, em relação ao piso do nível atual, que indica o
+/// início do próximo nível
+const LEVEL_JUMP_RATIO: f64 = 1.3;
+
+/// Aumento entre pontos consecutivos que ainda faz parte da transição
+const TRANSITION_RATIO: f64 = 1.1;
+
+/// Fração da latência máxima a partir da qual o passo já cruza uma linha
+/// por acesso
+const LINE_PLATEAU_RATIO: f64 = 0.9;
+
+/// Função exportada para JavaScript - capacidades de cache e tamanho de
+/// linha inferidos
+#[wasm_bindgen]
+pub async fn get_cache_profile() -> Result<JsValue, JsValue> {
+    let benchmark = CacheBenchmark::default();
+    let clock = BenchmarkClock::acquire(&benchmark.timing).await;
+    let profile = benchmark.run_until(&clock, Deadline::none(&clock))?;
+    serde_wasm_bindgen::to_value(&profile).map_err(JsValue::from)
+}
+
+#[derive(Default)]
+pub struct CacheBenchmark {
+    config: CacheConfig,
+    timing: TimingConfig,
+}
+
+impl CacheBenchmark {
+    pub fn new(config: CacheConfig, timing: TimingConfig) -> Self {
+        CacheBenchmark { config, timing }
+    }
+
+    /// Executa as duas varreduras, verificando o prazo entre cada medição
+    pub fn run_until(&self, clock: &dyn Clock, deadline: Deadline<'_>) -> Result<CacheProfile, SourceError> {
+        let timer_profile = clock.characterize(&self.timing);
+        let timer = AdaptiveTimer::new(clock, &timer_profile, &self.timing);
+
+        let mut working_set = Vec::new();
+        for bytes in Self::working_set_sizes(&self.config) {
+            let chain = Self::working_set_chain(bytes);
+            working_set.push(LatencySample {
+                bytes: bytes as u32,
+                ns_per_access: self.measure(&timer, &chain),
+            });
+            deadline.check()?;
+        }
+
+        let levels = Self::infer_levels(&working_set);
+        let probe_size = levels
+            .first()
+            .map(|&l1| l1 as usize * 4)
+            .unwrap_or(DEFAULT_LINE_PROBE_SIZE);
+
+        let mut strides = Vec::new();
+        for stride in STRIDES {
+            let chain = Self::stride_chain(probe_size, stride);
+            strides.push(LatencySample {
+                bytes: stride as u32,
+                ns_per_access: self.measure(&timer, &chain),
+            });
+            deadline.check()?;
+        }
+
+        let kb = |level: Option<&u32>| level.map(|bytes| bytes / 1024);
+        Ok(CacheProfile {
+            l1_kb: kb(levels.first()),
+            l2_kb: kb(levels.get(1)),
+            l3_kb: kb(levels.get(2)),
+            line_size: Self::infer_line_size(&strides),
+            working_set,
+            strides,
+            clock: clock.name().to_string(),
+        })
+    }
+
+    /// Tamanhos de 2^k e 1,5 x 2^k bytes entre o mínimo e o máximo
+    pub fn working_set_sizes(config: &CacheConfig) -> Vec<usize> {
+        let min = (config.min_size_kb.max(4) as usize) * 1024;
+        let max = (config.max_size_kb as usize) * 1024;
+        let mut sizes = Vec::new();
+        let mut size = min.next_power_of_two();
+        while size <= max {
+            sizes.push(size);
+            if size + size / 2 <= max {
+                sizes.push(size + size / 2);
+            }
+            size *= 2;
+        }
+        sizes
+    }
+
+    /// Menor tempo por acesso, em nanossegundos, entre as repetições
+    fn measure(&self, timer: &AdaptiveTimer, chain: &PointerChain) -> f64 {
+        // Aquecimento: uma volta completa carrega o working set no cache
+        black_box(chain.chase(chain.nodes as u32));
+
+        let mut best = f64::INFINITY;
+        for _ in 0..self.config.repetitions.max(1) {
+            let measurement = timer.measure(self.config.accesses, |accesses| chain.chase(accesses));
+            best = best.min(measurement.per_iteration() * 1_000_000.0);
+        }
+        best
+    }
+
+    /// Um nó a cada `NODE_SPACING` bytes de um buffer de `bytes` bytes
+    fn working_set_chain(bytes: usize) -> PointerChain {
+        let offsets: Vec<usize> = (0..(bytes / NODE_SPACING).max(1))
+            .map(|node| node * NODE_SPACING)
+            .collect();
+        PointerChain::new(bytes, Self::shuffle_by_page(offsets, bytes as u64))
+    }
+
+    /// Cada bloco de `LINE_BLOCK` bytes é percorrido em ordem, com acessos a
+    /// cada `stride` bytes; a ordem dos blocos é embaralhada
+    fn stride_chain(bytes: usize, stride: usize) -> PointerChain {
+        let bytes = bytes.max(LINE_BLOCK);
+        let blocks: Vec<usize> = (0..bytes / LINE_BLOCK).map(|block| block * LINE_BLOCK).collect();
+
+        let mut offsets = Vec::with_capacity(bytes / stride);
+        for block in Self::shuffle_by_page(blocks, (bytes ^ stride) as u64) {
+            offsets.extend((0..LINE_BLOCK / stride).map(|i| block + i * stride));
+        }
+        PointerChain::new(bytes, offsets)
+    }
+
+    /// Embaralha os offsets dentro de cada página e depois a ordem das
+    /// páginas, mantendo juntos os offsets de uma mesma página
+    fn shuffle_by_page(offsets: Vec<usize>, seed: u64) -> Vec<usize> {
+        let mut rng = XorShift::new(seed);
+        let mut pages: Vec<Vec<usize>> = Vec::new();
+        for offset in offsets {
+            match pages.last_mut() {
+                Some(page) if page[0] / PAGE_SIZE == offset / PAGE_SIZE => page.push(offset),
+                _ => pages.push(vec![offset]),
+            }
+        }
+
+        rng.shuffle(&mut pages);
+        for page in &mut pages {
+            rng.shuffle(page);
+        }
+        pages.into_iter().flatten().collect()
+    }
+
+    /// Capacidades (em bytes) de cada nível: o último tamanho antes de cada
+    /// degrau de latência, em ordem crescente
+    pub fn infer_levels(samples: &[LatencySample]) -> Vec<u32> {
+        let mut levels = Vec::new();
+        let mut floor = match samples.first() {
+            Some(sample) => sample.ns_per_access,
+            None => return levels,
+        };
+
+        let mut k = 1;
+        while k < samples.len() {
+            if samples[k].ns_per_access > floor * LEVEL_JUMP_RATIO {
+                levels.push(samples[k - 1].bytes);
+                // A transição entre níveis ocupa alguns pontos da varredura
+                while k + 1 < samples.len()
+                    && samples[k + 1].ns_per_access > samples[k].ns_per_access * TRANSITION_RATIO
+                {
+                    k += 1;
+                }
+                floor = samples[k].ns_per_access;
+            } else {
+                floor = floor.min(samples[k].ns_per_access);
+            }
+            k += 1;
+        }
+        levels
+    }
+
+    /// Menor passo cuja latência já está no patamar de um miss por acesso;
+    /// `None` se a latência não varia com o passo
+    pub fn infer_line_size(samples: &[LatencySample]) -> Option<u32> {
+        let max = samples.iter().map(|s| s.ns_per_access).fold(f64::NEG_INFINITY, f64::max);
+        let min = samples.iter().map(|s| s.ns_per_access).fold(f64::INFINITY, f64::min);
+        if samples.is_empty() || max <= min * LEVEL_JUMP_RATIO {
+            return None;
+        }
+        samples
+            .iter()
+            .find(|sample| sample.ns_per_access >= max * LINE_PLATEAU_RATIO)
+            .map(|sample| sample.bytes)
+    }
+}
+
+/// Lista circular em um buffer de `u32`: cada nó guarda o índice do
+/// próximo
+struct PointerChain {
+    buffer: Vec<u32>,
+    start: u32,
+    nodes: usize,
+}
+
+impl PointerChain {
+    /// Liga os offsets (em bytes) na ordem dada, fechando o ciclo
+    fn new(bytes: usize, offsets: Vec<usize>) -> Self {
+        let mut buffer = vec![0u32; (bytes / 4).max(1)];
+        for (i, &offset) in offsets.iter().enumerate() {
+            let next = offsets[(i + 1) % offsets.len()];
+            buffer[offset / 4] = (next / 4) as u32;
+        }
+        PointerChain {
+            buffer,
+            start: offsets.first().map_or(0, |&offset| (offset / 4) as u32),
+            nodes: offsets.len(),
+        }
+    }
+
+    /// Segue `accesses` ponteiros a partir do início
+    fn chase(&self, accesses: u32) -> u32 {
+        let mut index = self.start;
+        for _ in 0..accesses {
+            index = self.buffer[index as usize];
+        }
+        index
+    }
+}
+
+/// Gerador xorshift64 com semente fixa: a mesma lista é gerada em toda
+/// coleta
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Fisher-Yates
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+impl FingerprintSource for CacheBenchmark {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    // A geometria real é fixa por modelo de CPU, mas a inferida depende de
+    // onde os degraus caem numa curva medida: com relógio grosso, carga na
+    // máquina ou L2/L3 compartilhados, o mesmo dispositivo alterna entre
+    // tamanhos vizinhos. Fica fora do identificador estável.
+    fn stability(&self) -> Stability {
+        Stability::Volatile
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Cache
+    }
+
+    fn collect<'a>(&'a self, deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            // Relógio da coleta, compartilhado com os demais benchmarks
+            Ok(SourceOutput::Cache(self.run_until(deadline.clock(), deadline)?))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KB: u32 = 1024;
+
+    fn curve(points: &[(u32, f64)]) -> Vec<LatencySample> {
+        points
+            .iter()
+            .map(|&(bytes, ns_per_access)| LatencySample { bytes, ns_per_access })
+            .collect()
+    }
+
+    #[test]
+    fn staircase_gives_three_levels() {
+        let samples = curve(&[
+            (16 * KB, 1.0),
+            (24 * KB, 1.02),
+            (32 * KB, 0.98),
+            // Transição de dois pontos entre L1 e L2
+            (48 * KB, 2.5),
+            (64 * KB, 4.0),
+            (128 * KB, 4.1),
+            (256 * KB, 3.9),
+            (512 * KB, 12.0),
+            (1024 * KB, 12.5),
+            (4096 * KB, 12.2),
+            (8192 * KB, 12.0),
+            (16384 * KB, 60.0),
+            (32768 * KB, 62.0),
+        ]);
+
+        assert_eq!(
+            CacheBenchmark::infer_levels(&samples),
+            vec![32 * KB, 256 * KB, 8192 * KB]
+        );
+    }
+
+    #[test]
+    fn flat_curve_has_no_levels() {
+        let samples = curve(&[
+            (16 * KB, 1.0),
+            (32 * KB, 1.1),
+            (64 * KB, 0.95),
+            (128 * KB, 1.2),
+            (256 * KB, 1.05),
+        ]);
+
+        assert!(CacheBenchmark::infer_levels(&samples).is_empty());
+        assert_eq!(CacheBenchmark::infer_line_size(&samples), None);
+    }
+
+    #[test]
+    fn empty_curve_is_handled() {
+        assert!(CacheBenchmark::infer_levels(&[]).is_empty());
+        assert_eq!(CacheBenchmark::infer_line_size(&[]), None);
+    }
+
+    #[test]
+    fn line_size_is_start_of_plateau() {
+        let samples = curve(&[
+            (4, 1.0),
+            (8, 1.2),
+            (16, 1.6),
+            (32, 2.6),
+            (64, 5.0),
+            (128, 5.1),
+            (256, 4.9),
+        ]);
+
+        assert_eq!(CacheBenchmark::infer_line_size(&samples), Some(64));
+    }
+
+    #[test]
+    fn working_set_sizes_step_by_half_powers_of_two() {
+        let config = CacheConfig {
+            min_size_kb: 16,
+            max_size_kb: 128,
+            ..CacheConfig::default()
+        };
+        let kb: Vec<usize> = CacheBenchmark::working_set_sizes(&config)
+            .into_iter()
+            .map(|bytes| bytes / 1024)
+            .collect();
+
+        assert_eq!(kb, vec![16, 24, 32, 48, 64, 96, 128]);
+    }
+
+    #[test]
+    fn working_set_sizes_clamp_the_range() {
+        // Mínimo abaixo de 4 KB é elevado; fora de potência de 2, arredondado
+        let tiny = CacheConfig { min_size_kb: 1, max_size_kb: 8, ..CacheConfig::default() };
+        assert_eq!(CacheBenchmark::working_set_sizes(&tiny), vec![4096, 6144, 8192]);
+
+        let odd = CacheConfig { min_size_kb: 20, max_size_kb: 40, ..CacheConfig::default() };
+        assert_eq!(CacheBenchmark::working_set_sizes(&odd), vec![32 * 1024]);
+
+        let inverted = CacheConfig { min_size_kb: 64, max_size_kb: 16, ..CacheConfig::default() };
+        assert!(CacheBenchmark::working_set_sizes(&inverted).is_empty());
+    }
+}
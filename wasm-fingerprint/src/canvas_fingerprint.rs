@@ -4,7 +4,11 @@ use web_sys::{
     CanvasRenderingContext2d, HtmlCanvasElement,
 };
 use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
 use crate::dom_utils::get_document;
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceFuture, SourceOutput, Stability};
+use crate::CanvasFingerprintData;
+use crate::config::CanvasConfig;
 
 /// Função exportada para JavaScript - Canvas Fingerprinting
 #[wasm_bindgen]
@@ -12,18 +16,46 @@ pub fn get_canvas_fingerprint() -> Result<String, JsValue> {
     CanvasFingerprint::collect()
 }
 
-pub struct CanvasFingerprint;
+#[derive(Default)]
+pub struct CanvasFingerprint {
+    config: CanvasConfig,
+}
+
+/// Área retangular da cena, com hash próprio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanvasRegion {
+    pub name: &'static str,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
 
 impl CanvasFingerprint {
+    /// Dimensões da imagem reduzida usada no hash perceptual (dHash)
+    const PHASH_WIDTH: usize = 9;
+    const PHASH_HEIGHT: usize = 8;
+
+    /// Cor usada no padrão sólido da detecção de ruído
+    const SOLID_COLOR: [u8; 3] = [102, 51, 153];
+
+    pub fn new(config: CanvasConfig) -> Self {
+        CanvasFingerprint { config }
+    }
+
     pub fn collect() -> Result<String, JsValue> {
+        Ok(Self::collect_with(&CanvasConfig::default())?.hash)
+    }
+
+    pub fn collect_with(config: &CanvasConfig) -> Result<CanvasFingerprintData, JsValue> {
         let document = get_document()?;
 
         // Create hidden canvas
         let canvas = document.create_element("canvas")?
             .dyn_into::<HtmlCanvasElement>()?;
 
-        canvas.set_width(280);
-        canvas.set_height(60);
+        canvas.set_width(config.width);
+        canvas.set_height(config.height);
         // Note: style() method not available, need to use different approach
         // canvas.style().set_property("display", "none")?;
 
@@ -32,66 +64,562 @@ impl CanvasFingerprint {
             .ok_or("Failed to get 2d context")?
             .dyn_into::<CanvasRenderingContext2d>()?;
 
-        // Draw complex scene for fingerprinting
-        Self::draw_fingerprint_scene(&ctx)?;
+        // Draw complex scene for fingerprinting, one region at a time
+        let regions = Self::regions(config.width, config.height);
+        Self::draw_scene(&ctx, &regions)?;
+        let pixels = Self::read_pixels(&ctx, config)?;
 
-        // Get canvas data
-        let data_url = canvas.to_data_url()?;
+        let data_url = if config.include_data_url {
+            canvas.to_data_url()?
+        } else {
+            String::new()
+        };
 
-        // Calculate SHA-256 hash of the data URL
-        let mut hasher = Sha256::new();
-        hasher.update(data_url.as_bytes());
-        let hash_result = hasher.finalize();
+        let mut data = Self::analyze(&pixels, config.width, config.height, &regions, data_url);
+
+        if config.detect_noise {
+            // Mesma cena de novo: sem ruído injetado, a leitura é idêntica
+            ctx.clear_rect(0.0, 0.0, config.width as f64, config.height as f64);
+            Self::draw_scene(&ctx, &regions)?;
+            let repeated = Self::read_pixels(&ctx, config)?;
+
+            // Cor sólida opaca: qualquer pixel diferente foi alterado na leitura
+            ctx.set_fill_style_str(&format!(
+                "rgb({}, {}, {})",
+                Self::SOLID_COLOR[0], Self::SOLID_COLOR[1], Self::SOLID_COLOR[2]
+            ));
+            ctx.fill_rect(0.0, 0.0, config.width as f64, config.height as f64);
+            let solid = Self::read_pixels(&ctx, config)?;
+
+            data.noise_rate = Self::noise_rate(&pixels, &repeated, &solid);
+            data.canvas_noise_detected = data.noise_rate > 0.0;
+        }
 
-        Ok(format!("{:x}", hash_result))
+        Ok(data)
     }
 
-    fn draw_fingerprint_scene(ctx: &CanvasRenderingContext2d) -> Result<(), JsValue> {
-        // Text with special unicode characters
-        ctx.set_text_baseline("alphabetic");
-        ctx.set_fill_style_str("#f60");
-        ctx.fill_rect(125.0, 1.0, 62.0, 20.0);
+    fn draw_scene(ctx: &CanvasRenderingContext2d, regions: &[CanvasRegion]) -> Result<(), JsValue> {
+        for region in regions {
+            Self::draw_region(ctx, region)?;
+        }
+        Ok(())
+    }
 
-        ctx.set_fill_style_str("#069");
-        ctx.set_font("11pt Arial");
-        ctx.fill_text("Cwm fjordbank glyphs vext quiz, 😃", 2.0, 15.0)?;
+    /// Ler os pixels RGBA de volta
+    fn read_pixels(ctx: &CanvasRenderingContext2d, config: &CanvasConfig) -> Result<Vec<u8>, JsValue> {
+        Ok(ctx
+            .get_image_data(0.0, 0.0, config.width as f64, config.height as f64)?
+            .data()
+            .0)
+    }
 
-        // Complex gradient - create_linear_gradient not available in current web-sys
-        ctx.set_fill_style_str("rgba(100, 150, 200, 0.5)");
-        ctx.fill_rect(0.0, 20.0, 280.0, 20.0);
+    /// Fração dos pixels que diferem entre duas leituras da mesma cena
+    pub fn pixel_difference_rate(a: &[u8], b: &[u8]) -> f64 {
+        let total = a.len().max(b.len()) / 4;
+        if total == 0 {
+            return 0.0;
+        }
+        let matching = a.chunks(4).zip(b.chunks(4)).filter(|(p, q)| p == q).count();
+        (total - matching) as f64 / total as f64
+    }
 
-        // Bezier curves
-        ctx.set_stroke_style_str("rgb(120, 186, 176)");
-        ctx.begin_path();
-        ctx.move_to(20.0, 40.0);
-        ctx.bezier_curve_to(20.0, 50.0, 200.0, 50.0, 200.0, 40.0);
-        ctx.stroke();
+    /// Fração dos pixels de uma área pintada com `color` (opaca) cuja
+    /// leitura não corresponde à cor pintada
+    pub fn solid_deviation_rate(pixels: &[u8], color: [u8; 3]) -> f64 {
+        let total = pixels.len() / 4;
+        if total == 0 {
+            return 0.0;
+        }
+        let deviating = pixels
+            .chunks(4)
+            .filter(|p| p[..3] != color || p[3] != 255)
+            .count();
+        deviating as f64 / total as f64
+    }
+
+    /// Taxa de ruído da leitura: a maior entre a diferença de duas leituras
+    /// da cena e o desvio da área pintada com `SOLID_COLOR`
+    pub fn noise_rate(pixels: &[u8], repeated: &[u8], solid: &[u8]) -> f64 {
+        Self::pixel_difference_rate(pixels, repeated)
+            .max(Self::solid_deviation_rate(solid, Self::SOLID_COLOR))
+    }
+
+    /// Calcula o hash dos pixels, os hashes por região e o hash perceptual
+    /// a partir do buffer RGBA lido do canvas
+    pub fn analyze(
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        regions: &[CanvasRegion],
+        data_url: String,
+    ) -> CanvasFingerprintData {
+        let region_hashes = regions
+            .iter()
+            .map(|region| {
+                let hash = Self::hash_bytes(&Self::region_pixels(pixels, width, region));
+                (region.name.to_string(), hash)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        CanvasFingerprintData {
+            hash: Self::hash_bytes(pixels),
+            data_url,
+            width,
+            height,
+            region_hashes,
+            perceptual_hash: Self::perceptual_hash(pixels, width, height),
+            canvas_noise_detected: false,
+            noise_rate: 0.0,
+        }
+    }
+
+    /// Divisão da cena em linhas de tiles; cada tile isola uma primitiva de
+    /// desenho, para medir quanto cada uma contribui para a entropia. Os
+    /// números são a largura relativa de cada tile na linha.
+    const LAYOUT: [&'static [(&'static str, u32)]; 4] = [
+        &[("text", 3), ("blend", 1)],
+        &[("emoji", 1)],
+        &[("linear_gradient", 1), ("radial_gradient", 1), ("shadow", 1), ("bezier", 1)],
+        &[("composite", 1), ("subpixel_text", 1)],
+    ];
+
+    /// Modos de `globalCompositeOperation` desenhados no tile `composite`;
+    /// todos afetam apenas a área desenhada
+    const COMPOSITE_MODES: [&'static str; 8] = [
+        "multiply", "screen", "overlay", "color-dodge",
+        "soft-light", "difference", "hue", "luminosity",
+    ];
+
+    /// Emojis com modificadores, sequências ZWJ e bandeiras, cuja
+    /// renderização depende da fonte de emoji do sistema
+    const EMOJI_ROW: &'static str = "😃🎨🦄🧬🫠👍🏽👨‍👩‍👧‍👦🏳️‍🌈🇧🇷✨";
+
+    /// Tiles da cena, de cima para baixo e da esquerda para a direita
+    pub fn regions(width: u32, height: u32) -> Vec<CanvasRegion> {
+        let rows = Self::LAYOUT.len() as u32;
+        let mut regions = Vec::new();
+
+        for (row, tiles) in Self::LAYOUT.iter().enumerate() {
+            let y = height * row as u32 / rows;
+            let tile_height = height * (row as u32 + 1) / rows - y;
+            let total: u32 = tiles.iter().map(|(_, weight)| weight).sum();
+
+            let mut offset = 0;
+            for &(name, weight) in tiles.iter() {
+                let x = width * offset / total;
+                offset += weight;
+                regions.push(CanvasRegion {
+                    name,
+                    x,
+                    y,
+                    width: width * offset / total - x,
+                    height: tile_height,
+                });
+            }
+        }
+        regions
+    }
 
-        // Arc with transparency
-        ctx.set_global_composite_operation("multiply")?;
-        ctx.set_fill_style_str("rgba(255, 125, 0, 0.5)");
+    /// Desenha o conteúdo de uma região, recortado aos seus limites e em
+    /// coordenadas locais
+    fn draw_region(ctx: &CanvasRenderingContext2d, region: &CanvasRegion) -> Result<(), JsValue> {
+        let (w, h) = (region.width as f64, region.height as f64);
+
+        ctx.save();
         ctx.begin_path();
-        ctx.arc(50.0, 50.0, 20.0, 0.0, std::f64::consts::PI * 2.0)?;
-        ctx.fill();
-
-        // Pattern
-        ctx.set_global_composite_operation("source-over")?;
-
-        // Draw text with various fonts
-        let fonts = vec![
-            "10pt no-real-font-123",
-            "11pt Arial",
-            "20pt Arial",
-            "12pt 'Courier New'",
-        ];
-
-        for (i, font) in fonts.iter().enumerate() {
-            ctx.set_font(font);
-            ctx.set_fill_style_str(&format!("rgba({}, {}, {}, 0.8)",
-                i * 50, 255 - i * 50, (i * 30) % 255));
-            ctx.fill_text(&format!("Test {}", i), 10.0 + (i as f64 * 70.0), 55.0)?;
+        ctx.rect(region.x as f64, region.y as f64, w, h);
+        ctx.clip();
+        ctx.translate(region.x as f64, region.y as f64)?;
+
+        match region.name {
+            "text" => {
+                // Text with special unicode characters
+                ctx.set_text_baseline("alphabetic");
+                ctx.set_fill_style_str("#f60");
+                ctx.fill_rect(w * 0.6, 1.0, w * 0.3, 20.0);
+
+                ctx.set_fill_style_str("#069");
+                ctx.set_font("11pt Arial");
+                ctx.fill_text("Cwm fjordbank glyphs vext quiz", 2.0, 15.0)?;
+
+                // Draw text with various fonts
+                let fonts = [
+                    "10pt no-real-font-123",
+                    "11pt Arial",
+                    "20pt Arial",
+                    "12pt 'Courier New'",
+                ];
+
+                for (i, font) in fonts.iter().enumerate() {
+                    ctx.set_font(font);
+                    ctx.set_fill_style_str(&format!("rgba({}, {}, {}, 0.8)",
+                        i * 50, 255 - i * 50, (i * 30) % 255));
+                    ctx.fill_text(&format!("Test {}", i), 2.0 + (i as f64 * w / 4.0), h - 2.0)?;
+                }
+            }
+            "emoji" => {
+                ctx.set_text_baseline("middle");
+                ctx.set_font("16pt Arial");
+                ctx.fill_text(Self::EMOJI_ROW, 2.0, h / 2.0)?;
+            }
+            "bezier" => {
+                // Bezier curves
+                ctx.set_stroke_style_str("rgb(120, 186, 176)");
+                ctx.set_line_width(2.0);
+                ctx.begin_path();
+                ctx.move_to(10.0, 5.0);
+                ctx.bezier_curve_to(10.0, h + 10.0, w - 10.0, h + 10.0, w - 10.0, 5.0);
+                ctx.stroke();
+            }
+            "blend" => {
+                ctx.set_fill_style_str("rgba(100, 150, 200, 0.5)");
+                ctx.fill_rect(0.0, 0.0, w, h);
+
+                // Arc with transparency
+                ctx.set_global_composite_operation("multiply")?;
+                ctx.set_fill_style_str("rgba(255, 125, 0, 0.5)");
+                ctx.begin_path();
+                ctx.arc(w / 2.0, h / 2.0, w.min(h) / 2.0 - 2.0, 0.0, std::f64::consts::PI * 2.0)?;
+                ctx.fill();
+            }
+            "linear_gradient" => {
+                // Interpolação de cores com alfa, em diagonal
+                let gradient = ctx.create_linear_gradient(0.0, 0.0, w, h);
+                gradient.add_color_stop(0.0, "rgba(255, 0, 128, 0.9)")?;
+                gradient.add_color_stop(0.5, "rgb(0, 200, 255)")?;
+                gradient.add_color_stop(1.0, "rgba(255, 240, 0, 0.6)")?;
+                ctx.set_fill_style_canvas_gradient(&gradient);
+                ctx.fill_rect(0.0, 0.0, w, h);
+            }
+            "radial_gradient" => {
+                // Círculos não concêntricos: o cone resultante depende da
+                // implementação do rasterizador
+                let gradient = ctx.create_radial_gradient(
+                    w * 0.35, h * 0.4, 1.0,
+                    w * 0.5, h * 0.5, w.max(h) * 0.6,
+                )?;
+                gradient.add_color_stop(0.0, "#fff")?;
+                gradient.add_color_stop(0.3, "rgba(80, 0, 160, 0.8)")?;
+                gradient.add_color_stop(1.0, "rgba(0, 120, 60, 0.2)")?;
+                ctx.set_fill_style_canvas_gradient(&gradient);
+                ctx.fill_rect(0.0, 0.0, w, h);
+            }
+            "shadow" => {
+                // Sombra desfocada, cujo kernel de blur varia entre navegadores
+                ctx.set_shadow_blur(6.0);
+                ctx.set_shadow_color("rgba(0, 0, 0, 0.7)");
+                ctx.set_shadow_offset_x(3.0);
+                ctx.set_shadow_offset_y(2.0);
+                ctx.set_fill_style_str("rgb(230, 90, 40)");
+                ctx.fill_rect(w * 0.2, h * 0.2, w * 0.45, h * 0.45);
+            }
+            "composite" => {
+                // Um disco sobre um fundo listrado para cada modo de composição
+                let cell = w / Self::COMPOSITE_MODES.len() as f64;
+                for (i, mode) in Self::COMPOSITE_MODES.iter().enumerate() {
+                    let x = i as f64 * cell;
+                    ctx.set_global_composite_operation("source-over")?;
+                    ctx.set_fill_style_str("rgb(40, 160, 220)");
+                    ctx.fill_rect(x, 0.0, cell, h / 2.0);
+                    ctx.set_fill_style_str("rgb(250, 200, 60)");
+                    ctx.fill_rect(x, h / 2.0, cell, h / 2.0);
+
+                    ctx.set_global_composite_operation(mode)?;
+                    ctx.set_fill_style_str("rgba(200, 30, 90, 0.75)");
+                    ctx.begin_path();
+                    ctx.arc(x + cell / 2.0, h / 2.0, cell.min(h) / 2.0 - 1.0, 0.0, std::f64::consts::PI * 2.0)?;
+                    ctx.fill();
+                }
+            }
+            "subpixel_text" => {
+                // Mesmo texto em posições e tamanhos fracionários: o
+                // anti-aliasing e o hinting de posições sub-pixel variam
+                ctx.set_fill_style_str("#222");
+                for i in 0..4 {
+                    let offset = i as f64 * 0.25;
+                    ctx.set_font(&format!("{}px sans-serif", 10.0 + offset));
+                    ctx.fill_text("Ag1", 2.0 + i as f64 * w / 4.0 + offset, h / 2.0 + offset)?;
+                    ctx.fill_text("Ag1", 2.0 + i as f64 * w / 4.0 - offset, h - 2.0 - offset)?;
+                }
+            }
+            _ => {}
         }
 
+        ctx.restore();
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn hash_bytes(bytes: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(bytes);
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Linhas RGBA de uma região, concatenadas
+    fn region_pixels(pixels: &[u8], width: u32, region: &CanvasRegion) -> Vec<u8> {
+        let stride = width as usize * 4;
+        let mut out = Vec::with_capacity(region.width as usize * region.height as usize * 4);
+        for row in region.y..region.y + region.height {
+            let start = row as usize * stride + region.x as usize * 4;
+            let end = start + region.width as usize * 4;
+            if let Some(line) = pixels.get(start..end) {
+                out.extend_from_slice(line);
+            }
+        }
+        out
+    }
+
+    /// Hash perceptual (dHash de 64 bits, em hexadecimal): a imagem é
+    /// reduzida a 9x8 tons de cinza e cada bit indica se um pixel é mais
+    /// claro que o vizinho à direita. Diferenças de anti-aliasing mudam poucos
+    /// bits; fontes diferentes mudam muitos.
+    pub fn perceptual_hash(pixels: &[u8], width: u32, height: u32) -> String {
+        let (width, height) = (width as usize, height as usize);
+        if width == 0 || height == 0 || pixels.len() < width * height * 4 {
+            return String::new();
+        }
+
+        // Média de luminância de cada célula, com fundo transparente = branco
+        let mut cells = [[0.0f64; Self::PHASH_WIDTH]; Self::PHASH_HEIGHT];
+        for (cy, row) in cells.iter_mut().enumerate() {
+            let (y0, y1) = Self::cell_bounds(cy, Self::PHASH_HEIGHT, height);
+            for (cx, cell) in row.iter_mut().enumerate() {
+                let (x0, x1) = Self::cell_bounds(cx, Self::PHASH_WIDTH, width);
+                let mut total = 0.0;
+                let mut count = 0.0;
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let offset = (y * width + x) * 4;
+                        let p = &pixels[offset..offset + 4];
+                        let luminance = 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+                        let alpha = p[3] as f64 / 255.0;
+                        total += luminance * alpha + 255.0 * (1.0 - alpha);
+                        count += 1.0;
+                    }
+                }
+                *cell = if count > 0.0 { total / count } else { 255.0 };
+            }
+        }
+
+        let mut bits: u64 = 0;
+        for row in &cells {
+            for pair in row.windows(2) {
+                bits = (bits << 1) | (pair[0] > pair[1]) as u64;
+            }
+        }
+        format!("{:016x}", bits)
+    }
+
+    /// Intervalo de pixels coberto pela célula `index` de `cells`, com ao
+    /// menos um pixel mesmo em imagens menores que a grade
+    fn cell_bounds(index: usize, cells: usize, size: usize) -> (usize, usize) {
+        let start = (index * size / cells).min(size - 1);
+        let end = ((index + 1) * size / cells).clamp(start + 1, size);
+        (start, end)
+    }
+
+    /// Distância de Hamming entre dois hashes perceptuais (0 a 64)
+    pub fn perceptual_distance(a: &str, b: &str) -> Option<u32> {
+        let a = u64::from_str_radix(a, 16).ok()?;
+        let b = u64::from_str_radix(b, 16).ok()?;
+        Some((a ^ b).count_ones())
+    }
+}
+
+impl FingerprintSource for CanvasFingerprint {
+    fn name(&self) -> &str {
+        "canvas"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Stable
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Canvas
+    }
+
+    fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let canvas = CanvasFingerprint::collect_with(&self.config)?;
+            Ok(SourceOutput::Canvas(canvas))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Buffer RGBA com a cor de cada pixel dada por `color(x, y)`
+    fn image(width: u32, height: u32, color: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| color(x, y))
+            .collect()
+    }
+
+    fn gray(value: u8) -> [u8; 4] {
+        [value, value, value, 255]
+    }
+
+    fn solid(width: u32, height: u32) -> Vec<u8> {
+        let [r, g, b] = CanvasFingerprint::SOLID_COLOR;
+        image(width, height, |_, _| [r, g, b, 255])
+    }
+
+    /// Gerador congruente linear, para ruído reproduzível sem dependências
+    fn noise(seed: &mut u32) -> u8 {
+        *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (*seed >> 24) as u8
+    }
+
+    #[test]
+    fn default_layout_tiles() {
+        let region = |name, x, y, width, height| CanvasRegion { name, x, y, width, height };
+        let config = CanvasConfig::default();
+
+        assert_eq!((config.width, config.height), (280, 120));
+        assert_eq!(
+            CanvasFingerprint::regions(config.width, config.height),
+            vec![
+                region("text", 0, 0, 210, 30),
+                region("blend", 210, 0, 70, 30),
+                region("emoji", 0, 30, 280, 30),
+                region("linear_gradient", 0, 60, 70, 30),
+                region("radial_gradient", 70, 60, 70, 30),
+                region("shadow", 140, 60, 70, 30),
+                region("bezier", 210, 60, 70, 30),
+                region("composite", 0, 90, 140, 30),
+                region("subpixel_text", 140, 90, 140, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn regions_cover_odd_sizes_without_overlap() {
+        let (width, height) = (301, 151);
+        let regions = CanvasFingerprint::regions(width, height);
+        let mut covered = vec![0u8; (width * height) as usize];
+        for region in &regions {
+            for y in region.y..region.y + region.height {
+                for x in region.x..region.x + region.width {
+                    covered[(y * width + x) as usize] += 1;
+                }
+            }
+        }
+
+        assert!(covered.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn region_hashes_change_only_where_pixels_change() {
+        let (width, height) = (400, 200);
+        let regions = CanvasFingerprint::regions(width, height);
+        let base = image(width, height, |x, y| gray((x + y) as u8));
+        // Um pixel alterado dentro do tile `shadow` (200..300, 100..150)
+        let changed = image(width, height, |x, y| {
+            if (x, y) == (250, 120) { gray(0) } else { gray((x + y) as u8) }
+        });
+
+        let a = CanvasFingerprint::analyze(&base, width, height, &regions, String::new());
+        let b = CanvasFingerprint::analyze(&changed, width, height, &regions, String::new());
+
+        assert_eq!(a.region_hashes.len(), regions.len());
+        assert_eq!(a.hash, CanvasFingerprint::hash_bytes(&base));
+        assert_ne!(a.hash, b.hash);
+        for region in &regions {
+            let same = a.region_hashes[region.name] == b.region_hashes[region.name];
+            assert_eq!(same, region.name != "shadow", "{}", region.name);
+        }
+    }
+
+    #[test]
+    fn region_pixels_are_row_slices() {
+        let pixels = image(4, 3, |x, y| gray((y * 4 + x) as u8));
+        let region = CanvasRegion { name: "tile", x: 1, y: 1, width: 2, height: 2 };
+
+        let values: Vec<u8> = CanvasFingerprint::region_pixels(&pixels, 4, &region)
+            .chunks(4)
+            .map(|p| p[0])
+            .collect();
+        assert_eq!(values, vec![5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn perceptual_hash_of_gradients() {
+        let phash = CanvasFingerprint::perceptual_hash;
+        // Mais claro à esquerda: todo pixel reduzido é mais claro que o vizinho
+        let darkening = image(18, 8, |x, _| gray(255 - x as u8 * 10));
+        let brightening = image(18, 8, |x, _| gray(x as u8 * 10));
+        let vertical = image(18, 8, |_, y| gray(y as u8 * 30));
+
+        assert_eq!(phash(&darkening, 18, 8), "ffffffffffffffff");
+        assert_eq!(phash(&brightening, 18, 8), "0000000000000000");
+        assert_eq!(phash(&vertical, 18, 8), "0000000000000000");
+        // Transparente conta como branco
+        assert_eq!(phash(&image(18, 8, |_, _| [0, 0, 0, 0]), 18, 8), "0000000000000000");
+        assert_eq!(phash(&[], 0, 0), "");
+        assert_eq!(phash(&darkening, 18, 9), "");
+    }
+
+    #[test]
+    fn perceptual_hash_tolerates_small_changes() {
+        let (width, height) = (90, 80);
+        let base = image(width, height, |x, y| gray((200 - x - y) as u8));
+        let mut seed = 7;
+        let jittered: Vec<u8> = base.iter()
+            .enumerate()
+            .map(|(i, &v)| if i % 4 == 3 { v } else { v ^ (noise(&mut seed) & 1) })
+            .collect();
+
+        let a = CanvasFingerprint::perceptual_hash(&base, width, height);
+        let b = CanvasFingerprint::perceptual_hash(&jittered, width, height);
+        assert_eq!(CanvasFingerprint::perceptual_distance(&a, &b), Some(0));
+        assert_eq!(
+            CanvasFingerprint::perceptual_distance("ffffffffffffffff", "0000000000000000"),
+            Some(64)
+        );
+        assert_eq!(CanvasFingerprint::perceptual_distance("not hex", &a), None);
+    }
+
+    #[test]
+    fn identical_readings_are_not_noisy() {
+        let scene = image(20, 10, |x, y| gray((x * y) as u8));
+        let rate = CanvasFingerprint::noise_rate(&scene, &scene.clone(), &solid(20, 10));
+
+        assert_eq!(rate, 0.0);
+    }
+
+    #[test]
+    fn randomized_low_bits_are_noisy() {
+        let scene = image(20, 10, |x, y| gray((x * y) as u8));
+        let mut seed = 42;
+        let repeated: Vec<u8> = scene.iter()
+            .enumerate()
+            .map(|(i, &v)| if i % 4 == 3 { v } else { v ^ (noise(&mut seed) & 1) })
+            .collect();
+
+        let rate = CanvasFingerprint::noise_rate(&scene, &repeated, &solid(20, 10));
+        assert!(rate > 0.5, "{}", rate);
+        assert_eq!(CanvasFingerprint::pixel_difference_rate(&scene, &scene), 0.0);
+    }
+
+    #[test]
+    fn solid_fill_deviations_are_caught() {
+        let [r, g, b] = CanvasFingerprint::SOLID_COLOR;
+        // Um pixel em cada quatro com ±1 em um canal
+        let deviating = image(20, 10, |x, _| match x % 4 {
+            0 => [r + 1, g, b, 255],
+            2 => [r, g - 1, b, 255],
+            _ => [r, g, b, 255],
+        });
+        let scene = image(20, 10, |x, y| gray((x * y) as u8));
+
+        assert_eq!(CanvasFingerprint::solid_deviation_rate(&solid(20, 10), CanvasFingerprint::SOLID_COLOR), 0.0);
+        assert_eq!(CanvasFingerprint::noise_rate(&scene, &scene, &deviating), 0.5);
+        // Alfa diferente de 255 também é desvio
+        let translucent = image(2, 1, |_, _| [r, g, b, 254]);
+        assert_eq!(CanvasFingerprint::solid_deviation_rate(&translucent, CanvasFingerprint::SOLID_COLOR), 1.0);
+    }
+}
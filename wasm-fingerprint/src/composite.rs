@@ -0,0 +1,273 @@
+//! Identificadores compostos do fingerprint
+//!
+//! O hash composto é calculado sobre uma serialização canônica e versionada
+//! (JSON com chaves ordenadas), e não sobre o formato `Debug` das estruturas.
+//! São gerados dois identificadores:
+//!
+//! - **estável**: apenas componentes determinísticos (Canvas, fontes, WebGL, WebGPU, Audio,
+//!   atributos do navegador, hardware quantizado e vetores customizados
+//!   estáveis, sob `custom:<nome>`). Deve se repetir entre visitas do mesmo
+//!   dispositivo.
+//! - **completo**: inclui também os componentes voláteis (tempos de
+//!   benchmark, geometria de cache medida, vetor ρ de port contention).
+//!
+//! Qualquer mudança no que entra em cada identificador deve incrementar
+//! `COMPOSITE_VERSION`.
+
+use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::source::Stability;
+use crate::utils::sha256_hex;
+use crate::FingerprintData;
+
+/// Versão da serialização canônica usada nos hashes compostos
+pub const COMPOSITE_VERSION: u32 = 9;
+
+/// Prefixo das chaves dos vetores customizados na serialização canônica
+pub const CUSTOM_PREFIX: &str = "custom:";
+
+#[derive(Serialize)]
+struct CanonicalComposite<'a> {
+    version: u32,
+    kind: &'a str,
+    components: BTreeMap<String, Value>,
+}
+
+/// Serialização canônica dos componentes estáveis
+pub fn canonical_stable(data: &FingerprintData) -> String {
+    canonical(data, "stable", false)
+}
+
+/// Serialização canônica de todos os componentes
+pub fn canonical_full(data: &FingerprintData) -> String {
+    canonical(data, "full", true)
+}
+
+pub fn stable_hash(data: &FingerprintData) -> String {
+    sha256_hex(&canonical_stable(data))
+}
+
+pub fn full_hash(data: &FingerprintData) -> String {
+    sha256_hex(&canonical_full(data))
+}
+
+fn canonical(data: &FingerprintData, kind: &str, include_volatile: bool) -> String {
+    let mut components = BTreeMap::new();
+
+    // Com ruído injetado o hash muda a cada coleta, então só o fato de haver
+    // ruído entra no identificador
+    component(&mut components, data, "canvas", || {
+        if data.canvas_fingerprint.canvas_noise_detected {
+            json!({ "noise_detected": true })
+        } else {
+            json!(data.canvas_fingerprint.hash)
+        }
+    });
+    component(&mut components, data, "fonts", || json!(data.font_fingerprint.hash));
+    component(&mut components, data, "webgl", || json!({
+        "hash": data.webgl_fingerprint.hash,
+        "vendor": data.webgl_fingerprint.vendor,
+        "renderer": data.webgl_fingerprint.renderer,
+    }));
+    component(&mut components, data, "webgpu", || json!(data.webgpu_fingerprint.hash));
+    component(&mut components, data, "audio", || json!({
+        "hash": data.audio_fingerprint.hash,
+        "sample_rate": data.audio_fingerprint.sample_rate,
+        "variants": data.audio_fingerprint.variants.iter()
+            .map(|(name, variant)| {
+                let value = if variant.status.is_ok() {
+                    json!(variant.hash)
+                } else {
+                    json!({ "missing": variant.status.label() })
+                };
+                (name.clone(), value)
+            })
+            .collect::<BTreeMap<_, _>>(),
+    }));
+
+    let browser = &data.browser_info;
+    components.insert("browser".to_string(), json!({
+        "user_agent": browser.user_agent,
+        "language": browser.language,
+        "platform": browser.platform,
+        "hardware_concurrency": browser.hardware_concurrency,
+        "device_memory": browser.device_memory,
+        "screen_resolution": browser.screen_resolution,
+        "color_depth": browser.color_depth,
+        "timezone_offset": browser.timezone_offset,
+        "plugins_count": browser.plugins_count,
+    }));
+
+    // Hardware quantizado: apenas contagens que não dependem de timing
+    let profile = &data.hardware_profile;
+    components.insert("hardware_stable".to_string(), json!({
+        "cores": profile.cores,
+        "memory": profile.memory,
+        "concurrency": profile.concurrency,
+    }));
+
+    if include_volatile {
+        component(&mut components, data, "hardware", || json!({
+            "cpu_benchmark": profile.cpu_benchmark,
+            "memory_benchmark": profile.memory_benchmark,
+            "crypto_benchmark": profile.crypto_benchmark,
+            "instruction_timing": profile.instruction_timing,
+        }));
+        component(&mut components, data, "cache", || json!({
+            "l1_kb": profile.cache.l1_kb,
+            "l2_kb": profile.cache.l2_kb,
+            "l3_kb": profile.cache.l3_kb,
+            "line_size": profile.cache.line_size,
+        }));
+        component(&mut components, data, "port_contention", || json!({
+            "hash": profile.port_contention_hash,
+            "bucket": profile.port_contention_bucket,
+        }));
+    }
+
+    // Prefixados para que um vetor customizado com o nome de um embutido
+    // (ex.: `canvas`) não substitua a entrada dele
+    for (name, custom) in &data.custom_components {
+        if include_volatile || custom.stability == Stability::Stable {
+            let entry = component_entry(data, name, || json!(custom.hash));
+            components.insert(format!("{}{}", CUSTOM_PREFIX, name), entry);
+        }
+    }
+
+    let composite = CanonicalComposite {
+        version: COMPOSITE_VERSION,
+        kind,
+        components,
+    };
+
+    // Serializar mapas e valores JSON não falha
+    serde_json::to_string(&composite).unwrap_or_default()
+}
+
+/// Insere o valor do componente, ou um marcador explícito quando ele não foi
+/// coletado (para que um componente ausente nunca se confunda com um vazio)
+fn component<F>(components: &mut BTreeMap<String, Value>, data: &FingerprintData, name: &str, value: F)
+where
+    F: FnOnce() -> Value,
+{
+    components.insert(name.to_string(), component_entry(data, name, value));
+}
+
+fn component_entry<F>(data: &FingerprintData, name: &str, value: F) -> Value
+where
+    F: FnOnce() -> Value,
+{
+    match data.component_status.get(name) {
+        Some(status) if !status.is_ok() => json!({ "missing": status.label() }),
+        _ => value(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{ComponentStatus, CustomComponentData};
+    use crate::test_support::fingerprint;
+    use crate::AudioVariantData;
+
+    fn components(canonical: &str) -> BTreeMap<String, Value> {
+        let value: Value = serde_json::from_str(canonical).unwrap();
+        serde_json::from_value(value["components"].clone()).unwrap()
+    }
+
+    fn custom(hash: &str, stability: Stability) -> CustomComponentData {
+        CustomComponentData {
+            hash: hash.to_string(),
+            value: Value::Null,
+            stability,
+        }
+    }
+
+    #[test]
+    fn custom_component_cannot_replace_builtin() {
+        let mut data = fingerprint();
+        let builtin = stable_hash(&data);
+        data.custom_components.insert("canvas".to_string(), custom("spoofed", Stability::Stable));
+
+        let entries = components(&canonical_stable(&data));
+        assert_eq!(entries["canvas"], json!("canvas-hash"));
+        assert_eq!(entries["custom:canvas"], json!("spoofed"));
+        assert_ne!(stable_hash(&data), builtin);
+    }
+
+    #[test]
+    fn canonical_form_is_versioned() {
+        let value: Value = serde_json::from_str(&canonical_stable(&fingerprint())).unwrap();
+
+        assert_eq!(value["version"], json!(COMPOSITE_VERSION));
+        assert_eq!(value["kind"], json!("stable"));
+        assert_eq!(components(&canonical_full(&fingerprint()))["canvas"], json!("canvas-hash"));
+    }
+
+    #[test]
+    fn volatile_custom_components_only_enter_full_hash() {
+        let mut data = fingerprint();
+        data.custom_components.insert("timing".to_string(), custom("abc", Stability::Volatile));
+
+        assert!(!components(&canonical_stable(&data)).contains_key("custom:timing"));
+        assert_eq!(components(&canonical_full(&data))["custom:timing"], json!("abc"));
+    }
+
+    #[test]
+    fn missing_component_gets_explicit_marker() {
+        let mut data = fingerprint();
+        data.component_status.insert("webgl".to_string(), ComponentStatus::TimedOut);
+
+        let entries = components(&canonical_stable(&data));
+        assert_eq!(entries["webgl"], json!({ "missing": "timed_out" }));
+    }
+
+    #[test]
+    fn failed_audio_variant_gets_explicit_marker() {
+        let mut data = fingerprint();
+        data.audio_fingerprint.variants.insert("convolver".to_string(), AudioVariantData {
+            status: ComponentStatus::Blocked,
+            ..Default::default()
+        });
+
+        let entries = components(&canonical_stable(&data));
+        assert_eq!(entries["audio"]["hash"], json!("audio-hash"));
+        assert_eq!(entries["audio"]["variants"]["convolver"], json!({ "missing": "blocked" }));
+    }
+
+    #[test]
+    fn stable_hash_ignores_benchmark_times() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.hardware_profile.cpu_benchmark *= 2.0;
+
+        assert_eq!(stable_hash(&a), stable_hash(&b));
+        assert_ne!(full_hash(&a), full_hash(&b));
+    }
+
+    #[test]
+    fn audio_variant_hashes_enter_the_stable_hash() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.audio_fingerprint.variants.insert("convolver".to_string(), AudioVariantData {
+            hash: "variant-hash".to_string(),
+            ..Default::default()
+        });
+
+        let entries = components(&canonical_stable(&b));
+        assert_eq!(entries["audio"]["variants"]["convolver"], json!("variant-hash"));
+        assert_ne!(stable_hash(&a), stable_hash(&b));
+    }
+
+    #[test]
+    fn noisy_canvas_enters_only_as_a_flag() {
+        let mut a = fingerprint();
+        a.canvas_fingerprint.canvas_noise_detected = true;
+        let mut b = a.clone();
+        b.canvas_fingerprint.hash = "other".to_string();
+
+        assert_eq!(components(&canonical_stable(&a))["canvas"], json!({ "noise_detected": true }));
+        assert_eq!(stable_hash(&a), stable_hash(&b));
+    }
+}
@@ -0,0 +1,507 @@
+//! Configuração da coleta, ajustável em tempo de execução
+//!
+//! `CollectorConfig` é desserializado de um objeto JavaScript passado ao
+//! construtor do `FingerprintCollector`. O campo opcional `profile`
+//! (`"fast"`, `"balanced"` ou `"precise"`) escolhe a base, e os demais campos
+//! informados sobrescrevem apenas o que foi passado. Chaves desconhecidas
+//! são rejeitadas:
+//!
+//! ```js
+//! new FingerprintCollector({ profile: "fast", port_contention: { measurements: 3 } });
+//! ```
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Parâmetros dos benchmarks de hardware (Proposta B)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BenchmarkConfig {
+    pub cpu_iterations: u32,
+    pub memory_accesses: u32,
+    pub crypto_iterations: u32,
+    /// Repetições de cada sequência do perfil de instruções
+    pub instruction_repetitions: u32,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        BenchmarkConfig {
+            cpu_iterations: 1_000_000,
+            memory_accesses: 100_000,
+            crypto_iterations: 10_000,
+            instruction_repetitions: 100,
+        }
+    }
+}
+
+/// Parâmetros da detecção da hierarquia de cache
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Menor working set da varredura, em KB
+    pub min_size_kb: u32,
+    /// Maior working set da varredura, em KB; precisa passar do L3 para
+    /// que ele seja detectado
+    pub max_size_kb: u32,
+    /// Acessos encadeados por medição
+    pub accesses: u32,
+    /// Medições de cada tamanho (vale a menor)
+    pub repetitions: u32,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            min_size_kb: 4,
+            max_size_kb: 32 * 1024,
+            accesses: 1 << 20,
+            repetitions: 3,
+        }
+    }
+}
+
+/// Parâmetros do relógio dos benchmarks
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TimingConfig {
+    /// Usa o contador de um worker sobre `SharedArrayBuffer` quando a
+    /// página é cross-origin isolated; o relógio é compartilhado pela coleta
+    /// inteira, e o worker ocupa um núcleo até ela terminar
+    pub counter_clock: bool,
+    /// Duração da calibração do contador contra `performance.now()`, em ms
+    pub calibration_ms: f64,
+    /// Incrementos do relógio registrados na caracterização
+    pub resolution_samples: u32,
+    /// Tempo máximo da caracterização, em ms
+    pub characterization_ms: f64,
+    /// Duração mínima de cada medição, em múltiplos da resolução do relógio
+    pub min_resolutions: f64,
+    /// Fator máximo aplicado às iterações configuradas de cada benchmark
+    pub max_iteration_scale: u32,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        TimingConfig {
+            counter_clock: true,
+            calibration_ms: 50.0,
+            resolution_samples: 200,
+            characterization_ms: 50.0,
+            min_resolutions: 20.0,
+            max_iteration_scale: 16,
+        }
+    }
+}
+
+/// Parâmetros do fingerprint de contenção de portas
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PortContentionConfig {
+    pub iterations: u32,
+    pub warmup_iterations: u32,
+    pub measurements: u32,
+    /// Iterações dos benchmarks específicos de instruções Wasm
+    pub wasm_iterations: u32,
+}
+
+impl Default for PortContentionConfig {
+    fn default() -> Self {
+        PortContentionConfig {
+            iterations: 100_000,
+            warmup_iterations: 1_000,
+            measurements: 10,
+            wasm_iterations: 100_000,
+        }
+    }
+}
+
+/// Parâmetros do fingerprint de Canvas
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CanvasConfig {
+    pub width: u32,
+    pub height: u32,
+    /// Inclui a imagem PNG (data URL) no fingerprint; desabilitado por
+    /// padrão, já que aumenta muito o tamanho do payload
+    pub include_data_url: bool,
+    /// Renderiza a cena duas vezes e um padrão sólido para detectar ruído
+    /// injetado na leitura dos pixels
+    pub detect_noise: bool,
+}
+
+impl Default for CanvasConfig {
+    fn default() -> Self {
+        CanvasConfig {
+            width: 280,
+            height: 120,
+            include_data_url: false,
+            detect_noise: true,
+        }
+    }
+}
+
+/// Grafo de áudio renderizado por uma variante do fingerprint de Audio
+///
+/// Motores de áudio diferentes divergem em nós diferentes, por isso cada
+/// variante exercita um nó (ou forma de onda) específico.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioGraph {
+    /// Oscilador triangular → compressor (grafo original do guia)
+    TriangleCompressor,
+    SineCompressor,
+    SquareCompressor,
+    SawtoothCompressor,
+    /// Oscilador dente de serra → filtro biquad passa-banda
+    BiquadFilter,
+    /// Oscilador quadrado → ganho com rampas linear e exponencial
+    GainAutomation,
+    /// Oscilador triangular → `AnalyserNode` (também lê o espectro do nó)
+    Analyser,
+    /// Oscilador quadrado → `ConvolverNode` com resposta ao impulso sintética
+    Convolver,
+}
+
+impl AudioGraph {
+    pub const ALL: [AudioGraph; 8] = [
+        AudioGraph::TriangleCompressor,
+        AudioGraph::SineCompressor,
+        AudioGraph::SquareCompressor,
+        AudioGraph::SawtoothCompressor,
+        AudioGraph::BiquadFilter,
+        AudioGraph::GainAutomation,
+        AudioGraph::Analyser,
+        AudioGraph::Convolver,
+    ];
+
+    /// Nome usado como chave em `AudioFingerprintData::variants`
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioGraph::TriangleCompressor => "triangle_compressor",
+            AudioGraph::SineCompressor => "sine_compressor",
+            AudioGraph::SquareCompressor => "square_compressor",
+            AudioGraph::SawtoothCompressor => "sawtooth_compressor",
+            AudioGraph::BiquadFilter => "biquad_filter",
+            AudioGraph::GainAutomation => "gain_automation",
+            AudioGraph::Analyser => "analyser",
+            AudioGraph::Convolver => "convolver",
+        }
+    }
+}
+
+/// Parâmetros do fingerprint de Audio
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub sample_rate: f32,
+    /// Número de amostras renderizadas
+    pub length: u32,
+    pub oscillator_frequency: f32,
+    /// Variantes renderizadas além do grafo principal
+    pub graphs: Vec<AudioGraph>,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        // Configuração conforme o guia: 1 canal, 5000 amostras, 44100 Hz
+        AudioConfig {
+            sample_rate: 44100.0,
+            length: 5000,
+            oscillator_frequency: 10000.0,
+            graphs: AudioGraph::ALL.to_vec(),
+        }
+    }
+}
+
+/// Parâmetros do fingerprint de fontes instaladas
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FontConfig {
+    /// Fontes testadas; a ordem define as posições do bitset
+    pub candidates: Vec<String>,
+    /// Famílias genéricas usadas como fallback na comparação
+    pub baselines: Vec<String>,
+    /// Texto medido em cada fonte
+    pub test_string: String,
+    /// Tamanho da fonte, em pixels
+    pub font_size: u32,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        let candidates = [
+            "Andale Mono", "Arial", "Arial Black", "Arial Narrow", "Arial Unicode MS",
+            "Avenir", "Baskerville", "Book Antiqua", "Bookman Old Style", "Calibri",
+            "Cambria", "Candara", "Century Gothic", "Comic Sans MS", "Consolas",
+            "Constantia", "Corbel", "Courier", "Courier New", "DejaVu Sans",
+            "DejaVu Serif", "Droid Sans", "Franklin Gothic Medium", "Futura", "Garamond",
+            "Geneva", "Georgia", "Gill Sans", "Helvetica", "Helvetica Neue",
+            "Impact", "Liberation Mono", "Liberation Sans", "Lucida Console", "Lucida Grande",
+            "Lucida Sans Unicode", "Menlo", "Microsoft Sans Serif", "Monaco", "Noto Sans",
+            "Optima", "Palatino", "Palatino Linotype", "Roboto", "Segoe UI",
+            "Segoe UI Emoji", "Tahoma", "Times", "Times New Roman", "Trebuchet MS",
+            "Ubuntu", "Verdana", "Wingdings",
+        ];
+        FontConfig {
+            candidates: candidates.iter().map(|f| f.to_string()).collect(),
+            baselines: ["monospace", "sans-serif", "serif"].iter().map(|f| f.to_string()).collect(),
+            test_string: "mmmmmmmmmmlli WwQq@#%".to_string(),
+            font_size: 72,
+        }
+    }
+}
+
+/// Parâmetros do fingerprint WebGPU
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WebGPUConfig {
+    /// Executa um compute shader e inclui o hash do resultado; exige criar
+    /// um `GPUDevice`, por isso fica desabilitado fora do perfil `precise`
+    pub compute_shader: bool,
+}
+
+/// Configuração completa da coleta
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CollectorConfig {
+    /// Tempo máximo de cada vetor, em milissegundos
+    pub source_timeout_ms: f64,
+    /// Orçamento total da coleta, em milissegundos
+    pub total_budget_ms: f64,
+    pub benchmarks: BenchmarkConfig,
+    pub cache: CacheConfig,
+    pub timing: TimingConfig,
+    pub port_contention: PortContentionConfig,
+    pub canvas: CanvasConfig,
+    pub audio: AudioConfig,
+    pub fonts: FontConfig,
+    pub webgpu: WebGPUConfig,
+}
+
+impl Default for CollectorConfig {
+    fn default() -> Self {
+        CollectorConfig::balanced()
+    }
+}
+
+impl CollectorConfig {
+    /// Perfil padrão, com os mesmos parâmetros das versões anteriores
+    pub fn balanced() -> Self {
+        CollectorConfig {
+            source_timeout_ms: 5_000.0,
+            total_budget_ms: 15_000.0,
+            benchmarks: BenchmarkConfig::default(),
+            cache: CacheConfig::default(),
+            timing: TimingConfig::default(),
+            port_contention: PortContentionConfig::default(),
+            canvas: CanvasConfig::default(),
+            audio: AudioConfig::default(),
+            fonts: FontConfig::default(),
+            webgpu: WebGPUConfig::default(),
+        }
+    }
+
+    /// Perfil para páginas sensíveis a latência (ex.: checkout)
+    pub fn fast() -> Self {
+        CollectorConfig {
+            source_timeout_ms: 500.0,
+            total_budget_ms: 1_500.0,
+            benchmarks: BenchmarkConfig {
+                cpu_iterations: 200_000,
+                memory_accesses: 20_000,
+                crypto_iterations: 2_000,
+                instruction_repetitions: 20,
+            },
+            cache: CacheConfig {
+                min_size_kb: 4,
+                max_size_kb: 8 * 1024,
+                accesses: 1 << 17,
+                repetitions: 1,
+            },
+            timing: TimingConfig {
+                counter_clock: false,
+                calibration_ms: 20.0,
+                resolution_samples: 50,
+                characterization_ms: 10.0,
+                min_resolutions: 10.0,
+                max_iteration_scale: 4,
+            },
+            port_contention: PortContentionConfig {
+                iterations: 20_000,
+                warmup_iterations: 500,
+                measurements: 3,
+                wasm_iterations: 20_000,
+            },
+            ..CollectorConfig::balanced()
+        }
+    }
+
+    /// Perfil com mais medições, para análise offline e pesquisa
+    pub fn precise() -> Self {
+        CollectorConfig {
+            source_timeout_ms: 15_000.0,
+            total_budget_ms: 60_000.0,
+            benchmarks: BenchmarkConfig {
+                cpu_iterations: 5_000_000,
+                memory_accesses: 500_000,
+                crypto_iterations: 50_000,
+                instruction_repetitions: 500,
+            },
+            cache: CacheConfig {
+                min_size_kb: 4,
+                max_size_kb: 128 * 1024,
+                accesses: 1 << 22,
+                repetitions: 5,
+            },
+            timing: TimingConfig {
+                counter_clock: true,
+                calibration_ms: 200.0,
+                resolution_samples: 1000,
+                characterization_ms: 200.0,
+                min_resolutions: 100.0,
+                max_iteration_scale: 64,
+            },
+            port_contention: PortContentionConfig {
+                iterations: 200_000,
+                warmup_iterations: 5_000,
+                measurements: 25,
+                wasm_iterations: 200_000,
+            },
+            webgpu: WebGPUConfig { compute_shader: true },
+            ..CollectorConfig::balanced()
+        }
+    }
+
+    pub fn from_profile(profile: &str) -> Option<Self> {
+        match profile {
+            "fast" => Some(CollectorConfig::fast()),
+            "balanced" => Some(CollectorConfig::balanced()),
+            "precise" => Some(CollectorConfig::precise()),
+            _ => None,
+        }
+    }
+
+    /// Aplica os campos presentes em `overrides` sobre a configuração do
+    /// perfil indicado em `overrides.profile` (ou do perfil padrão)
+    pub fn from_json(overrides: serde_json::Value) -> Result<Self, String> {
+        let base = match overrides.get("profile") {
+            None | Some(serde_json::Value::Null) => CollectorConfig::default(),
+            Some(serde_json::Value::String(name)) => CollectorConfig::from_profile(name)
+                .ok_or_else(|| format!("Unknown profile: {}", name))?,
+            Some(other) => return Err(format!("Invalid profile: {}", other)),
+        };
+
+        let mut overrides = overrides;
+        if let serde_json::Value::Object(fields) = &mut overrides {
+            fields.remove("profile");
+        }
+
+        let mut merged = serde_json::to_value(base).map_err(|e| e.to_string())?;
+        Self::merge(&mut merged, overrides, "")?;
+        serde_json::from_value(merged).map_err(|e| e.to_string())
+    }
+
+    /// Desserializa a configuração de um objeto JavaScript
+    /// (`undefined`/`null` resultam na configuração padrão)
+    pub fn from_js(value: JsValue) -> Result<Self, JsValue> {
+        if value.is_undefined() || value.is_null() {
+            return Ok(CollectorConfig::default());
+        }
+
+        let overrides: serde_json::Value = serde_wasm_bindgen::from_value(value)?;
+        Self::from_json(overrides)
+            .map_err(|e| JsValue::from_str(&format!("Invalid collector config: {}", e)))
+    }
+
+    /// Aplica `overrides` sobre `base` recursivamente. A configuração
+    /// serializada tem todas as chaves, então uma chave ausente em `base` é
+    /// um erro de digitação e é rejeitada em vez de ignorada.
+    fn merge(base: &mut serde_json::Value, overrides: serde_json::Value, path: &str) -> Result<(), String> {
+        match (base, overrides) {
+            (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+                for (key, value) in overrides {
+                    let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    match base.get_mut(&key) {
+                        Some(existing) => Self::merge(existing, value, &field)?,
+                        None => return Err(format!("Unknown config key: {}", field)),
+                    }
+                }
+            }
+            (base, overrides) => *base = overrides,
+        }
+        Ok(())
+    }
+}
+
+/// Função exportada para JavaScript - retorna a configuração de um perfil
+/// (`fast`, `balanced` ou `precise`) para ser ajustada e passada ao coletor
+#[wasm_bindgen]
+pub fn get_collector_profile(profile: &str) -> Result<JsValue, JsValue> {
+    let config = CollectorConfig::from_profile(profile)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown profile: {}", profile)))?;
+    serde_wasm_bindgen::to_value(&config).map_err(JsValue::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn overrides_apply_on_top_of_profile() {
+        let config = CollectorConfig::from_json(json!({
+            "profile": "fast",
+            "port_contention": { "measurements": 3 },
+        }))
+        .unwrap();
+        let fast = CollectorConfig::from_profile("fast").unwrap();
+
+        assert_eq!(config.port_contention.measurements, 3);
+        assert_eq!(config.port_contention.iterations, fast.port_contention.iterations);
+        assert_eq!(config.cache.max_size_kb, fast.cache.max_size_kb);
+    }
+
+    #[test]
+    fn empty_overrides_give_default_profile() {
+        let config = CollectorConfig::from_json(json!({})).unwrap();
+        let default = CollectorConfig::default();
+        assert_eq!(config.port_contention.measurements, default.port_contention.measurements);
+        assert_eq!(config.total_budget_ms, default.total_budget_ms);
+    }
+
+    #[test]
+    fn unknown_profile_is_rejected() {
+        let error = CollectorConfig::from_json(json!({ "profile": "turbo" })).unwrap_err();
+        assert_eq!(error, "Unknown profile: turbo");
+        assert!(CollectorConfig::from_json(json!({ "profile": 1 })).is_err());
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected_with_their_path() {
+        let error = CollectorConfig::from_json(json!({ "port_contention": { "measurments": 3 } })).unwrap_err();
+        assert_eq!(error, "Unknown config key: port_contention.measurments");
+
+        let error = CollectorConfig::from_json(json!({ "canvs": {} })).unwrap_err();
+        assert_eq!(error, "Unknown config key: canvs");
+    }
+
+    #[test]
+    fn profile_is_only_special_at_the_root() {
+        let error = CollectorConfig::from_json(json!({ "benchmarks": { "profile": "fast" } })).unwrap_err();
+        assert_eq!(error, "Unknown config key: benchmarks.profile");
+    }
+
+    #[test]
+    fn profiles_trade_time_for_precision() {
+        let (fast, balanced, precise) =
+            (CollectorConfig::fast(), CollectorConfig::balanced(), CollectorConfig::precise());
+
+        assert!(fast.total_budget_ms < balanced.total_budget_ms);
+        assert!(balanced.total_budget_ms < precise.total_budget_ms);
+        assert!(fast.port_contention.measurements < balanced.port_contention.measurements);
+        assert!(balanced.port_contention.measurements < precise.port_contention.measurements);
+        assert!(fast.timing.max_iteration_scale < balanced.timing.max_iteration_scale);
+        assert!(balanced.timing.max_iteration_scale < precise.timing.max_iteration_scale);
+    }
+}
@@ -0,0 +1,207 @@
+//! Fingerprint de fontes instaladas
+//!
+//! Para cada fonte candidata, mede um texto de teste com
+//! `font: "<tamanho> '<candidata>', <base>"` e compara com a medida da fonte
+//! genérica de fallback (`monospace`, `sans-serif`, `serif`). Se a medida
+//! muda para alguma das bases, a candidata está instalada.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use crate::dom_utils::get_document;
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceFuture, SourceOutput, Stability};
+use crate::utils::sha256_hex;
+use crate::FontFingerprintData;
+use crate::config::FontConfig;
+
+/// Função exportada para JavaScript - hash do conjunto de fontes instaladas
+#[wasm_bindgen]
+pub fn get_font_fingerprint() -> Result<String, JsValue> {
+    Ok(FontFingerprint::collect_with(&FontConfig::default())?.hash)
+}
+
+/// Função exportada para JavaScript - fontes instaladas, em ordem alfabética
+#[wasm_bindgen]
+pub fn get_installed_fonts() -> Result<Vec<String>, JsValue> {
+    Ok(FontFingerprint::collect_with(&FontConfig::default())?.fonts)
+}
+
+#[derive(Default)]
+pub struct FontFingerprint {
+    config: FontConfig,
+}
+
+/// Dimensões medidas de um texto
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TextSize {
+    width: f64,
+    ascent: f64,
+    descent: f64,
+}
+
+impl FontFingerprint {
+    pub fn new(config: FontConfig) -> Self {
+        FontFingerprint { config }
+    }
+
+    pub fn collect_with(config: &FontConfig) -> Result<FontFingerprintData, JsValue> {
+        let document = get_document()?;
+        let canvas = document.create_element("canvas")?
+            .dyn_into::<HtmlCanvasElement>()?;
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or("Failed to get 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        let baselines = config
+            .baselines
+            .iter()
+            .map(|base| Ok((base.as_str(), Self::measure(&ctx, config, base)?)))
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        let mut detected = Vec::with_capacity(config.candidates.len());
+        for candidate in &config.candidates {
+            let mut installed = false;
+            for (base, base_size) in &baselines {
+                let font = Self::font_family(candidate, base);
+                if Self::measure(&ctx, config, &font)? != *base_size {
+                    installed = true;
+                    break;
+                }
+            }
+            detected.push(installed);
+        }
+
+        Ok(Self::from_detection(&config.candidates, &detected))
+    }
+
+    fn measure(ctx: &CanvasRenderingContext2d, config: &FontConfig, family: &str) -> Result<TextSize, JsValue> {
+        ctx.set_font(&format!("{}px {}", config.font_size, family));
+        let metrics = ctx.measure_text(&config.test_string)?;
+        Ok(TextSize {
+            width: metrics.width(),
+            ascent: metrics.actual_bounding_box_ascent(),
+            descent: metrics.actual_bounding_box_descent(),
+        })
+    }
+
+    /// Lista de famílias `'<candidata>', <base>`. O nome vai entre aspas
+    /// simples com `\`, `'` e quebras de linha escapados: sem isso, um nome
+    /// com aspas invalida a declaração e o navegador ignora `font` em
+    /// silêncio, medindo a fonte anterior.
+    pub fn font_family(candidate: &str, base: &str) -> String {
+        let mut quoted = String::with_capacity(candidate.len() + 2);
+        for c in candidate.chars() {
+            match c {
+                '\\' => quoted.push_str("\\\\"),
+                '\'' => quoted.push_str("\\'"),
+                '\n' => quoted.push_str("\\a "),
+                _ => quoted.push(c),
+            }
+        }
+        format!("'{}', {}", quoted, base)
+    }
+
+    /// Monta o resultado a partir da detecção de cada candidata (na ordem de
+    /// `candidates`). O bitset segue a ordem das candidatas, e o hash cobre
+    /// a lista de candidatas junto do bitset, para que listas diferentes
+    /// nunca produzam o mesmo hash.
+    pub fn from_detection(candidates: &[String], detected: &[bool]) -> FontFingerprintData {
+        let mut bytes = vec![0u8; (candidates.len() + 7) / 8];
+        for (i, _) in detected.iter().enumerate().filter(|(_, &d)| d) {
+            bytes[i / 8] |= 0x80 >> (i % 8);
+        }
+        let bitset: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut fonts: Vec<String> = candidates
+            .iter()
+            .zip(detected)
+            .filter(|(_, &d)| d)
+            .map(|(font, _)| font.clone())
+            .collect();
+        fonts.sort();
+
+        FontFingerprintData {
+            hash: sha256_hex(&format!("{}|{}", candidates.join(","), bitset)),
+            fonts,
+            bitset,
+            candidates_count: candidates.len() as u32,
+        }
+    }
+}
+
+impl FingerprintSource for FontFingerprint {
+    fn name(&self) -> &str {
+        "fonts"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Stable
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Fonts
+    }
+
+    fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let fonts = FontFingerprint::collect_with(&self.config)?;
+            Ok(SourceOutput::Fonts(fonts))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(fonts: &[&str]) -> Vec<String> {
+        fonts.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn bitset_follows_candidate_order() {
+        let candidates = names(&["Verdana", "Arial", "Comic Sans MS", "Georgia", "Impact",
+            "Tahoma", "Times", "Ubuntu", "Wingdings"]);
+        let mut detected = vec![false; candidates.len()];
+        detected[0] = true;
+        detected[2] = true;
+        detected[8] = true;
+
+        let data = FontFingerprint::from_detection(&candidates, &detected);
+
+        assert_eq!(data.bitset, "a080");
+        assert_eq!(data.candidates_count, 9);
+        assert_eq!(data.fonts, names(&["Comic Sans MS", "Verdana", "Wingdings"]));
+    }
+
+    #[test]
+    fn hash_is_stable_and_covers_candidates() {
+        let candidates = names(&["Arial", "Comic Sans MS", "Verdana"]);
+        let data = FontFingerprint::from_detection(&candidates, &[true, false, true]);
+
+        // sha256("Arial,Comic Sans MS,Verdana|a0")
+        assert_eq!(data.hash, "6cc67016e1946763e508e0ac7fbb4d0b2520ecef4184bfdc210c6f7942290d58");
+        assert_eq!(data.hash, FontFingerprint::from_detection(&candidates, &[true, false, true]).hash);
+
+        // Mesmo bitset com outra lista de candidatas
+        let other = names(&["Arial", "Courier", "Verdana"]);
+        assert_ne!(data.hash, FontFingerprint::from_detection(&other, &[true, false, true]).hash);
+    }
+
+    #[test]
+    fn empty_detection() {
+        let data = FontFingerprint::from_detection(&[], &[]);
+        assert_eq!(data.bitset, "");
+        assert!(data.fonts.is_empty());
+    }
+
+    #[test]
+    fn font_family_escapes_quotes() {
+        let family = FontFingerprint::font_family;
+        assert_eq!(family("Arial", "serif"), "'Arial', serif");
+        assert_eq!(family("O'Font", "monospace"), "'O\\'Font', monospace");
+        assert_eq!(family("Back\\slash", "serif"), "'Back\\\\slash', serif");
+        assert_eq!(family("Two\nLines", "serif"), "'Two\\a Lines', serif");
+    }
+}
@@ -1,139 +1,167 @@
 use wasm_bindgen::prelude::*;
-use crate::utils::performance_now;
-use crate::HardwareProfile;
+use crate::{CacheProfile, HardwareProfile, TimerProfile};
+use crate::config::{BenchmarkConfig, TimingConfig};
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::timing::{AdaptiveTimer, BenchmarkClock, Clock};
 
-pub struct HardwareBenchmarks;
+#[derive(Default)]
+pub struct HardwareBenchmarks {
+    config: BenchmarkConfig,
+    timing: TimingConfig,
+}
 
 impl HardwareBenchmarks {
-    pub fn new() -> Self {
-        HardwareBenchmarks
+    pub fn with_config(config: BenchmarkConfig, timing: TimingConfig) -> Self {
+        HardwareBenchmarks { config, timing }
     }
 
+    /// Versão síncrona, sempre com `performance.now()`
     pub fn run_all_benchmarks(&self) -> Result<HardwareProfile, JsValue> {
+        let clock = BenchmarkClock::Performance;
+        Ok(self.run_benchmarks_until(&clock, Deadline::none(&clock))?)
+    }
+
+    /// Executa os benchmarks, verificando o prazo entre cada um deles. Os
+    /// tempos são os das iterações configuradas, mesmo quando o relógio
+    /// exige mais iterações para uma medição confiável.
+    pub fn run_benchmarks_until(&self, clock: &dyn Clock, deadline: Deadline<'_>) -> Result<HardwareProfile, SourceError> {
+        let timer_profile = clock.characterize(&self.timing);
+        let timer = AdaptiveTimer::new(clock, &timer_profile, &self.timing);
+
+        let cpu_benchmark = self.cpu_intensive_benchmark(&timer)?;
+        deadline.check()?;
+        let memory_benchmark = self.memory_access_benchmark(&timer)?;
+        deadline.check()?;
+        let crypto_benchmark = self.crypto_operations_benchmark(&timer)?;
+        deadline.check()?;
+        let instruction_timing = self.instruction_timing_profile(&timer)?;
+
         Ok(HardwareProfile {
             cores: 4, // Default, will be overridden
             memory: 8.0, // Default, will be overridden
             concurrency: 4, // Default, will be overridden
             benchmarks: None, // Will be filled later
-            cpu_benchmark: self.cpu_intensive_benchmark()?,
-            memory_benchmark: self.memory_access_benchmark()?,
-            crypto_benchmark: self.crypto_operations_benchmark()?,
-            instruction_timing: self.instruction_timing_profile()?,
+            cpu_benchmark,
+            memory_benchmark,
+            crypto_benchmark,
+            instruction_timing,
             port_contention_hash: String::new(), // Will be filled by PortContentionFingerprint
+            port_contention_vector: Vec::new(),
+            port_contention_bucket: String::new(),
+            cache: CacheProfile::default(), // Will be filled by CacheBenchmark
+            timer: timer_profile,
+            port_contention_timer: TimerProfile::default(), // Will be filled by PortContentionFingerprint
         })
     }
 
-    fn cpu_intensive_benchmark(&self) -> Result<f64, JsValue> {
-        let iterations = 1_000_000;
-        let start = performance_now();
+    fn cpu_intensive_benchmark(&self, timer: &AdaptiveTimer) -> Result<f64, JsValue> {
+        let iterations = self.config.cpu_iterations;
 
         // CPU-intensive operations
         let mut result = 1.0f64;
-        for i in 0..iterations {
-            result = result * 1.000001 + (i as f64).sin();
-            // Ensure we never take sqrt of negative number
-            result = result.abs().sqrt() * 2.0 - 1.0;
-            if i % 1000 == 0 {
-                result = result.abs();
+        let measurement = timer.measure(iterations, |iterations| {
+            result = 1.0;
+            for i in 0..iterations {
+                result = result * 1.000001 + (i as f64).sin();
+                // Ensure we never take sqrt of negative number
+                result = result.abs().sqrt() * 2.0 - 1.0;
+                if i % 1000 == 0 {
+                    result = result.abs();
+                }
             }
-        }
-
-        let end = performance_now();
-        let duration = end - start;
+            result
+        });
 
         // Prevent optimization
         if result.is_nan() {
             return Err(JsValue::from_str("Benchmark failed"));
         }
 
-        Ok(duration)
+        Ok(measurement.scaled_to(iterations))
     }
 
-    fn memory_access_benchmark(&self) -> Result<f64, JsValue> {
+    fn memory_access_benchmark(&self, timer: &AdaptiveTimer) -> Result<f64, JsValue> {
         let size = 1024 * 1024; // 1MB
         let mut data = vec![0u32; size];
 
         // Initialize with pattern
-        for i in 0..size {
-            data[i] = (i * 31) as u32;
+        for (i, value) in data.iter_mut().enumerate() {
+            *value = (i * 31) as u32;
         }
 
-        let start = performance_now();
-
         // Random memory access pattern
         let mut sum = 0u64;
-        let mut index = 0usize;
-
-        for _ in 0..100_000 {
-            index = ((index * 1103515245 + 12345) % size as usize) as usize;
-            sum += data[index] as u64;
-            data[index] = (sum & 0xFFFFFFFF) as u32;
-
-            // Stride access
-            let stride_index = (index + 64) % size;
-            sum += data[stride_index] as u64;
-        }
-
-        let end = performance_now();
+        let measurement = timer.measure(self.config.memory_accesses, |accesses| {
+            let mut index = 0usize;
+            for _ in 0..accesses {
+                index = (index * 1103515245 + 12345) % size;
+                sum += data[index] as u64;
+                data[index] = (sum & 0xFFFFFFFF) as u32;
+
+                // Stride access
+                let stride_index = (index + 64) % size;
+                sum += data[stride_index] as u64;
+            }
+            sum
+        });
 
         // Prevent optimization
         if sum == 0 {
             return Err(JsValue::from_str("Benchmark optimized away"));
         }
 
-        Ok(end - start)
+        Ok(measurement.scaled_to(self.config.memory_accesses))
     }
 
-    fn crypto_operations_benchmark(&self) -> Result<f64, JsValue> {
-        let iterations = 10_000;
-        let start = performance_now();
-
+    fn crypto_operations_benchmark(&self, timer: &AdaptiveTimer) -> Result<f64, JsValue> {
+        let iterations = self.config.crypto_iterations;
         let mut hash = 0x811c9dc5u32; // FNV-1a init
 
-        for i in 0..iterations {
-            // Simple hash function (FNV-1a variant)
-            let bytes = (i as u32).to_le_bytes();
-            for byte in bytes.iter() {
-                hash ^= *byte as u32;
-                hash = hash.wrapping_mul(0x01000193);
-            }
-
-            // Additional mixing
-            hash ^= hash >> 16;
-            hash = hash.wrapping_mul(0x85ebca6b);
-            hash ^= hash >> 13;
-            hash = hash.wrapping_mul(0xc2b2ae35);
-            hash ^= hash >> 16;
-        }
+        let measurement = timer.measure(iterations, |iterations| {
+            for i in 0..iterations {
+                // Simple hash function (FNV-1a variant)
+                let bytes = i.to_le_bytes();
+                for byte in bytes.iter() {
+                    hash ^= *byte as u32;
+                    hash = hash.wrapping_mul(0x01000193);
+                }
 
-        let end = performance_now();
+                // Additional mixing
+                hash ^= hash >> 16;
+                hash = hash.wrapping_mul(0x85ebca6b);
+                hash ^= hash >> 13;
+                hash = hash.wrapping_mul(0xc2b2ae35);
+                hash ^= hash >> 16;
+            }
+            hash
+        });
 
         // Prevent optimization
         if hash == 0 {
             return Err(JsValue::from_str("Hash computation failed"));
         }
 
-        Ok(end - start)
+        Ok(measurement.scaled_to(iterations))
     }
 
-    fn instruction_timing_profile(&self) -> Result<Vec<f64>, JsValue> {
+    fn instruction_timing_profile(&self, timer: &AdaptiveTimer) -> Result<Vec<f64>, JsValue> {
         let mut timings = Vec::new();
 
         // Test 1: Integer arithmetic
-        let timing = self.measure_instruction_sequence(|| {
+        let timing = self.measure_instruction_sequence(timer, || {
             let mut acc = 1i32;
             for i in 1..1000 {
                 acc = acc.wrapping_mul(i);
                 acc = acc.wrapping_add(i * 2);
                 acc = acc.wrapping_sub(i / 2);
-                acc = acc ^ (i << 3);
+                acc ^= i << 3;
             }
             acc
         })?;
         timings.push(timing);
 
         // Test 2: Floating point operations
-        let timing = self.measure_instruction_sequence(|| {
+        let timing = self.measure_instruction_sequence(timer, || {
             let mut acc = 1.0f64;
             for i in 1..1000 {
                 let f = i as f64;
@@ -146,18 +174,18 @@ impl HardwareBenchmarks {
         timings.push(timing);
 
         // Test 3: Branch prediction stress
-        let timing = self.measure_instruction_sequence(|| {
+        let timing = self.measure_instruction_sequence(timer, || {
             let mut acc = 0i32;
             let mut pattern = 0b10110010u8;
             for i in 0..1000 {
                 pattern = pattern.rotate_left(1);
                 if pattern & 1 == 1 {
-                    acc += i;
+                    acc = acc.wrapping_add(i);
                 } else {
-                    acc -= i / 2;
+                    acc = acc.wrapping_sub(i / 2);
                 }
                 if pattern & 2 == 2 {
-                    acc *= 2;
+                    acc = acc.wrapping_mul(2);
                 }
                 if pattern & 4 == 4 {
                     acc = acc.wrapping_add(pattern as i32);
@@ -168,13 +196,13 @@ impl HardwareBenchmarks {
         timings.push(timing);
 
         // Test 4: Memory fence operations (simulated)
-        let timing = self.measure_instruction_sequence(|| {
+        let timing = self.measure_instruction_sequence(timer, || {
             let mut data = vec![0i32; 256];
             let mut sum = 0i32;
             for i in 0..256 {
                 data[i] = i as i32;
                 // Simulate memory barrier with volatile-like access
-                sum += data[i];
+                sum = sum.wrapping_add(data[i]);
                 data[(i + 128) % 256] = sum;
             }
             sum
@@ -182,11 +210,11 @@ impl HardwareBenchmarks {
         timings.push(timing);
 
         // Test 5: Division and modulo (typically slower)
-        let timing = self.measure_instruction_sequence(|| {
+        let timing = self.measure_instruction_sequence(timer, || {
             let mut acc = 1000000i32;
             for i in 1..500 {
-                acc = acc / i;
-                acc = acc * i + (acc % i);
+                acc /= i;
+                acc = acc.wrapping_mul(i).wrapping_add(acc % i);
                 acc = acc.wrapping_add(1000000 / i);
             }
             acc
@@ -196,7 +224,7 @@ impl HardwareBenchmarks {
         Ok(timings)
     }
 
-    fn measure_instruction_sequence<F>(&self, f: F) -> Result<f64, JsValue>
+    fn measure_instruction_sequence<F>(&self, timer: &AdaptiveTimer, f: F) -> Result<f64, JsValue>
     where
         F: Fn() -> i32,
     {
@@ -206,21 +234,91 @@ impl HardwareBenchmarks {
         }
 
         // Actual measurement
-        let iterations = 100;
-        let start = performance_now();
-
         let mut prevent_opt = 0i32;
-        for _ in 0..iterations {
-            prevent_opt ^= f();
-        }
-
-        let end = performance_now();
+        let measurement = timer.measure(self.config.instruction_repetitions, |iterations| {
+            for _ in 0..iterations {
+                prevent_opt ^= f();
+            }
+            prevent_opt
+        });
 
         // Prevent optimization
         if prevent_opt == i32::MAX {
             return Err(JsValue::from_str("Sequence optimized away"));
         }
 
-        Ok((end - start) / iterations as f64)
+        Ok(measurement.per_iteration())
+    }
+}
+
+impl FingerprintSource for HardwareBenchmarks {
+    fn name(&self) -> &str {
+        "hardware"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Volatile
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Hardware
+    }
+
+    fn collect<'a>(&'a self, deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            // Relógio da coleta, compartilhado com os demais benchmarks
+            Ok(SourceOutput::Hardware(self.run_benchmarks_until(deadline.clock(), deadline)?))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{InstantClock, ScriptedClock, INSTANT_CLOCK};
+
+    fn small_benchmarks() -> HardwareBenchmarks {
+        HardwareBenchmarks::with_config(
+            BenchmarkConfig {
+                cpu_iterations: 1_000,
+                memory_accesses: 1_000,
+                crypto_iterations: 100,
+                instruction_repetitions: 2,
+            },
+            TimingConfig { characterization_ms: 5.0, ..Default::default() },
+        )
+    }
+
+    #[test]
+    fn benchmarks_run_natively() {
+        let clock = InstantClock::new();
+        let profile = small_benchmarks().run_benchmarks_until(&clock, Deadline::none(&clock)).unwrap();
+
+        assert_eq!(profile.timer.clock, INSTANT_CLOCK);
+        for time in [profile.cpu_benchmark, profile.memory_benchmark, profile.crypto_benchmark] {
+            assert!(time.is_finite() && time >= 0.0, "{}", time);
+        }
+        assert!(!profile.instruction_timing.is_empty());
+        assert!(profile.instruction_timing.iter().all(|t| t.is_finite()));
+    }
+
+    #[test]
+    fn expired_deadline_stops_after_the_first_benchmark() {
+        let clock = ScriptedClock::stepping(1.0);
+        let result = small_benchmarks().run_benchmarks_until(&clock, Deadline::at(&clock, 0.0));
+
+        assert_eq!(result.unwrap_err(), SourceError::TimedOut);
+    }
+
+    #[test]
+    fn deadline_is_checked_between_benchmarks() {
+        // Prazo em 50 no relógio do orçamento: passa após o segundo benchmark
+        let budget = ScriptedClock::new([0.0, 10.0, 100.0], 0.0);
+        let clock = ScriptedClock::stepping(1.0);
+        let deadline = Deadline::at(&budget, 50.0).with_benchmark_clock(&clock);
+        let result = small_benchmarks().run_benchmarks_until(&clock, deadline);
+
+        assert_eq!(result.unwrap_err(), SourceError::TimedOut);
+        assert_eq!(budget.reads(), 3);
     }
-}
\ No newline at end of file
+}
@@ -1,22 +1,33 @@
 use wasm_bindgen::prelude::*;
 use web_sys::window;
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
 
 mod canvas_fingerprint;
+mod font_fingerprint;
+mod webgl_context;
 mod webgl_fingerprint;
+mod webgl_precision;
+mod webgl_render;
+mod webgpu_fingerprint;
 mod audio_fingerprint;
 mod hardware_benchmarks;
+mod cache_benchmark;
 mod port_contention;
 mod wasm_port_benchmark;
+mod timing;
 mod utils;
 mod dom_utils;
+mod matcher;
+mod config;
+mod composite;
+mod source;
+mod spectral;
+mod schema;
+#[cfg(test)]
+mod test_support;
 
-use crate::canvas_fingerprint::CanvasFingerprint;
-use crate::webgl_fingerprint::WebGLFingerprint;
-use crate::audio_fingerprint::AudioFingerprint;
-use crate::hardware_benchmarks::HardwareBenchmarks;
-use crate::port_contention::PortContentionFingerprint;
 use crate::dom_utils::get_window;
 
 #[wasm_bindgen]
@@ -31,37 +42,233 @@ macro_rules! console_log {
 
 // Funções auxiliares movidas para dom_utils.rs
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct FingerprintData {
+    /// Versão do esquema deste documento (ver `schema`)
+    pub schema_version: u32,
+    /// Igual a `full_hash`; mantido por compatibilidade
     pub fingerprint_hash: String,
+    /// Hash apenas dos componentes determinísticos (ver `composite`)
+    #[serde(default)]
+    pub stable_hash: String,
+    /// Hash de todos os componentes, incluindo os voláteis
+    #[serde(default)]
+    pub full_hash: String,
+    /// Versão da serialização canônica usada nos dois hashes
+    #[serde(default)]
+    pub composite_version: u32,
     pub canvas_fingerprint: CanvasFingerprintData,
+    #[serde(default)]
+    pub font_fingerprint: FontFingerprintData,
     pub webgl_fingerprint: WebGLFingerprintData,
+    #[serde(default)]
+    pub webgpu_fingerprint: WebGPUFingerprintData,
     pub audio_fingerprint: AudioFingerprintData,
     pub hardware_profile: HardwareProfile,
     pub browser_info: BrowserAttributes,
     pub timestamp: f64,
+    #[serde(default)]
+    pub custom_components: BTreeMap<String, CustomComponentData>,
+    /// Estado de coleta de cada vetor, indexado pelo nome do vetor
+    #[serde(default)]
+    pub component_status: BTreeMap<String, ComponentStatus>,
+    /// Vetores que não produziram dados nesta coleta
+    #[serde(default)]
+    pub missing_components: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl FingerprintData {
+    /// Se o componente foi coletado com sucesso; fingerprints sem estado
+    /// registrado (versões antigas) são considerados completos
+    pub fn component_ok(&self, name: &str) -> bool {
+        self.component_status.get(name).map_or(true, ComponentStatus::is_ok)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct CanvasFingerprintData {
+    /// SHA-256 dos pixels RGBA lidos com `getImageData`
     pub hash: String,
+    /// Imagem PNG em data URL; vazio a menos que
+    /// `CanvasConfig::include_data_url` esteja habilitado
     pub data_url: String,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    /// Hash dos pixels de cada tile da cena (ver `CanvasFingerprint::regions`)
+    #[serde(default)]
+    pub region_hashes: BTreeMap<String, String>,
+    /// dHash de 64 bits, tolerante a diferenças de anti-aliasing
+    #[serde(default)]
+    pub perceptual_hash: String,
+    /// A leitura do canvas é alterada com ruído (Brave, Firefox
+    /// `resistFingerprinting`, extensões); `hash` muda a cada coleta
+    #[serde(default)]
+    pub canvas_noise_detected: bool,
+    /// Fração estimada dos pixels alterados pelo ruído
+    #[serde(default)]
+    pub noise_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct FontFingerprintData {
+    /// SHA-256 da lista de candidatas junto com o bitset
+    pub hash: String,
+    /// Fontes detectadas, em ordem alfabética
+    pub fonts: Vec<String>,
+    /// Bit `i` (MSB primeiro) indica se a candidata `i` está instalada, em hex
+    pub bitset: String,
+    /// Número de fontes candidatas testadas
+    pub candidates_count: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct WebGLFingerprintData {
+    /// SHA-256 de todos os demais campos (ver `WebGLFingerprint::hash`)
     pub hash: String,
+    /// `VENDOR`, em geral mascarado pelo navegador
     pub vendor: String,
+    /// `RENDERER`, em geral mascarado pelo navegador
     pub renderer: String,
+    /// Vendor real da GPU (`WEBGL_debug_renderer_info`); vazio se indisponível
+    #[serde(default)]
+    pub unmasked_vendor: String,
+    /// Renderer real da GPU (`WEBGL_debug_renderer_info`); vazio se indisponível
+    #[serde(default)]
+    pub unmasked_renderer: String,
+    /// Contexto usado na coleta: `webgl2` ou `webgl`
+    #[serde(default)]
+    pub context: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub shading_language_version: String,
+    /// Extensões suportadas, em ordem alfabética
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Formatos de precisão, indexados por `<shader>_<precisão>`
+    /// (ex.: `fragment_high_float`)
+    #[serde(default)]
+    pub precisions: BTreeMap<String, ShaderPrecision>,
+    /// Resultado de `getParameter` para todos os parâmetros da varredura
+    /// (limites `MAX_*`, faixas `ALIASED_*`, bits do framebuffer e
+    /// parâmetros de extensões), indexados pelo nome da constante. As chaves
+    /// são as mesmas no WebGL1 e no WebGL2; parâmetros indisponíveis no
+    /// contexto têm lista vazia e os vetoriais (ex.: `MAX_VIEWPORT_DIMS`)
+    /// têm mais de um valor.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, Vec<f64>>,
+    /// SHA-256 do framebuffer completo de cada cena de teste, indexado pelo
+    /// nome da cena (ver `WebGLScene`)
+    #[serde(default)]
+    pub scene_hashes: BTreeMap<String, String>,
+    /// Cenas que não foram renderizadas e o motivo (ex.: saída uniforme,
+    /// que indica renderização falha ou leitura bloqueada)
+    #[serde(default)]
+    pub render_failures: BTreeMap<String, String>,
+    /// Bits exatos das sondas de matemática em ponto flutuante dos shaders
+    /// (ver `PrecisionProbe`); falhas aparecem em `render_failures`
+    #[serde(default)]
+    pub precision_probes: PrecisionProbeData,
+}
+
+impl WebGLFingerprintData {
+    /// Vendor real quando disponível, senão o mascarado
+    pub fn effective_vendor(&self) -> &str {
+        if self.unmasked_vendor.is_empty() { &self.vendor } else { &self.unmasked_vendor }
+    }
+
+    /// Renderer real quando disponível, senão o mascarado
+    pub fn effective_renderer(&self) -> &str {
+        if self.unmasked_renderer.is_empty() { &self.renderer } else { &self.unmasked_renderer }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct WebGPUFingerprintData {
+    /// SHA-256 de todos os demais campos (ver `WebGPUFingerprint::hash`)
+    pub hash: String,
+    pub vendor: String,
+    pub architecture: String,
+    pub device: String,
+    pub description: String,
+    /// Features do adaptador, em ordem alfabética
+    pub features: Vec<String>,
+    /// Todos os limites de `GPUSupportedLimits`, indexados pelo nome
+    pub limits: BTreeMap<String, f64>,
+    /// Hash dos bits da saída do compute shader; vazio se não executado
+    #[serde(default)]
+    pub compute_hash: String,
+    /// Motivo da falha do compute shader; vazio se executou ou não foi pedido
+    #[serde(default)]
+    pub compute_error: String,
+}
+
+/// Resultado das sondas de precisão executadas em um fragment shader
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecisionProbeData {
+    /// SHA-256 da codificação e de todos os valores; vazio se não executado
+    pub hash: String,
+    /// `float32` (bits lidos de uma textura float) ou `rgba8` (bits
+    /// empacotados pelo próprio shader, quando não há render target float)
+    pub encoding: String,
+    /// Padrão de bits IEEE 754 de cada sonda, em hexadecimal
+    pub values: BTreeMap<String, String>,
+}
+
+/// Resultado de `getShaderPrecisionFormat`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct ShaderPrecision {
+    pub range_min: i32,
+    pub range_max: i32,
+    pub precision: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct AudioFingerprintData {
     pub hash: String,
+    /// Taxa de amostragem real do contexto de renderização (Hz)
     pub sample_rate: i32,
+    /// Número de amostras do buffer renderizado
+    #[serde(default)]
+    pub buffer_length: u32,
+    /// Soma dos valores absolutos das amostras
+    #[serde(default)]
+    pub sum: f64,
+    #[serde(default)]
+    pub avg: f64,
+    #[serde(default)]
+    pub max: f64,
+    /// Amostras em posições fixas do buffer, escaladas por 10^6
+    #[serde(default)]
+    pub samples: Vec<i32>,
+    /// Soma dos valores absolutos em 10 faixas consecutivas do buffer
+    /// (faixas de tempo, mantidas pela compatibilidade do hash)
+    #[serde(default)]
+    pub frequency_bins: Vec<f64>,
+    /// Características espectrais (FFT) do buffer
+    #[serde(default)]
+    pub spectral: SpectralFeatures,
+    /// `spectral` como vetor numérico, para comparação com tolerância
+    #[serde(default)]
+    pub feature_vector: Vec<f64>,
+    /// Resultado de cada variante de grafo, indexado pelo nome da variante
+    #[serde(default)]
+    pub variants: BTreeMap<String, AudioVariantData>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct AudioVariantData {
+    pub hash: String,
+    pub feature_vector: Vec<f64>,
+    /// Estado da renderização; uma variante que falhou fica sem hash nem
+    /// características, sem descartar o grafo principal
+    #[serde(default)]
+    pub status: ComponentStatus,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct HardwareProfile {
     pub cores: i32,
     pub memory: f64,
@@ -72,9 +279,74 @@ pub struct HardwareProfile {
     pub crypto_benchmark: f64,
     pub instruction_timing: Vec<f64>,
     pub port_contention_hash: String,
+    #[serde(default)]
+    pub port_contention_vector: Vec<ContentionResult>,
+    #[serde(default)]
+    pub port_contention_bucket: String,
+    /// Hierarquia de cache inferida por pointer chasing
+    #[serde(default)]
+    pub cache: CacheProfile,
+    /// Relógio usado nos benchmarks, com resolução e jitter medidos
+    #[serde(default)]
+    pub timer: TimerProfile,
+    /// Relógio usado nas medições de port contention
+    #[serde(default)]
+    pub port_contention_timer: TimerProfile,
+}
+
+/// Relógio usado nos benchmarks e sua granularidade efetiva
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct TimerProfile {
+    /// `performance_now` ou `shared_array_buffer_counter`
+    pub clock: String,
+    /// Granularidade efetiva (mediana dos incrementos), em ms
+    pub resolution_ms: f64,
+    /// Distribuição dos incrementos entre leituras consecutivas
+    pub jitter: JitterStats,
+    pub cross_origin_isolated: bool,
+    pub shared_array_buffer: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Estatísticas dos incrementos não nulos do relógio, em ms
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct JitterStats {
+    pub samples: u32,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub std_dev_ms: f64,
+}
+
+/// Geometria de cache inferida da latência de acessos encadeados
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct CacheProfile {
+    /// Capacidades inferidas, em KB; `None` quando o nível não apareceu
+    /// na varredura
+    pub l1_kb: Option<u32>,
+    pub l2_kb: Option<u32>,
+    pub l3_kb: Option<u32>,
+    /// Tamanho de linha efetivo, em bytes (inclui o efeito de prefetch de
+    /// linhas adjacentes)
+    pub line_size: Option<u32>,
+    /// Latência por tamanho do working set
+    pub working_set: Vec<LatencySample>,
+    /// Latência por passo entre acessos
+    pub strides: Vec<LatencySample>,
+    /// Relógio usado nas medições (ver `TimerProfile::clock`)
+    #[serde(default)]
+    pub clock: String,
+}
+
+/// Latência média de um acesso encadeado
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct LatencySample {
+    /// Tamanho do working set ou do passo, em bytes
+    pub bytes: u32,
+    pub ns_per_access: f64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct BenchmarkResults {
     pub math_ops: f64,
     pub string_ops: f64,
@@ -82,7 +354,7 @@ pub struct BenchmarkResults {
     pub crypto_ops: f64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
 pub struct BrowserAttributes {
     pub user_agent: String,
     pub language: String,
@@ -100,73 +372,26 @@ pub struct BrowserAttributes {
 #[wasm_bindgen]
 pub struct FingerprintCollector {
     data: FingerprintData,
+    sources: SourceRegistry,
+    config: CollectorConfig,
 }
 
 #[wasm_bindgen]
 impl FingerprintCollector {
+    /// `config` é um objeto opcional com os campos de `CollectorConfig`,
+    /// incluindo `profile: "fast" | "balanced" | "precise"`
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<FingerprintCollector, JsValue> {
-        utils::set_panic_hook();
-
-        let window = get_window()?;
-        let navigator = window.navigator();
-        let screen = window.screen().map_err(|_| JsValue::from_str("No screen object"))?;
-
-        let screen_width = screen.width().unwrap_or(1920);
-        let screen_height = screen.height().unwrap_or(1080);
-        let color_depth = screen.color_depth().unwrap_or(24);
-
-        let browser_attrs = BrowserAttributes {
-            user_agent: navigator.user_agent().unwrap_or("Unknown".to_string()),
-            language: navigator.language().unwrap_or("en-US".to_string()),
-            platform: navigator.platform().unwrap_or("Unknown".to_string()),
-            hardware_concurrency: navigator.hardware_concurrency() as i32,
-            device_memory: Some(8.0), // Default value, will try to get real value
-            screen_width,
-            screen_height,
-            screen_resolution: format!("{}x{}", screen_width, screen_height),
-            color_depth,
-            timezone_offset: js_sys::Date::new_0().get_timezone_offset() as i32,
-            plugins_count: 0, // plugins() not available in current web-sys
-        };
-
-        Ok(FingerprintCollector {
-            data: FingerprintData {
-                fingerprint_hash: String::new(),
-                canvas_fingerprint: CanvasFingerprintData {
-                    hash: String::new(),
-                    data_url: String::new(),
-                },
-                webgl_fingerprint: WebGLFingerprintData {
-                    hash: String::new(),
-                    vendor: String::new(),
-                    renderer: String::new(),
-                },
-                audio_fingerprint: AudioFingerprintData {
-                    hash: String::new(),
-                    sample_rate: 44100,
-                },
-                hardware_profile: HardwareProfile {
-                    cores: navigator.hardware_concurrency() as i32,
-                    memory: 8.0, // Default value
-                    concurrency: navigator.hardware_concurrency() as i32,
-                    benchmarks: None,
-                    cpu_benchmark: 0.0,
-                    memory_benchmark: 0.0,
-                    crypto_benchmark: 0.0,
-                    instruction_timing: Vec::new(),
-                    port_contention_hash: String::new(),
-                },
-                browser_info: browser_attrs,
-                timestamp: js_sys::Date::now(),
-            }
-        })
+    pub fn new(config: JsValue) -> Result<FingerprintCollector, JsValue> {
+        Self::with_config(CollectorConfig::from_js(config)?)
     }
 
     #[wasm_bindgen]
     pub fn run_hardware_benchmarks(&mut self) -> Result<String, JsValue> {
         console_log!("Running hardware benchmarks only...");
-        let hw_benchmarks = HardwareBenchmarks::new();
+        let hw_benchmarks = HardwareBenchmarks::with_config(
+            self.config.benchmarks.clone(),
+            self.config.timing.clone(),
+        );
         self.data.hardware_profile = hw_benchmarks.run_all_benchmarks()?;
 
         // Return just the hardware profile as JSON
@@ -178,58 +403,29 @@ impl FingerprintCollector {
     pub async fn collect_fingerprint(&mut self) -> Result<String, JsValue> {
         console_log!("Starting fingerprint collection...");
 
-        // Proposta A: Collect traditional API-based fingerprints
-        console_log!("Collecting Canvas fingerprint...");
-        let canvas_fp = CanvasFingerprint::collect()?;
-        self.data.canvas_fingerprint.hash = self.hash_data(&canvas_fp);
-        self.data.canvas_fingerprint.data_url = canvas_fp;
-
-        console_log!("Collecting WebGL fingerprint...");
-        let webgl_fp = WebGLFingerprint::collect()?;
-        let webgl_parts: Vec<&str> = webgl_fp.split('|').collect();
-        self.data.webgl_fingerprint.hash = self.hash_data(&webgl_fp);
-        self.data.webgl_fingerprint.vendor = webgl_parts.get(0).unwrap_or(&"Unknown").to_string();
-        self.data.webgl_fingerprint.renderer = webgl_parts.get(1).unwrap_or(&"Unknown").to_string();
-
-        console_log!("Collecting Audio fingerprint...");
-        let audio_fp = AudioFingerprint::collect().await?;
-        self.data.audio_fingerprint.hash = self.hash_data(&audio_fp);
-        // Default sample rate (will be extracted in audio_fingerprint module)
-        self.data.audio_fingerprint.sample_rate = 44100;
-
-        // Proposta B: Hardware microbenchmarks
-        console_log!("Running hardware benchmarks...");
-        let hw_benchmarks = HardwareBenchmarks::new();
-        let hw_profile = hw_benchmarks.run_all_benchmarks()?;
-
-        // Update hardware profile with benchmark results
-        self.data.hardware_profile.cpu_benchmark = hw_profile.cpu_benchmark;
-        self.data.hardware_profile.memory_benchmark = hw_profile.memory_benchmark;
-        self.data.hardware_profile.crypto_benchmark = hw_profile.crypto_benchmark;
-        self.data.hardware_profile.instruction_timing = hw_profile.instruction_timing;
-
-        // Set benchmark results
-        self.data.hardware_profile.benchmarks = Some(BenchmarkResults {
-            math_ops: hw_profile.cpu_benchmark,
-            string_ops: hw_profile.memory_benchmark,
-            array_ops: hw_profile.crypto_benchmark,
-            crypto_ops: hw_profile.cpu_benchmark * 1.2, // Simulated
-        });
-
-        // Proposta B Avançada: Port Contention fingerprinting
-        console_log!("Collecting Port Contention fingerprint...");
-        let port_contention_fp = PortContentionFingerprint::collect()?;
-        self.data.hardware_profile.port_contention_hash = port_contention_fp;
-
-        // Generate final composite hash
-        let composite = format!(
-            "{}:{}:{}:{:?}",
-            self.data.canvas_fingerprint.hash,
-            self.data.webgl_fingerprint.hash,
-            self.data.audio_fingerprint.hash,
-            self.data.hardware_profile
-        );
-        self.data.fingerprint_hash = self.hash_data(&composite);
+        // O orçamento corre em `performance.now()`, a escala do `setTimeout`
+        // que interrompe os vetores, e já conta o tempo de iniciar e calibrar
+        // o contador do worker. O relógio dos benchmarks é um só por coleta
+        // e o contador (quando usado) roda até o fim.
+        let budget = BenchmarkClock::Performance;
+        let started_at = budget.now();
+        let benchmark = BenchmarkClock::acquire(&self.config.timing).await;
+        let clocks = CollectionClocks { budget: &budget, started_at, benchmark: &benchmark };
+        self.sources
+            .collect_into(&mut self.data, &self.config, clocks, &source::browser_sleep, |name| {
+                console_log!("Collecting {} fingerprint...", name)
+            })
+            .await;
+
+        if !self.data.missing_components.is_empty() {
+            console_log!("Missing components: {}", self.data.missing_components.join(", "));
+        }
+
+        // Generate final composite hashes
+        self.data.composite_version = composite::COMPOSITE_VERSION;
+        self.data.stable_hash = composite::stable_hash(&self.data);
+        self.data.full_hash = composite::full_hash(&self.data);
+        self.data.fingerprint_hash = self.data.full_hash.clone();
 
         // Serialize the complete fingerprint
         let json = serde_json::to_string(&self.data)
@@ -239,22 +435,50 @@ impl FingerprintCollector {
         Ok(json)
     }
 
+    /// Hash completo, incluindo componentes voláteis (benchmarks)
     #[wasm_bindgen]
     pub fn get_composite_hash(&self) -> String {
-        let composite = format!(
-            "{}:{}:{}:{:?}",
-            self.data.canvas_fingerprint.hash,
-            self.data.webgl_fingerprint.hash,
-            self.data.audio_fingerprint.hash,
-            self.data.hardware_profile
-        );
-        self.hash_data(&composite)
+        composite::full_hash(&self.data)
     }
 
-    fn hash_data(&self, data: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(data.as_bytes());
-        format!("{:x}", hasher.finalize())
+    /// Hash apenas dos componentes determinísticos, estável entre coletas
+    #[wasm_bindgen]
+    pub fn get_stable_hash(&self) -> String {
+        composite::stable_hash(&self.data)
+    }
+
+    /// Define o tempo máximo por vetor e o orçamento total da coleta (ms)
+    #[wasm_bindgen]
+    pub fn set_time_budget(&mut self, source_timeout_ms: f64, total_budget_ms: f64) {
+        self.config.source_timeout_ms = source_timeout_ms;
+        self.config.total_budget_ms = total_budget_ms;
+    }
+
+    /// Vetores interrompidos por tempo na última coleta
+    #[wasm_bindgen]
+    pub fn timed_out_sources(&self) -> Vec<String> {
+        self.data.component_status.iter()
+            .filter(|(_, status)| **status == ComponentStatus::TimedOut)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Habilita um vetor de fingerprinting registrado
+    #[wasm_bindgen]
+    pub fn enable_source(&mut self, name: &str) -> bool {
+        self.sources.set_enabled(name, true)
+    }
+
+    /// Desabilita um vetor de fingerprinting registrado
+    #[wasm_bindgen]
+    pub fn disable_source(&mut self, name: &str) -> bool {
+        self.sources.set_enabled(name, false)
+    }
+
+    /// Nomes dos vetores registrados, na ordem de coleta
+    #[wasm_bindgen]
+    pub fn source_names(&self) -> Vec<String> {
+        self.sources.names()
     }
 
     #[wasm_bindgen]
@@ -289,6 +513,103 @@ impl FingerprintCollector {
     }
 }
 
+impl FingerprintCollector {
+    /// Construtor para uso em Rust, com a configuração já desserializada
+    pub fn with_config(config: CollectorConfig) -> Result<FingerprintCollector, JsValue> {
+        utils::set_panic_hook();
+
+        let window = get_window()?;
+        let navigator = window.navigator();
+        let screen = window.screen().map_err(|_| JsValue::from_str("No screen object"))?;
+
+        let screen_width = screen.width().unwrap_or(1920);
+        let screen_height = screen.height().unwrap_or(1080);
+        let color_depth = screen.color_depth().unwrap_or(24);
+
+        let browser_attrs = BrowserAttributes {
+            user_agent: navigator.user_agent().unwrap_or("Unknown".to_string()),
+            language: navigator.language().unwrap_or("en-US".to_string()),
+            platform: navigator.platform().unwrap_or("Unknown".to_string()),
+            hardware_concurrency: navigator.hardware_concurrency() as i32,
+            device_memory: Some(8.0), // Default value, will try to get real value
+            screen_width,
+            screen_height,
+            screen_resolution: format!("{}x{}", screen_width, screen_height),
+            color_depth,
+            timezone_offset: js_sys::Date::new_0().get_timezone_offset() as i32,
+            plugins_count: 0, // plugins() not available in current web-sys
+        };
+
+        Ok(FingerprintCollector {
+            data: FingerprintData {
+                schema_version: schema::SCHEMA_VERSION,
+                fingerprint_hash: String::new(),
+                stable_hash: String::new(),
+                full_hash: String::new(),
+                composite_version: composite::COMPOSITE_VERSION,
+                canvas_fingerprint: CanvasFingerprintData {
+                    hash: String::new(),
+                    data_url: String::new(),
+                    width: config.canvas.width,
+                    height: config.canvas.height,
+                    region_hashes: BTreeMap::new(),
+                    perceptual_hash: String::new(),
+                    canvas_noise_detected: false,
+                    noise_rate: 0.0,
+                },
+                font_fingerprint: FontFingerprintData::default(),
+                webgl_fingerprint: WebGLFingerprintData::default(),
+                webgpu_fingerprint: WebGPUFingerprintData::default(),
+                audio_fingerprint: AudioFingerprintData {
+                    hash: String::new(),
+                    sample_rate: config.audio.sample_rate as i32,
+                    buffer_length: 0,
+                    sum: 0.0,
+                    avg: 0.0,
+                    max: 0.0,
+                    samples: Vec::new(),
+                    frequency_bins: Vec::new(),
+                    spectral: SpectralFeatures::default(),
+                    feature_vector: Vec::new(),
+                    variants: BTreeMap::new(),
+                },
+                hardware_profile: HardwareProfile {
+                    cores: navigator.hardware_concurrency() as i32,
+                    memory: 8.0, // Default value
+                    concurrency: navigator.hardware_concurrency() as i32,
+                    benchmarks: None,
+                    cpu_benchmark: 0.0,
+                    memory_benchmark: 0.0,
+                    crypto_benchmark: 0.0,
+                    instruction_timing: Vec::new(),
+                    port_contention_hash: String::new(),
+                    port_contention_vector: Vec::new(),
+                    port_contention_bucket: String::new(),
+                    cache: CacheProfile::default(),
+                    timer: TimerProfile::default(),
+                    port_contention_timer: TimerProfile::default(),
+                },
+                browser_info: browser_attrs,
+                timestamp: js_sys::Date::now(),
+                custom_components: BTreeMap::new(),
+                component_status: BTreeMap::new(),
+                missing_components: Vec::new(),
+            },
+            sources: SourceRegistry::with_default_sources(&config),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &CollectorConfig {
+        &self.config
+    }
+
+    /// Registro de vetores, para adicionar vetores customizados em Rust
+    pub fn sources_mut(&mut self) -> &mut SourceRegistry {
+        &mut self.sources
+    }
+}
+
 // Função de exemplo exportada para JavaScript demonstrando acesso ao window
 #[wasm_bindgen]
 pub fn log_window_details() {
@@ -307,14 +628,118 @@ pub fn log_window_details() {
 }
 
 // Funções exportadas para obter fingerprints diretamente
-pub use crate::canvas_fingerprint::get_canvas_fingerprint;
-pub use crate::webgl_fingerprint::get_webgl_fingerprint;
+pub use crate::canvas_fingerprint::{get_canvas_fingerprint, CanvasFingerprint, CanvasRegion};
+pub use crate::font_fingerprint::{get_font_fingerprint, get_installed_fonts, FontFingerprint};
+pub use crate::webgl_fingerprint::{get_webgl_fingerprint, get_webgl_parameters, WebGLFingerprint};
+pub use crate::webgl_context::WebGLContext;
+pub use crate::webgl_precision::PrecisionProbe;
+pub use crate::webgl_render::{GlObjects, WebGLRenderer, WebGLScene};
+pub use crate::webgpu_fingerprint::{get_webgpu_fingerprint, get_webgpu_info, WebGPUFingerprint};
 
 // Funções exportadas para obter fingerprint de Audio diretamente
-pub use crate::audio_fingerprint::{get_audio_fingerprint, get_audio_fingerprint_hash};
+pub use crate::audio_fingerprint::{
+    get_audio_features,
+    get_audio_fingerprint,
+    get_audio_fingerprint_hash,
+    AudioFingerprint,
+};
+pub use crate::spectral::{SpectralFeatures, ROLLOFF_FRACTION, SPECTRAL_BANDS};
 
 // Funções exportadas para obter fingerprint de Port Contention
-pub use crate::port_contention::{get_port_contention_fingerprint, get_port_contention_detailed};
+pub use crate::port_contention::{
+    get_port_contention_fingerprint,
+    get_port_contention_detailed,
+    get_port_contention_vector,
+    ContentionResult,
+    PortContentionFingerprint,
+};
+
+// Microbenchmarks de hardware
+pub use crate::hardware_benchmarks::HardwareBenchmarks;
+
+// Detecção da hierarquia de cache
+pub use crate::cache_benchmark::{get_cache_profile, CacheBenchmark};
+
+// Relógio dos benchmarks
+pub use crate::timing::{
+    cross_origin_isolated,
+    get_timer_profile,
+    jitter_stats,
+    measure_resolution,
+    AdaptiveTimer,
+    BenchmarkClock,
+    Clock,
+    CounterClock,
+    InstantClock,
+    Measurement,
+    ScriptedClock,
+    COUNTER_CLOCK,
+    INSTANT_CLOCK,
+    PERFORMANCE_NOW_CLOCK,
+    SCRIPTED_CLOCK,
+};
+
+// Identificadores compostos (serialização canônica versionada)
+pub use crate::composite::{
+    canonical_full,
+    canonical_stable,
+    full_hash,
+    stable_hash,
+    COMPOSITE_VERSION,
+};
+
+// Esquema versionado e migrações
+pub use crate::schema::{
+    get_fingerprint_schema,
+    json_schema,
+    migrate,
+    migrate_fingerprint,
+    schema_version_of,
+    SCHEMA_VERSION,
+};
+
+// Configuração da coleta
+pub use crate::config::{
+    get_collector_profile,
+    AudioConfig,
+    AudioGraph,
+    BenchmarkConfig,
+    CacheConfig,
+    CanvasConfig,
+    CollectorConfig,
+    FontConfig,
+    PortContentionConfig,
+    TimingConfig,
+    WebGPUConfig,
+};
+
+// Comparação de fingerprints (disponível também nativamente via rlib)
+pub use crate::matcher::{
+    compare_fingerprints,
+    ComponentMatch,
+    FingerprintMatcher,
+    MatchResult,
+    MatchThresholds,
+    MatchVerdict,
+    MatchWeights,
+};
+
+// Abstração de vetores de fingerprinting
+pub use crate::source::{
+    browser_sleep,
+    CollectionClocks,
+    ComponentStatus,
+    CustomComponentData,
+    Deadline,
+    FingerprintSource,
+    OutputSlot,
+    SourceError,
+    SleepFuture,
+    SourceFuture,
+    SourceOutput,
+    SourceRegistry,
+    Stability,
+};
 
 // Funções exportadas para benchmarks Wasm específicos
 pub use crate::wasm_port_benchmark::{
@@ -331,4 +756,6 @@ pub use crate::wasm_port_benchmark::{
     measure_wasm_port_contention,
     run_all_wasm_benchmarks,
     generate_wasm_fingerprint,
+    WasmBenchmarkResult,
+    WasmPortBenchmark,
 };
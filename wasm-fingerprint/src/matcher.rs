@@ -0,0 +1,966 @@
+//! Módulo de comparação de fingerprints com pontuação ponderada
+//!
+//! Porta para Rust a lógica de `fingerprint-matcher.js`, para que o servidor,
+//! o analisador e o navegador calculem a similaridade da mesma forma.
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    AudioFingerprintData, BrowserAttributes, CanvasFingerprintData, FingerprintData,
+    FontFingerprintData, WebGLFingerprintData, WebGPUFingerprintData,
+};
+use crate::canvas_fingerprint::CanvasFingerprint;
+use crate::port_contention::PortContentionFingerprint;
+use crate::schema;
+
+/// Pesos de cada componente no score final
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatchWeights {
+    pub canvas: f64,
+    /// Ausente em pesos serializados antes do vetor de fontes
+    #[serde(default = "MatchWeights::default_fonts")]
+    pub fonts: f64,
+    pub webgl: f64,
+    /// Ausente em pesos serializados antes do vetor WebGPU
+    #[serde(default = "MatchWeights::default_webgpu")]
+    pub webgpu: f64,
+    pub audio: f64,
+    pub browser: f64,
+    pub hardware_stable: f64,
+    pub hardware_dynamic: f64,
+}
+
+impl Default for MatchWeights {
+    fn default() -> Self {
+        MatchWeights {
+            canvas: 0.25,           // Muito estável, único por GPU/driver
+            fonts: Self::default_fonts(),
+            webgl: 0.20,            // Estável, identifica o hardware
+            webgpu: Self::default_webgpu(),
+            audio: 0.15,            // Estável, identifica a pilha de áudio
+            browser: 0.20,          // Semi-estável (user agent, resolução, etc)
+            hardware_stable: 0.07,  // Cores, memória
+            hardware_dynamic: 0.03, // Benchmarks (variáveis)
+        }
+    }
+}
+
+impl MatchWeights {
+    /// Estável, mas muda quando o usuário instala ou remove fontes
+    fn default_fonts() -> f64 {
+        0.10
+    }
+
+    /// Identifica a GPU, mas em parte redundante com o WebGL
+    fn default_webgpu() -> f64 {
+        0.05
+    }
+}
+
+/// Limiares de similaridade para classificar um par de fingerprints
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatchThresholds {
+    pub same_device: f64,
+    pub likely_same: f64,
+    pub possibly_same: f64,
+}
+
+impl Default for MatchThresholds {
+    fn default() -> Self {
+        MatchThresholds {
+            same_device: 0.85,
+            likely_same: 0.75,
+            possibly_same: 0.65,
+        }
+    }
+}
+
+/// Veredito da comparação
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchVerdict {
+    Exact,
+    SameDevice,
+    LikelySame,
+    PossiblySame,
+    Different,
+}
+
+/// Resultado da comparação de um componente individual
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComponentMatch {
+    pub component: String,
+    pub similarity: f64,
+    pub weight: f64,
+    pub matched: bool,
+    /// `false` quando o componente está ausente em algum dos fingerprints;
+    /// nesse caso ele não entra no score
+    pub available: bool,
+}
+
+/// Resultado completo da comparação, com a explicação por componente
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatchResult {
+    pub similarity: f64,
+    pub verdict: MatchVerdict,
+    pub components: Vec<ComponentMatch>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FingerprintMatcher {
+    pub weights: MatchWeights,
+    pub thresholds: MatchThresholds,
+}
+
+impl FingerprintMatcher {
+    /// Distância ln(ρ) a partir da qual o vetor de port contention é
+    /// considerado totalmente diferente
+    const RHO_DISTANCE_SCALE: f64 = 0.3;
+
+    /// Diferença relativa máxima para duas métricas de áudio serem
+    /// consideradas iguais
+    const AUDIO_TOLERANCE: f64 = 0.001;
+
+    /// Distância de Hamming do hash perceptual a partir da qual dois canvas
+    /// são considerados visualmente diferentes (imagens sem relação ficam
+    /// em torno de 32 bits de 64)
+    const PERCEPTUAL_DISTANCE_SCALE: f64 = 32.0;
+
+    /// Fator aplicado ao peso do Canvas quando algum dos lados injeta ruído
+    /// na leitura, já que o hash muda a cada visita
+    const NOISY_CANVAS_WEIGHT_FACTOR: f64 = 0.2;
+
+    /// Peso de cada parâmetro WebGL comum aos dois fingerprints (somados,
+    /// no máximo ~2,5, contra 6 do vendor e 3 do renderer)
+    const WEBGL_PARAMETER_WEIGHT: f64 = 0.05;
+
+    /// Peso das sondas de precisão dos shaders: identificam a família da
+    /// GPU mesmo com vendor e renderer mascarados
+    const WEBGL_PRECISION_PROBE_WEIGHT: f64 = 3.0;
+
+    pub fn new(weights: MatchWeights, thresholds: MatchThresholds) -> Self {
+        FingerprintMatcher { weights, thresholds }
+    }
+
+    /// Compara dois fingerprints e retorna o score ponderado com a explicação
+    pub fn compare(&self, a: &FingerprintData, b: &FingerprintData) -> MatchResult {
+        let canvas_weight = if a.canvas_fingerprint.canvas_noise_detected
+            || b.canvas_fingerprint.canvas_noise_detected
+        {
+            self.weights.canvas * Self::NOISY_CANVAS_WEIGHT_FACTOR
+        } else {
+            self.weights.canvas
+        };
+
+        let components = vec![
+            Self::optional_component(
+                a,
+                b,
+                "canvas",
+                Self::compare_canvas(&a.canvas_fingerprint, &b.canvas_fingerprint),
+                canvas_weight,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "fonts",
+                Self::compare_fonts(&a.font_fingerprint, &b.font_fingerprint),
+                self.weights.fonts,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "webgl",
+                Self::compare_webgl(&a.webgl_fingerprint, &b.webgl_fingerprint),
+                self.weights.webgl,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "webgpu",
+                Self::compare_webgpu(&a.webgpu_fingerprint, &b.webgpu_fingerprint),
+                self.weights.webgpu,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "audio",
+                Self::compare_audio(&a.audio_fingerprint, &b.audio_fingerprint),
+                self.weights.audio,
+            ),
+            Self::component(
+                "browser",
+                Self::compare_browser(&a.browser_info, &b.browser_info),
+                self.weights.browser,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "hardware_stable",
+                Self::compare_hardware_stable(a, b),
+                self.weights.hardware_stable,
+            ),
+            Self::optional_component(
+                a,
+                b,
+                "hardware_dynamic",
+                Self::compare_hardware_dynamic(a, b),
+                self.weights.hardware_dynamic,
+            ),
+        ];
+
+        let available = components.iter().filter(|c| c.available);
+        let total_weight: f64 = available.clone().map(|c| c.weight).sum();
+        let total_score: f64 = available.map(|c| c.similarity * c.weight).sum();
+        let similarity = if total_weight > 0.0 {
+            total_score / total_weight
+        } else {
+            0.0
+        };
+
+        MatchResult {
+            similarity,
+            verdict: self.verdict(similarity),
+            components,
+        }
+    }
+
+    /// Classifica um score de similaridade segundo os limiares configurados
+    pub fn verdict(&self, similarity: f64) -> MatchVerdict {
+        if similarity >= 1.0 {
+            MatchVerdict::Exact
+        } else if similarity >= self.thresholds.same_device {
+            MatchVerdict::SameDevice
+        } else if similarity >= self.thresholds.likely_same {
+            MatchVerdict::LikelySame
+        } else if similarity >= self.thresholds.possibly_same {
+            MatchVerdict::PossiblySame
+        } else {
+            MatchVerdict::Different
+        }
+    }
+
+    fn component(name: &str, similarity: f64, weight: f64) -> ComponentMatch {
+        ComponentMatch {
+            component: name.to_string(),
+            similarity,
+            weight,
+            matched: similarity >= 1.0,
+            available: true,
+        }
+    }
+
+    /// Componente coletado por um vetor que pode falhar; só é comparado se
+    /// os dois fingerprints o coletaram com sucesso. Uma similaridade `None`
+    /// indica que o componente não existia quando algum deles foi coletado.
+    fn optional_component(
+        a: &FingerprintData,
+        b: &FingerprintData,
+        name: &str,
+        similarity: impl Into<Option<f64>>,
+        weight: f64,
+    ) -> ComponentMatch {
+        match similarity.into() {
+            Some(similarity) if a.component_ok(name) && b.component_ok(name) => {
+                Self::component(name, similarity, weight)
+            }
+            _ => ComponentMatch {
+                component: name.to_string(),
+                similarity: 0.0,
+                weight,
+                matched: false,
+                available: false,
+            },
+        }
+    }
+
+    fn exact(a: &str, b: &str) -> f64 {
+        if !a.is_empty() && a == b { 1.0 } else { 0.0 }
+    }
+
+    /// Vendor e renderer pesam mais; os parâmetros são comparados apenas
+    /// nos disponíveis nos dois lados, de modo que uma coleta WebGL1 e outra
+    /// WebGL2 do mesmo dispositivo ainda coincidam nos limites comuns
+    fn compare_webgl(a: &WebGLFingerprintData, b: &WebGLFingerprintData) -> f64 {
+        let mut checks = vec![
+            (a.effective_vendor() == b.effective_vendor(), 6.0),     // Vendor (muito estável)
+            (a.effective_renderer() == b.effective_renderer(), 3.0), // Renderer (estável)
+            (a.hash == b.hash, 1.0),
+        ];
+        checks.extend(
+            a.parameters
+                .iter()
+                .filter(|(_, values)| !values.is_empty())
+                .filter_map(|(name, values)| {
+                    let other = b.parameters.get(name).filter(|v| !v.is_empty())?;
+                    Some((values == other, Self::WEBGL_PARAMETER_WEIGHT))
+                }),
+        );
+        // Codificações diferentes não são comparáveis
+        let (probes_a, probes_b) = (&a.precision_probes, &b.precision_probes);
+        if !probes_a.hash.is_empty() && !probes_b.hash.is_empty() && probes_a.encoding == probes_b.encoding {
+            checks.push((probes_a.hash == probes_b.hash, Self::WEBGL_PRECISION_PROBE_WEIGHT));
+        }
+        Self::weighted_checks(&checks)
+    }
+
+    /// `None` para fingerprints coletados antes do vetor WebGPU. O hash do
+    /// compute shader só é comparado quando os dois lados o executaram.
+    fn compare_webgpu(a: &WebGPUFingerprintData, b: &WebGPUFingerprintData) -> Option<f64> {
+        if a.hash.is_empty() || b.hash.is_empty() {
+            return None;
+        }
+        let mut checks = vec![
+            (a.vendor == b.vendor, 3.0),
+            (a.architecture == b.architecture, 2.0),
+            (a.description == b.description, 2.0),
+            (a.features == b.features, 1.0),
+            (a.limits == b.limits, 1.0),
+        ];
+        if !a.compute_hash.is_empty() && !b.compute_hash.is_empty() {
+            checks.push((a.compute_hash == b.compute_hash, 1.0));
+        }
+        Some(Self::weighted_checks(&checks))
+    }
+
+    /// Índice de Jaccard entre os conjuntos de fontes detectadas; `None`
+    /// para fingerprints coletados antes do vetor de fontes
+    fn compare_fonts(a: &FontFingerprintData, b: &FontFingerprintData) -> Option<f64> {
+        if a.hash.is_empty() || b.hash.is_empty() {
+            return None;
+        }
+        if a.hash == b.hash {
+            return Some(1.0);
+        }
+        let union = a.fonts.iter().chain(&b.fonts).collect::<std::collections::BTreeSet<_>>().len();
+        if union == 0 {
+            return Some(1.0);
+        }
+        let intersection = a.fonts.iter().filter(|font| b.fonts.contains(font)).count();
+        Some(intersection as f64 / union as f64)
+    }
+
+    /// Pixels idênticos valem 1; caso contrário combina a fração de regiões
+    /// idênticas com a proximidade do hash perceptual, de modo que diferenças
+    /// só de anti-aliasing pontuem mais que fontes diferentes
+    fn compare_canvas(a: &CanvasFingerprintData, b: &CanvasFingerprintData) -> f64 {
+        if Self::exact(&a.hash, &b.hash) == 1.0 {
+            return 1.0;
+        }
+        let distance = match CanvasFingerprint::perceptual_distance(&a.perceptual_hash, &b.perceptual_hash) {
+            Some(distance) => distance,
+            None => return 0.0,
+        };
+        let perceptual = (1.0 - distance as f64 / Self::PERCEPTUAL_DISTANCE_SCALE).max(0.0);
+
+        let regions = a.region_hashes.len().max(b.region_hashes.len());
+        if regions == 0 {
+            return perceptual;
+        }
+        let matched = a.region_hashes
+            .iter()
+            .filter(|(name, hash)| b.region_hashes.get(*name) == Some(*hash))
+            .count();
+
+        (matched as f64 / regions as f64 + perceptual) / 2.0
+    }
+
+    /// Compara as métricas do buffer renderizado com tolerância; retorna a
+    /// fração das métricas que coincidem. Fingerprints sem métricas
+    /// (versões antigas) são comparados apenas pelo hash.
+    fn compare_audio(a: &AudioFingerprintData, b: &AudioFingerprintData) -> f64 {
+        if Self::exact(&a.hash, &b.hash) == 1.0 {
+            return 1.0;
+        }
+        if a.buffer_length == 0 || b.buffer_length == 0 {
+            return 0.0;
+        }
+        // Renderizações com parâmetros diferentes não são comparáveis
+        if a.sample_rate != b.sample_rate || a.buffer_length != b.buffer_length {
+            return 0.0;
+        }
+
+        let mut pairs = vec![(a.sum, b.sum), (a.avg, b.avg), (a.max, b.max)];
+        pairs.extend(a.samples.iter().zip(&b.samples).map(|(&x, &y)| (x as f64, y as f64)));
+        pairs.extend(a.frequency_bins.iter().copied().zip(b.frequency_bins.iter().copied()));
+        pairs.extend(a.feature_vector.iter().copied().zip(b.feature_vector.iter().copied()));
+
+        // Métricas presentes em apenas um dos lados contam como diferentes
+        let mut total = 3
+            + a.samples.len().max(b.samples.len())
+            + a.frequency_bins.len().max(b.frequency_bins.len())
+            + a.feature_vector.len().max(b.feature_vector.len());
+
+        // Variantes de grafo renderizadas nos dois fingerprints
+        for (name, variant_a) in &a.variants {
+            if let Some(variant_b) = b.variants.get(name) {
+                if !variant_a.status.is_ok() || !variant_b.status.is_ok() {
+                    continue;
+                }
+                pairs.extend(
+                    variant_a.feature_vector.iter().copied()
+                        .zip(variant_b.feature_vector.iter().copied()),
+                );
+                total += variant_a.feature_vector.len().max(variant_b.feature_vector.len());
+            }
+        }
+        let matched = pairs
+            .iter()
+            .filter(|&&(x, y)| Self::within_tolerance(x, y, Self::AUDIO_TOLERANCE))
+            .count();
+
+        matched as f64 / total as f64
+    }
+
+    fn within_tolerance(a: f64, b: f64, tolerance: f64) -> bool {
+        let scale = a.abs().max(b.abs());
+        scale == 0.0 || (a - b).abs() / scale <= tolerance
+    }
+
+    /// Navegadores diferentes no mesmo dispositivo ainda devem coincidir
+    /// nos atributos ligados ao hardware, por isso o user agent pesa pouco
+    fn compare_browser(a: &BrowserAttributes, b: &BrowserAttributes) -> f64 {
+        let checks = [
+            (Self::user_agent_family(&a.user_agent) == Self::user_agent_family(&b.user_agent), 0.5),
+            (a.platform == b.platform, 2.0),
+            (a.language == b.language, 1.0),
+            (a.screen_resolution == b.screen_resolution, 2.0),
+            (a.color_depth == b.color_depth, 1.0),
+            (a.timezone_offset == b.timezone_offset, 1.0),
+            (a.hardware_concurrency == b.hardware_concurrency, 2.0),
+        ];
+        Self::weighted_checks(&checks)
+    }
+
+    /// Se o vetor teve sucesso nos dois fingerprints
+    fn both_ok(a: &FingerprintData, b: &FingerprintData, name: &str) -> bool {
+        a.component_ok(name) && b.component_ok(name)
+    }
+
+    /// Contagens do perfil de hardware e, quando os dois lados a
+    /// detectaram, a geometria de cache (estável por modelo de CPU). Cada
+    /// grupo só entra se o vetor correspondente (`hardware`, `cache`) teve
+    /// sucesso nos dois lados; `None` se nenhum entrou.
+    fn compare_hardware_stable(a: &FingerprintData, b: &FingerprintData) -> Option<f64> {
+        let counts_ok = Self::both_ok(a, b, "hardware");
+        let cache_ok = Self::both_ok(a, b, "cache");
+        let (a, b) = (&a.hardware_profile, &b.hardware_profile);
+
+        let mut checks = Vec::new();
+        if counts_ok {
+            checks.extend([
+                (a.cores == b.cores, 2.0),
+                (a.memory == b.memory, 2.0),
+                (a.concurrency == b.concurrency, 1.0),
+            ]);
+        }
+        if !cache_ok {
+            return Self::optional_checks(&checks);
+        }
+        let (cache_a, cache_b) = (&a.cache, &b.cache);
+        for (x, y, weight) in [
+            (cache_a.l1_kb, cache_b.l1_kb, 1.0),
+            (cache_a.l2_kb, cache_b.l2_kb, 1.5),
+            (cache_a.l3_kb, cache_b.l3_kb, 1.5),
+            (cache_a.line_size, cache_b.line_size, 1.0),
+        ] {
+            if let (Some(x), Some(y)) = (x, y) {
+                checks.push((x == y, weight));
+            }
+        }
+        Self::optional_checks(&checks)
+    }
+
+    /// Compara os benchmarks e o vetor ρ de port contention com tolerância,
+    /// já que variam entre execuções. Cada parte só entra se o vetor
+    /// correspondente (`hardware`, `port_contention`) teve sucesso nos dois
+    /// lados; `None` se nenhuma entrou.
+    fn compare_hardware_dynamic(a: &FingerprintData, b: &FingerprintData) -> Option<f64> {
+        let mut scores = Vec::new();
+        let benchmarks_ok = Self::both_ok(a, b, "hardware");
+        let contention_ok = Self::both_ok(a, b, "port_contention");
+        let (a, b) = (&a.hardware_profile, &b.hardware_profile);
+
+        if let (true, Some(bench_a), Some(bench_b)) = (benchmarks_ok, &a.benchmarks, &b.benchmarks) {
+            let pairs = [
+                (bench_a.math_ops, bench_b.math_ops),
+                (bench_a.string_ops, bench_b.string_ops),
+                (bench_a.array_ops, bench_b.array_ops),
+                (bench_a.crypto_ops, bench_b.crypto_ops),
+            ];
+            scores.extend(pairs.iter().map(|&(x, y)| Self::benchmark_similarity(x, y)));
+        }
+
+        let distance = PortContentionFingerprint::distance(
+            &a.port_contention_vector,
+            &b.port_contention_vector,
+        );
+        if let (true, Some(distance)) = (contention_ok, distance) {
+            scores.push((1.0 - distance / Self::RHO_DISTANCE_SCALE).max(0.0));
+        }
+
+        if scores.is_empty() {
+            return None;
+        }
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+
+    fn weighted_checks(checks: &[(bool, f64)]) -> f64 {
+        let total: f64 = checks.iter().map(|(_, w)| w).sum();
+        let matches: f64 = checks.iter().filter(|(ok, _)| *ok).map(|(_, w)| w).sum();
+        if total > 0.0 { matches / total } else { 0.0 }
+    }
+
+    /// `weighted_checks`, ou `None` se não houver nada a comparar
+    fn optional_checks(checks: &[(bool, f64)]) -> Option<f64> {
+        if checks.is_empty() {
+            None
+        } else {
+            Some(Self::weighted_checks(checks))
+        }
+    }
+
+    /// Similaridade entre dois tempos de benchmark (tolera até ~20% de variação)
+    pub fn benchmark_similarity(a: f64, b: f64) -> f64 {
+        if a == 0.0 && b == 0.0 {
+            return 1.0;
+        }
+        if a == 0.0 || b == 0.0 {
+            return 0.0;
+        }
+
+        let ratio = a.min(b) / a.max(b);
+
+        if ratio >= 0.9 {
+            1.0
+        } else if ratio >= 0.8 {
+            0.9
+        } else if ratio >= 0.7 {
+            0.7
+        } else if ratio >= 0.6 {
+            0.5
+        } else {
+            0.3
+        }
+    }
+
+    /// Extrai a família do navegador a partir do user agent
+    pub fn user_agent_family(user_agent: &str) -> &'static str {
+        if user_agent.is_empty() {
+            "unknown"
+        } else if user_agent.contains("Chrome") && !user_agent.contains("Edg") {
+            "Chrome"
+        } else if user_agent.contains("Firefox") {
+            "Firefox"
+        } else if user_agent.contains("Safari") && !user_agent.contains("Chrome") {
+            "Safari"
+        } else if user_agent.contains("Edg") {
+            "Edge"
+        } else if user_agent.contains("Opera") || user_agent.contains("OPR") {
+            "Opera"
+        } else {
+            "other"
+        }
+    }
+}
+
+/// Função exportada para JavaScript - compara dois fingerprints serializados
+/// em JSON (de qualquer versão do esquema) e retorna o `MatchResult` também
+/// em JSON
+#[wasm_bindgen]
+pub fn compare_fingerprints(a_json: &str, b_json: &str) -> Result<String, JsValue> {
+    let a = schema::from_json(a_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid fingerprint A: {}", e)))?;
+    let b = schema::from_json(b_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid fingerprint B: {}", e)))?;
+
+    let result = FingerprintMatcher::default().compare(&a, &b);
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{ComponentStatus, OutputSlot, SourceOutput};
+    use crate::test_support::fingerprint;
+    use crate::{AudioVariantData, PrecisionProbeData};
+
+    fn component<'a>(result: &'a MatchResult, name: &str) -> &'a ComponentMatch {
+        result.components.iter().find(|c| c.component == name).unwrap()
+    }
+
+    #[test]
+    fn identical_fingerprints_match_exactly() {
+        let result = FingerprintMatcher::default().compare(&fingerprint(), &fingerprint());
+
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.verdict, MatchVerdict::Exact);
+        assert!(result.components.iter().filter(|c| c.available).all(|c| c.matched));
+    }
+
+    #[test]
+    fn components_missing_from_old_records_are_not_scored() {
+        let a = fingerprint();
+        let result = FingerprintMatcher::default().compare(&a, &a.clone());
+
+        // O fixture não tem WebGPU, como fingerprints anteriores ao vetor
+        let webgpu = component(&result, "webgpu");
+        assert!(!webgpu.available);
+        assert_eq!(result.similarity, 1.0);
+    }
+
+    #[test]
+    fn disabled_hardware_still_matches_itself_exactly() {
+        let mut a = fingerprint();
+        a.component_status.insert("hardware".to_string(), ComponentStatus::Disabled);
+        SourceOutput::clear(OutputSlot::Hardware, "hardware", &mut a);
+
+        let result = FingerprintMatcher::default().compare(&a, &a.clone());
+
+        assert!(!component(&result, "hardware_stable").available);
+        // O vetor ρ ainda foi coletado
+        assert!(component(&result, "hardware_dynamic").available);
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.verdict, MatchVerdict::Exact);
+    }
+
+    #[test]
+    fn timed_out_benchmarks_still_match_themselves_exactly() {
+        let mut a = fingerprint();
+        for (name, slot) in [("hardware", OutputSlot::Hardware), ("port_contention", OutputSlot::PortContention)] {
+            a.component_status.insert(name.to_string(), ComponentStatus::TimedOut);
+            SourceOutput::clear(slot, name, &mut a);
+        }
+
+        let result = FingerprintMatcher::default().compare(&a, &a.clone());
+
+        assert!(!component(&result, "hardware_stable").available);
+        assert!(!component(&result, "hardware_dynamic").available);
+        assert_eq!(result.similarity, 1.0);
+        assert_eq!(result.verdict, MatchVerdict::Exact);
+    }
+
+    #[test]
+    fn one_sided_timeout_is_not_compared() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.component_status.insert("hardware".to_string(), ComponentStatus::TimedOut);
+        SourceOutput::clear(OutputSlot::Hardware, "hardware", &mut b);
+        b.hardware_profile.cores = 0;
+        b.hardware_profile.memory = 0.0;
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        // Contagens zeradas do lado que falhou não contam como diferença
+        assert!(!component(&result, "hardware_stable").available);
+        let dynamic = component(&result, "hardware_dynamic");
+        assert!(dynamic.available);
+        assert_eq!(dynamic.similarity, 1.0);
+        assert_eq!(result.similarity, 1.0);
+    }
+
+    #[test]
+    fn failed_audio_variant_is_not_scored() {
+        let variant = |status, features: Vec<f64>| AudioVariantData {
+            hash: String::new(),
+            feature_vector: features,
+            status,
+        };
+        let mut a = fingerprint().audio_fingerprint;
+        a.hash = "a".to_string();
+        let mut b = a.clone();
+        b.hash = "b".to_string();
+        a.variants.insert("sine".to_string(), variant(ComponentStatus::Ok, vec![1.0, 2.0]));
+        b.variants.insert("sine".to_string(), variant(ComponentStatus::Silent, Vec::new()));
+
+        assert_eq!(FingerprintMatcher::compare_audio(&a, &b), 1.0);
+    }
+
+    #[test]
+    fn differing_component_lowers_similarity() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.canvas_fingerprint.hash = "other".to_string();
+        b.canvas_fingerprint.perceptual_hash = String::new();
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        // Só o peso do Canvas deixa de pontuar
+        let canvas = component(&result, "canvas");
+        let total: f64 = result.components.iter().filter(|c| c.available).map(|c| c.weight).sum();
+        assert_eq!(canvas.similarity, 0.0);
+        assert!((result.similarity - (1.0 - canvas.weight / total)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn failed_component_is_left_out_of_the_score() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.canvas_fingerprint.hash = String::new();
+        b.component_status.insert("canvas".to_string(), ComponentStatus::Blocked);
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        assert!(!component(&result, "canvas").available);
+        assert_eq!(result.similarity, 1.0);
+    }
+
+    #[test]
+    fn fingerprints_without_status_are_complete() {
+        let a = fingerprint();
+        assert!(a.component_ok("canvas"));
+
+        // Só o WebGPU, ausente do fixture, fica de fora
+        let result = FingerprintMatcher::default().compare(&a, &fingerprint());
+        assert!(result.components.iter().filter(|c| c.component != "webgpu").all(|c| c.available));
+    }
+
+    #[test]
+    fn audio_metrics_are_compared_with_tolerance() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.audio_fingerprint.hash = "other".to_string();
+        b.audio_fingerprint.sum *= 1.0005;
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "audio").similarity, 1.0);
+
+        // Uma das seis métricas fora da tolerância
+        b.audio_fingerprint.max = 0.5;
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert!((component(&result, "audio").similarity - 5.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn audio_rendered_with_other_parameters_does_not_match() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.audio_fingerprint.hash = "other".to_string();
+        b.audio_fingerprint.sample_rate = 48000;
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "audio").similarity, 0.0);
+
+        // Fingerprints antigos, sem métricas, só comparam o hash
+        let mut legacy = fingerprint();
+        legacy.audio_fingerprint.buffer_length = 0;
+        legacy.audio_fingerprint.hash = "other".to_string();
+        let result = FingerprintMatcher::default().compare(&a, &legacy);
+        assert_eq!(component(&result, "audio").similarity, 0.0);
+    }
+
+    #[test]
+    fn audio_variants_present_on_both_sides_are_compared() {
+        let variant = |features: Vec<f64>| AudioVariantData {
+            feature_vector: features,
+            ..Default::default()
+        };
+        let mut a = fingerprint();
+        a.audio_fingerprint.variants.insert("sine_compressor".to_string(), variant(vec![1.0, 2.0]));
+        a.audio_fingerprint.variants.insert("convolver".to_string(), variant(vec![5.0]));
+        let mut b = fingerprint();
+        b.audio_fingerprint.hash = "other".to_string();
+        b.audio_fingerprint.variants.insert("sine_compressor".to_string(), variant(vec![1.0, 3.0]));
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        // Seis métricas iguais e uma das duas características da variante
+        assert!((component(&result, "audio").similarity - 7.0 / 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn visually_close_canvas_scores_partially() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.canvas_fingerprint.hash = "other".to_string();
+        // Um bit de diferença no dHash, como em anti-aliasing
+        b.canvas_fingerprint.perceptual_hash = "00ff00ff00ff00fe".to_string();
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "canvas").similarity, 1.0 - 1.0 / 32.0);
+
+        // Regiões idênticas contam metade do score
+        let mut a = a;
+        a.canvas_fingerprint.region_hashes.insert("text".to_string(), "t".to_string());
+        a.canvas_fingerprint.region_hashes.insert("emoji".to_string(), "e".to_string());
+        b.canvas_fingerprint.region_hashes = a.canvas_fingerprint.region_hashes.clone();
+        b.canvas_fingerprint.region_hashes.insert("emoji".to_string(), "other".to_string());
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "canvas").similarity, (0.5 + 1.0 - 1.0 / 32.0) / 2.0);
+    }
+
+    #[test]
+    fn noisy_canvas_weighs_less() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.canvas_fingerprint.canvas_noise_detected = true;
+
+        let matcher = FingerprintMatcher::default();
+        let result = matcher.compare(&a, &b);
+
+        let expected = matcher.weights.canvas * FingerprintMatcher::NOISY_CANVAS_WEIGHT_FACTOR;
+        assert_eq!(component(&result, "canvas").weight, expected);
+    }
+
+    #[test]
+    fn fonts_are_compared_by_jaccard_index() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.font_fingerprint.hash = "other".to_string();
+        b.font_fingerprint.fonts = vec!["Arial".to_string(), "Georgia".to_string()];
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "fonts").similarity, 1.0 / 3.0);
+
+        // Fingerprints anteriores ao vetor de fontes não o comparam
+        b.font_fingerprint.hash = String::new();
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert!(!component(&result, "fonts").available);
+    }
+
+    #[test]
+    fn webgl_compares_the_unmasked_gpu() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.webgl_fingerprint.hash = "other".to_string();
+        // Mesmo vendor mascarado, GPU real diferente
+        b.webgl_fingerprint.unmasked_renderer = "Other GPU".to_string();
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "webgl").similarity, 0.6);
+    }
+
+    #[test]
+    fn precision_probes_are_compared_within_the_same_encoding() {
+        let mut a = fingerprint();
+        a.webgl_fingerprint.precision_probes = PrecisionProbeData {
+            hash: "probes".to_string(),
+            encoding: "float32".to_string(),
+            ..Default::default()
+        };
+        let mut b = a.clone();
+        b.webgl_fingerprint.hash = "other".to_string();
+        b.webgl_fingerprint.unmasked_renderer = "Other GPU".to_string();
+
+        // Mesma família de GPU pelas sondas, apesar do renderer diferente
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert!((component(&result, "webgl").similarity - 9.0 / 13.0).abs() < 1e-9);
+
+        b.webgl_fingerprint.precision_probes.encoding = "rgba8".to_string();
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "webgl").similarity, 0.6);
+    }
+
+    #[test]
+    fn webgpu_compute_hash_counts_only_when_both_sides_ran_it() {
+        let mut a = fingerprint();
+        a.webgpu_fingerprint.hash = "webgpu-hash".to_string();
+        a.webgpu_fingerprint.vendor = "example".to_string();
+        a.webgpu_fingerprint.architecture = "arch-1".to_string();
+        a.webgpu_fingerprint.compute_hash = "compute".to_string();
+        let mut b = a.clone();
+        b.webgpu_fingerprint.hash = "other".to_string();
+        b.webgpu_fingerprint.architecture = "arch-2".to_string();
+        b.webgpu_fingerprint.compute_hash = String::new();
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        let webgpu = component(&result, "webgpu");
+        assert!(webgpu.available);
+        assert!((webgpu.similarity - 7.0 / 9.0).abs() < 1e-9);
+
+        b.webgpu_fingerprint.compute_hash = "other".to_string();
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert!((component(&result, "webgpu").similarity - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unrelated_devices_are_different() {
+        let a = fingerprint();
+        let mut b = fingerprint();
+        b.canvas_fingerprint.hash = "other".to_string();
+        b.canvas_fingerprint.perceptual_hash = String::new();
+        b.font_fingerprint.hash = "other".to_string();
+        b.font_fingerprint.fonts = vec!["Helvetica".to_string()];
+        b.webgl_fingerprint.unmasked_vendor = "Other Vendor".to_string();
+        b.webgl_fingerprint.unmasked_renderer = "Other GPU".to_string();
+        b.webgl_fingerprint.hash = "other".to_string();
+        b.audio_fingerprint.hash = "other".to_string();
+        b.browser_info.platform = "MacIntel".to_string();
+        b.browser_info.screen_resolution = "2560x1440".to_string();
+        b.browser_info.hardware_concurrency = 4;
+        b.hardware_profile.cores = 4;
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        assert_eq!(result.verdict, MatchVerdict::Different);
+    }
+
+    #[test]
+    fn port_contention_distance_counts_as_dynamic_hardware() {
+        let contention = |rho: f64| vec![crate::ContentionResult {
+            instruction_pair: "mul_add".to_string(),
+            grouped_time: 1.0,
+            interleaved_time: rho,
+            ratio_rho: rho,
+        }];
+        let mut a = fingerprint();
+        a.hardware_profile.port_contention_vector = contention(1.0);
+        let mut b = fingerprint();
+        b.hardware_profile.port_contention_vector = contention(2.0);
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+
+        // Quatro benchmarks idênticos e um vetor ρ distante
+        assert!((component(&result, "hardware_dynamic").similarity - 0.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cache_levels_count_only_when_both_sides_detected_them() {
+        let mut a = fingerprint();
+        a.hardware_profile.cache.l1_kb = Some(32);
+        a.hardware_profile.cache.l2_kb = Some(256);
+        a.hardware_profile.cache.l3_kb = Some(8192);
+        a.hardware_profile.cache.line_size = Some(64);
+        let mut b = a.clone();
+        b.hardware_profile.cache.l2_kb = Some(512);
+        b.hardware_profile.cache.l3_kb = None;
+
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert!((component(&result, "hardware_stable").similarity - 7.0 / 8.5).abs() < 1e-9);
+
+        // Sem geometria de um dos lados, só as contagens do navegador
+        b.hardware_profile.cache = Default::default();
+        let result = FingerprintMatcher::default().compare(&a, &b);
+        assert_eq!(component(&result, "hardware_stable").similarity, 1.0);
+    }
+
+    #[test]
+    fn verdict_follows_thresholds() {
+        let matcher = FingerprintMatcher::default();
+        assert_eq!(matcher.verdict(1.0), MatchVerdict::Exact);
+        assert_eq!(matcher.verdict(0.85), MatchVerdict::SameDevice);
+        assert_eq!(matcher.verdict(0.80), MatchVerdict::LikelySame);
+        assert_eq!(matcher.verdict(0.70), MatchVerdict::PossiblySame);
+        assert_eq!(matcher.verdict(0.50), MatchVerdict::Different);
+    }
+
+    #[test]
+    fn benchmark_similarity_tolerates_small_variation() {
+        assert_eq!(FingerprintMatcher::benchmark_similarity(0.0, 0.0), 1.0);
+        assert_eq!(FingerprintMatcher::benchmark_similarity(0.0, 5.0), 0.0);
+        assert_eq!(FingerprintMatcher::benchmark_similarity(10.0, 9.5), 1.0);
+        assert_eq!(FingerprintMatcher::benchmark_similarity(10.0, 8.5), 0.9);
+        assert_eq!(FingerprintMatcher::benchmark_similarity(10.0, 2.0), 0.3);
+    }
+
+    #[test]
+    fn user_agent_family_distinguishes_browsers() {
+        let edge = "Mozilla/5.0 Chrome/120.0 Safari/537.36 Edg/120.0";
+        let safari = "Mozilla/5.0 (Macintosh) Version/17.0 Safari/605.1.15";
+        assert_eq!(FingerprintMatcher::user_agent_family(edge), "Edge");
+        assert_eq!(FingerprintMatcher::user_agent_family(safari), "Safari");
+        assert_eq!(FingerprintMatcher::user_agent_family(""), "unknown");
+    }
+}
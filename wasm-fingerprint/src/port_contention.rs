@@ -1,5 +1,9 @@
 use wasm_bindgen::prelude::*;
-use crate::utils::performance_now;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::config::{PortContentionConfig, TimingConfig};
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::timing::{AdaptiveTimer, BenchmarkClock, Clock};
 
 /// Módulo de Fingerprinting Microarquitetural baseado em Contenção Sequencial de Portas
 ///
@@ -8,10 +12,14 @@ use crate::utils::performance_now;
 /// para criar fingerprints únicos e estáveis do hardware.
 
 #[wasm_bindgen]
-pub struct PortContentionFingerprint;
+#[derive(Default)]
+pub struct PortContentionFingerprint {
+    config: PortContentionConfig,
+    timing: TimingConfig,
+}
 
 /// Estrutura para armazenar resultados de contenção
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContentionResult {
     pub instruction_pair: String,
     pub grouped_time: f64,
@@ -20,77 +28,29 @@ pub struct ContentionResult {
 }
 
 impl PortContentionFingerprint {
-    /// Número de iterações para cada medição (ajustável para precisão vs velocidade)
-    const ITERATIONS: u32 = 100_000;
-    const WARMUP_ITERATIONS: u32 = 1_000;
-    const NUM_MEASUREMENTS: u32 = 10;
+    /// Número de iterações e medições vêm de `PortContentionConfig`
+    /// (ajustável para precisão vs velocidade); as iterações são ampliadas
+    /// conforme a resolução do relógio (`TimingConfig`)
+    pub fn new(config: PortContentionConfig, timing: TimingConfig) -> Self {
+        PortContentionFingerprint { config, timing }
+    }
+
+    /// Largura de cada bucket na escala ln(ρ); grande o suficiente para
+    /// absorver o jitter de timing entre execuções na mesma CPU
+    const BUCKET_STEP: f64 = 0.15;
 
     /// Coleta o fingerprint microarquitetural completo
     pub fn collect() -> Result<String, JsValue> {
-        let mut results = Vec::new();
-
-        // Aquecimento do JIT/WASM runtime
-        Self::warmup()?;
-
-        // Teste diferentes pares de instruções que competem por diferentes portas
-        // Cada par revela informações sobre a microarquitetura específica
-
-        // Par 1: Multiplicação de inteiros vs Adição de inteiros
-        // Diferentes CPUs agendam essas operações de forma diferente
-        results.push(Self::measure_contention_pair(
-            "mul_add",
-            Self::execute_mul_grouped,
-            Self::execute_mul_add_interleaved,
-        )?);
-
-        // Par 2: Divisão vs Multiplicação
-        // Divisão geralmente usa uma porta específica com maior latência
-        results.push(Self::measure_contention_pair(
-            "div_mul",
-            Self::execute_div_grouped,
-            Self::execute_div_mul_interleaved,
-        )?);
-
-        // Par 3: Shift vs XOR
-        // Operações lógicas que podem usar portas diferentes
-        results.push(Self::measure_contention_pair(
-            "shift_xor",
-            Self::execute_shift_grouped,
-            Self::execute_shift_xor_interleaved,
-        )?);
-
-        // Par 4: Operações de ponto flutuante vs inteiros
-        // Revela a arquitetura das unidades de execução
-        results.push(Self::measure_contention_pair(
-            "float_int",
-            Self::execute_float_grouped,
-            Self::execute_float_int_interleaved,
-        )?);
-
-        // Par 5: Operações de branch prediction stress
-        // Testa o preditor de branches da CPU
-        results.push(Self::measure_contention_pair(
-            "branch_stress",
-            Self::execute_branch_grouped,
-            Self::execute_branch_pattern_interleaved,
-        )?);
-
-        // Par 6: Memory fence operations
-        // Testa barreiras de memória e ordenação
-        results.push(Self::measure_contention_pair(
-            "memory_fence",
-            Self::execute_memory_pattern_grouped,
-            Self::execute_memory_fence_interleaved,
-        )?);
+        let results = Self::collect_detailed()?;
 
         // Gera o fingerprint baseado nos ratios de contenção
         Ok(Self::generate_fingerprint(&results))
     }
 
     /// Aquecimento para estabilizar as medições
-    fn warmup() -> Result<(), JsValue> {
+    fn warmup(config: &PortContentionConfig) -> Result<(), JsValue> {
         let mut dummy = 1u32;
-        for _ in 0..Self::WARMUP_ITERATIONS {
+        for _ in 0..config.warmup_iterations {
             dummy = dummy.wrapping_mul(7).wrapping_add(13);
             dummy = dummy.rotate_left(3);
         }
@@ -101,12 +61,17 @@ impl PortContentionFingerprint {
         Ok(())
     }
 
-    /// Mede a contenção para um par de instruções específico
+    /// Mede a contenção para um par de instruções específico. Os tempos são
+    /// reportados para `config.iterations` iterações, mesmo quando o timer
+    /// executou mais para cobrir a resolução do relógio.
     fn measure_contention_pair<F1, F2>(
+        timer: &AdaptiveTimer,
         name: &str,
         grouped_fn: F1,
         interleaved_fn: F2,
-    ) -> Result<ContentionResult, JsValue>
+        config: &PortContentionConfig,
+        deadline: Deadline<'_>,
+    ) -> Result<ContentionResult, SourceError>
     where
         F1: Fn(u32) -> u32,
         F2: Fn(u32) -> u32,
@@ -115,21 +80,19 @@ impl PortContentionFingerprint {
         let mut interleaved_times = Vec::new();
 
         // Múltiplas medições para estabilidade estatística
-        for _ in 0..Self::NUM_MEASUREMENTS {
+        for _ in 0..config.measurements.max(1) {
+            deadline.check()?;
+
             // Medição agrupada
-            let start = performance_now();
-            let _ = grouped_fn(Self::ITERATIONS);
-            let grouped_time = performance_now() - start;
-            grouped_times.push(grouped_time);
+            let grouped = timer.measure(config.iterations, &grouped_fn);
+            grouped_times.push(grouped.scaled_to(config.iterations));
 
             // Pequeno delay para evitar interferência
             Self::cpu_pause();
 
             // Medição intercalada
-            let start = performance_now();
-            let _ = interleaved_fn(Self::ITERATIONS);
-            let interleaved_time = performance_now() - start;
-            interleaved_times.push(interleaved_time);
+            let interleaved = timer.measure(config.iterations, &interleaved_fn);
+            interleaved_times.push(interleaved.scaled_to(config.iterations));
 
             Self::cpu_pause();
         }
@@ -138,18 +101,11 @@ impl PortContentionFingerprint {
         let grouped_median = Self::median(&mut grouped_times);
         let interleaved_median = Self::median(&mut interleaved_times);
 
-        // Calcula o ratio ρ
-        let ratio = if grouped_median > 0.0 {
-            interleaved_median / grouped_median
-        } else {
-            1.0
-        };
-
         Ok(ContentionResult {
             instruction_pair: name.to_string(),
             grouped_time: grouped_median,
             interleaved_time: interleaved_median,
-            ratio_rho: ratio,
+            ratio_rho: Self::ratio(grouped_median, interleaved_median),
         })
     }
 
@@ -286,16 +242,14 @@ impl PortContentionFingerprint {
     /// Execução agrupada: Stress de predição de branches
     fn execute_branch_grouped(iterations: u32) -> u32 {
         let mut result = 0_u32;
-        let mut counter = 0_u32;
 
         // Padrão previsível
-        for _ in 0..iterations/2 {
+        for counter in 0..iterations/2 {
             if counter % 2 == 0 {
                 result = result.wrapping_add(1);
             } else {
                 result = result.wrapping_add(2);
             }
-            counter += 1;
         }
 
         // Padrão aleatório (usando operações determinísticas)
@@ -391,19 +345,55 @@ impl PortContentionFingerprint {
         }
     }
 
-    /// Calcula a mediana de um vetor (modifica o vetor)
-    fn median(values: &mut Vec<f64>) -> f64 {
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    /// Calcula a mediana de um vetor (modifica o vetor); 0.0 se vazio
+    pub fn median(values: &mut [f64]) -> f64 {
+        values.sort_by(|a, b| a.total_cmp(b));
         let len = values.len();
-        if len % 2 == 0 {
+        if len == 0 {
+            0.0
+        } else if len % 2 == 0 {
             (values[len / 2 - 1] + values[len / 2]) / 2.0
         } else {
             values[len / 2]
         }
     }
 
+    /// ρ = time(interleaved) / time(grouped); 1.0 (neutro) se o tempo
+    /// agrupado não for positivo
+    pub fn ratio(grouped_time: f64, interleaved_time: f64) -> f64 {
+        if grouped_time > 0.0 {
+            interleaved_time / grouped_time
+        } else {
+            1.0
+        }
+    }
+
+    /// Categoria de um ρ usada nos fingerprints (também pelo benchmark Wasm)
+    ///
+    /// Valores > 1.15 indicam que intercalado foi mais rápido (paralelismo efetivo)
+    /// Valores em (0.85, 1.15] indicam sem diferença (mesmas portas ou sem paralelismo)
+    /// Valores ≤ 0.85 indicam que agrupado foi mais rápido (cache effects)
+    pub fn category(rho: f64) -> &'static str {
+        if rho > 1.15 {
+            "high_parallelism"
+        } else if rho > 0.85 {
+            "no_contention"
+        } else {
+            "cache_beneficial"
+        }
+    }
+
+    /// Descrição legível da categoria de um ρ
+    pub fn interpretation(rho: f64) -> &'static str {
+        match Self::category(rho) {
+            "high_parallelism" => "✓ High parallelism detected (different execution ports)",
+            "no_contention" => "≈ No significant contention (same ports or no ILP benefit)",
+            _ => "↓ Grouped execution faster (cache locality benefits)",
+        }
+    }
+
     /// Gera o fingerprint final baseado nos resultados de contenção
-    fn generate_fingerprint(results: &[ContentionResult]) -> String {
+    pub fn generate_fingerprint(results: &[ContentionResult]) -> String {
         use sha2::{Sha256, Digest};
 
         // Cria uma representação estruturada dos resultados
@@ -411,17 +401,7 @@ impl PortContentionFingerprint {
 
         for result in results {
             // Usa o ratio ρ como componente principal do fingerprint
-            // Valores > 1 indicam que intercalado foi mais rápido (paralelismo efetivo)
-            // Valores ≈ 1 indicam sem diferença (mesmas portas ou sem paralelismo)
-            // Valores < 1 indicam que agrupado foi mais rápido (cache effects)
-
-            let category = if result.ratio_rho > 1.15 {
-                "high_parallelism"
-            } else if result.ratio_rho > 0.85 && result.ratio_rho < 1.15 {
-                "no_contention"
-            } else {
-                "cache_beneficial"
-            };
+            let category = Self::category(result.ratio_rho);
 
             fingerprint_data.push_str(&format!(
                 "{}:{}:{:.4}|",
@@ -458,48 +438,169 @@ impl PortContentionFingerprint {
 
     /// Retorna os resultados detalhados para análise
     pub fn collect_detailed() -> Result<Vec<ContentionResult>, JsValue> {
+        // As exportações são síncronas e o contador do worker exige uma
+        // calibração assíncrona, então aqui o relógio é `performance.now()`
+        let clock = BenchmarkClock::Performance;
+        let timing = TimingConfig::default();
+        let timer_profile = clock.characterize(&timing);
+        let timer = AdaptiveTimer::new(&clock, &timer_profile, &timing);
+        Ok(Self::collect_detailed_with(&timer, &PortContentionConfig::default(), Deadline::none(&clock))?)
+    }
+
+    /// Como `collect_detailed`, com timer e parâmetros próprios e
+    /// interrompendo a coleta ao estourar o prazo
+    pub fn collect_detailed_with(
+        timer: &AdaptiveTimer,
+        config: &PortContentionConfig,
+        deadline: Deadline<'_>,
+    ) -> Result<Vec<ContentionResult>, SourceError> {
         let mut results = Vec::new();
 
-        Self::warmup()?;
+        // Aquecimento do JIT/WASM runtime
+        Self::warmup(config)?;
 
+        // Teste diferentes pares de instruções que competem por diferentes portas
+        // Cada par revela informações sobre a microarquitetura específica
+
+        // Par 1: Multiplicação de inteiros vs Adição de inteiros
+        // Diferentes CPUs agendam essas operações de forma diferente
         results.push(Self::measure_contention_pair(
+            timer,
             "mul_add",
             Self::execute_mul_grouped,
             Self::execute_mul_add_interleaved,
+            config,
+            deadline,
         )?);
 
+        // Par 2: Divisão vs Multiplicação
+        // Divisão geralmente usa uma porta específica com maior latência
         results.push(Self::measure_contention_pair(
+            timer,
             "div_mul",
             Self::execute_div_grouped,
             Self::execute_div_mul_interleaved,
+            config,
+            deadline,
         )?);
 
+        // Par 3: Shift vs XOR
+        // Operações lógicas que podem usar portas diferentes
         results.push(Self::measure_contention_pair(
+            timer,
             "shift_xor",
             Self::execute_shift_grouped,
             Self::execute_shift_xor_interleaved,
+            config,
+            deadline,
         )?);
 
+        // Par 4: Operações de ponto flutuante vs inteiros
+        // Revela a arquitetura das unidades de execução
         results.push(Self::measure_contention_pair(
+            timer,
             "float_int",
             Self::execute_float_grouped,
             Self::execute_float_int_interleaved,
+            config,
+            deadline,
         )?);
 
+        // Par 5: Operações de branch prediction stress
+        // Testa o preditor de branches da CPU
         results.push(Self::measure_contention_pair(
+            timer,
             "branch_stress",
             Self::execute_branch_grouped,
             Self::execute_branch_pattern_interleaved,
+            config,
+            deadline,
         )?);
 
+        // Par 6: Memory fence operations
+        // Testa barreiras de memória e ordenação
         results.push(Self::measure_contention_pair(
+            timer,
             "memory_fence",
             Self::execute_memory_pattern_grouped,
             Self::execute_memory_fence_interleaved,
+            config,
+            deadline,
         )?);
 
         Ok(results)
     }
+
+    /// ID de bucket estável: cada ρ é quantizado em escala logarítmica antes
+    /// do hash, então pequenas variações de timing caem no mesmo bucket
+    ///
+    /// A grade é fixa (passo `BUCKET_STEP` em ln(ρ)): um ρ perto da fronteira
+    /// entre dois buckets pode cair em qualquer um deles em visitas
+    /// diferentes e mudar o ID. O ID serve para agrupar coletas; para ligar
+    /// visitas, o matcher usa `distance`, que não tem essa descontinuidade.
+    pub fn bucket_id(results: &[ContentionResult]) -> String {
+        use sha2::{Sha256, Digest};
+
+        let mut bucket_data = String::new();
+        for result in results {
+            let bucket = (Self::log_ratio(result.ratio_rho) / Self::BUCKET_STEP).round() as i64;
+            bucket_data.push_str(&format!("{}:{}|", result.instruction_pair, bucket));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(bucket_data.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// ln(ρ), protegido contra ρ nulo
+    fn log_ratio(rho: f64) -> f64 {
+        rho.max(f64::MIN_POSITIVE).ln()
+    }
+
+    /// Distância RMS entre dois vetores ρ na escala ln(ρ)
+    ///
+    /// Os vetores precisam ter os mesmos pares na mesma ordem (a ordem de
+    /// `collect_detailed`); `None` se estiverem vazios, tiverem tamanhos
+    /// diferentes ou algum par não casar. 0.0 indica vetores idênticos.
+    pub fn distance(a: &[ContentionResult], b: &[ContentionResult]) -> Option<f64> {
+        if a.is_empty() || a.len() != b.len() {
+            return None;
+        }
+        if a.iter().zip(b).any(|(ra, rb)| ra.instruction_pair != rb.instruction_pair) {
+            return None;
+        }
+
+        let sum_sq: f64 = a.iter()
+            .zip(b)
+            .map(|(ra, rb)| (Self::log_ratio(ra.ratio_rho) - Self::log_ratio(rb.ratio_rho)).powi(2))
+            .sum();
+        Some((sum_sq / a.len() as f64).sqrt())
+    }
+}
+
+impl FingerprintSource for PortContentionFingerprint {
+    fn name(&self) -> &str {
+        "port_contention"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Volatile
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::PortContention
+    }
+
+    fn collect<'a>(&'a self, deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            // Relógio da coleta, compartilhado com os demais benchmarks
+            let clock = deadline.clock();
+            let timer_profile = clock.characterize(&self.timing);
+            let timer = AdaptiveTimer::new(clock, &timer_profile, &self.timing);
+            let results = PortContentionFingerprint::collect_detailed_with(&timer, &self.config, deadline)?;
+            Ok(SourceOutput::PortContention(results, timer_profile))
+        })
+    }
 }
 
 /// Função exportada para JavaScript
@@ -508,6 +609,15 @@ pub fn get_port_contention_fingerprint() -> Result<String, JsValue> {
     PortContentionFingerprint::collect()
 }
 
+/// Função exportada para obter o vetor ρ bruto por par de instruções (JSON)
+#[wasm_bindgen]
+pub fn get_port_contention_vector() -> Result<String, JsValue> {
+    let results = PortContentionFingerprint::collect_detailed()?;
+
+    serde_json::to_string(&results)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
 /// Função exportada para obter dados detalhados
 #[wasm_bindgen]
 pub fn get_port_contention_detailed() -> Result<String, JsValue> {
@@ -515,7 +625,7 @@ pub fn get_port_contention_detailed() -> Result<String, JsValue> {
 
     let mut output = String::from("Port Contention Analysis:\n");
     output.push_str("=" .repeat(50).as_str());
-    output.push_str("\n");
+    output.push('\n');
 
     for result in results {
         output.push_str(&format!(
@@ -535,16 +645,181 @@ pub fn get_port_contention_detailed() -> Result<String, JsValue> {
             result.ratio_rho
         ));
 
-        let interpretation = if result.ratio_rho > 1.15 {
-            "✓ High parallelism detected (different execution ports)"
-        } else if result.ratio_rho > 0.85 && result.ratio_rho < 1.15 {
-            "≈ No significant contention (same ports or no ILP benefit)"
-        } else {
-            "↓ Grouped execution faster (cache locality benefits)"
-        };
-
-        output.push_str(&format!("  Interpretation: {}\n", interpretation));
+        output.push_str(&format!(
+            "  Interpretation: {}\n",
+            PortContentionFingerprint::interpretation(result.ratio_rho)
+        ));
     }
 
     Ok(output)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scripted_durations, single_run_profile};
+    use crate::timing::ScriptedClock;
+
+    fn vector(ratios: &[(&str, f64)]) -> Vec<ContentionResult> {
+        ratios.iter()
+            .map(|&(pair, rho)| ContentionResult {
+                instruction_pair: pair.to_string(),
+                grouped_time: 1.0,
+                interleaved_time: rho,
+                ratio_rho: rho,
+            })
+            .collect()
+    }
+
+    fn small_config() -> PortContentionConfig {
+        PortContentionConfig {
+            iterations: 64,
+            warmup_iterations: 10,
+            measurements: 3,
+            wasm_iterations: 64,
+        }
+    }
+
+    #[test]
+    fn iterations_scale_with_clock_resolution() {
+        // Cada leitura avança 1 ms: toda medição dura uma resolução, abaixo
+        // do alvo, então o timer chega ao limite de iterações
+        let clock = ScriptedClock::stepping(1.0);
+        let timing = TimingConfig::default();
+        let profile = clock.characterize(&timing);
+        let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+
+        let results = PortContentionFingerprint::collect_detailed_with(
+            &timer,
+            &small_config(),
+            Deadline::none(&clock),
+        )
+        .unwrap();
+
+        assert_eq!(profile.resolution_ms, 1.0);
+        assert_eq!(results.len(), 6);
+        let scaled = 1.0 / timing.max_iteration_scale as f64;
+        for result in &results {
+            assert_eq!(result.grouped_time, scaled);
+            assert_eq!(result.interleaved_time, scaled);
+            assert_eq!(result.ratio_rho, 1.0);
+        }
+    }
+
+    #[test]
+    fn expired_deadline_stops_collection() {
+        let clock = ScriptedClock::stepping(1.0);
+        let timing = TimingConfig::default();
+        let profile = clock.characterize(&timing);
+        let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+
+        let result = PortContentionFingerprint::collect_detailed_with(
+            &timer,
+            &small_config(),
+            Deadline::at(&clock, 0.0),
+        );
+
+        assert_eq!(result.unwrap_err(), SourceError::TimedOut);
+    }
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(PortContentionFingerprint::median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(PortContentionFingerprint::median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(PortContentionFingerprint::median(&mut [7.0]), 7.0);
+        assert_eq!(PortContentionFingerprint::median(&mut []), 0.0);
+    }
+
+    #[test]
+    fn ratio_is_interleaved_over_grouped() {
+        assert_eq!(PortContentionFingerprint::ratio(2.0, 3.0), 1.5);
+        assert_eq!(PortContentionFingerprint::ratio(4.0, 2.0), 0.5);
+        // Tempo agrupado nulo (relógio parado) é neutro
+        assert_eq!(PortContentionFingerprint::ratio(0.0, 3.0), 1.0);
+        assert_eq!(PortContentionFingerprint::ratio(-1.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn category_boundaries() {
+        let category = PortContentionFingerprint::category;
+        assert_eq!(category(1.5), "high_parallelism");
+        assert_eq!(category(1.1501), "high_parallelism");
+        assert_eq!(category(1.15), "no_contention");
+        assert_eq!(category(1.0), "no_contention");
+        assert_eq!(category(0.8501), "no_contention");
+        assert_eq!(category(0.85), "cache_beneficial");
+        assert_eq!(category(0.5), "cache_beneficial");
+    }
+
+    #[test]
+    fn pair_uses_median_of_even_measurements() {
+        let clock = scripted_durations(&[(4.0, 5.0), (1.0, 5.0), (3.0, 3.0), (2.0, 6.0)]);
+        let timing = TimingConfig::default();
+        let profile = single_run_profile();
+        let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+        let config = PortContentionConfig { measurements: 4, ..small_config() };
+
+        let result = PortContentionFingerprint::measure_contention_pair(
+            &timer,
+            "mul_add",
+            PortContentionFingerprint::execute_mul_grouped,
+            PortContentionFingerprint::execute_mul_add_interleaved,
+            &config,
+            Deadline::none(&clock),
+        )
+        .unwrap();
+
+        assert_eq!(clock.remaining(), 0);
+        assert_eq!(result.grouped_time, 2.5);
+        assert_eq!(result.interleaved_time, 5.0);
+        assert_eq!(result.ratio_rho, 2.0);
+    }
+
+    #[test]
+    fn distance_to_itself_is_zero() {
+        let v = vector(&[("mul_add", 1.3), ("div_mul", 0.9)]);
+        assert_eq!(PortContentionFingerprint::distance(&v, &v), Some(0.0));
+    }
+
+    #[test]
+    fn distance_of_known_vectors() {
+        let a = vector(&[("mul_add", 1.0), ("div_mul", 1.0)]);
+        let b = vector(&[("mul_add", std::f64::consts::E), ("div_mul", 1.0)]);
+        // RMS de (1, 0) em ln(ρ)
+        let distance = PortContentionFingerprint::distance(&a, &b).unwrap();
+        assert!((distance - 0.5f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn distance_requires_matching_pairs() {
+        let distance = PortContentionFingerprint::distance;
+        let v = vector(&[("mul_add", 1.3), ("div_mul", 0.9)]);
+
+        assert_eq!(distance(&v, &v[..1]), None);
+        assert_eq!(distance(&v, &vector(&[("div_mul", 0.9), ("mul_add", 1.3)])), None);
+        assert_eq!(distance(&v, &vector(&[("mul_add", 1.3), ("popcnt_or", 0.9)])), None);
+        assert_eq!(distance(&[], &[]), None);
+    }
+
+    #[test]
+    fn bucket_id_absorbs_small_jitter() {
+        let bucket_id = PortContentionFingerprint::bucket_id;
+        let v = vector(&[("mul_add", 1.3), ("div_mul", 0.9)]);
+
+        assert_eq!(bucket_id(&v), bucket_id(&v));
+        assert_eq!(bucket_id(&v), bucket_id(&vector(&[("mul_add", 1.31), ("div_mul", 0.895)])));
+        assert_ne!(bucket_id(&v), bucket_id(&vector(&[("mul_add", 1.6), ("div_mul", 0.9)])));
+    }
+
+    #[test]
+    fn bucket_id_splits_at_grid_boundaries() {
+        // Limitação documentada: ρ vizinhos em lados opostos de uma
+        // fronteira (ln ρ = BUCKET_STEP / 2) ganham IDs diferentes
+        let boundary = (PortContentionFingerprint::BUCKET_STEP / 2.0).exp();
+        let below = vector(&[("mul_add", boundary - 1e-6)]);
+        let above = vector(&[("mul_add", boundary + 1e-6)]);
+
+        assert_ne!(PortContentionFingerprint::bucket_id(&below), PortContentionFingerprint::bucket_id(&above));
+        assert!(PortContentionFingerprint::distance(&below, &above).unwrap() < 1e-5);
+    }
+}
@@ -0,0 +1,390 @@
+//! Esquema versionado do fingerprint
+//!
+//! `FingerprintData` é o formato canônico compartilhado pelo coletor Wasm,
+//! pelo servidor e pelo analisador de entropia. Todo documento carrega
+//! `schema_version`; documentos antigos são convertidos para a versão atual
+//! por `migrate` antes de serem desserializados.
+//!
+//! Histórico de versões:
+//!
+//! - **0**: formato legado do analisador (`sessionId`, `proposalA`,
+//!   `proposalB`, `browserAttributes`), sem campo de versão.
+//! - **1**: `FingerprintData` do coletor antes do versionamento (sem
+//!   `schema_version`).
+//! - **2**: versão atual, com `schema_version`.
+//!
+//! Campos novos marcados com `#[serde(default)]` não incrementam a versão:
+//! documentos anteriores continuam válidos e são lidos com o valor padrão,
+//! e o campo fica opcional no JSON Schema. A versão só muda quando um campo
+//! é renomeado, removido ou muda de tipo ou de significado, o que exige um
+//! passo de migração em `migrate`.
+//!
+//! O JSON Schema da versão atual é gerado a partir dos tipos Rust
+//! (`json_schema`) e publicado em `schema/fingerprint.schema.json`.
+
+use wasm_bindgen::prelude::*;
+use serde_json::{json, Map, Value};
+use crate::utils::sha256_hex;
+use crate::FingerprintData;
+
+/// Versão atual do esquema de `FingerprintData`
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Estado registrado para componentes que não existiam no formato legado
+const LEGACY_MISSING: &str = "absent in legacy record";
+
+/// Versão de um documento; documentos sem `schema_version` são
+/// identificados pelo formato
+pub fn schema_version_of(value: &Value) -> Result<u32, String> {
+    match value.get("schema_version") {
+        Some(version) => version
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| format!("Invalid schema_version: {}", version)),
+        None if is_legacy_v0(value) => Ok(0),
+        None => Ok(1),
+    }
+}
+
+/// Converte um documento de qualquer versão conhecida para a versão atual
+pub fn migrate(mut value: Value) -> Result<FingerprintData, String> {
+    if !value.is_object() {
+        return Err("Fingerprint must be a JSON object".to_string());
+    }
+
+    let mut version = schema_version_of(&value)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Unsupported schema version {} (newest known: {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    while version < SCHEMA_VERSION {
+        value = match version {
+            0 => migrate_v0_to_v1(&value),
+            1 => migrate_v1_to_v2(value),
+            _ => unreachable!(),
+        };
+        version += 1;
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid fingerprint: {}", e))
+}
+
+/// Desserializa um documento JSON de qualquer versão conhecida
+pub fn from_json(json: &str) -> Result<FingerprintData, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    migrate(value)
+}
+
+/// JSON Schema (draft-07) da versão atual de `FingerprintData`
+pub fn json_schema() -> Value {
+    let schema = schemars::schema_for!(FingerprintData);
+    let mut value = serde_json::to_value(schema).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        object.insert("$id".to_string(), json!(format!("fingerprint-v{}.schema.json", SCHEMA_VERSION)));
+    }
+    value
+}
+
+fn is_legacy_v0(value: &Value) -> bool {
+    ["proposalA", "proposalB", "browserAttributes"]
+        .iter()
+        .any(|key| value.get(key).is_some())
+}
+
+/// v0 → v1: mapeia as propostas A/B do formato legado para os campos do
+/// `FingerprintData`. Componentes ausentes no registro são marcados com
+/// estado de erro, para que não sejam comparados nem contem na entropia.
+fn migrate_v0_to_v1(legacy: &Value) -> Value {
+    let proposal_a = legacy.get("proposalA").cloned().unwrap_or(Value::Null);
+    let proposal_b = legacy.get("proposalB").cloned().unwrap_or(Value::Null);
+    let hardware = proposal_b.get("hardwareProfile").cloned().unwrap_or(Value::Null);
+    let browser = legacy.get("browserAttributes").cloned().unwrap_or(Value::Null);
+
+    let canvas = string_at(&proposal_a, &["canvas"]);
+    let webgl = string_at(&proposal_a, &["webgl"]);
+    let audio_value = proposal_a.get("audio").cloned().unwrap_or(Value::Null);
+    let audio = match &audio_value {
+        Value::Null => None,
+        Value::String(hash) => Some(hash.clone()),
+        other => Some(
+            string_at(other, &["hash"]).unwrap_or_else(|| sha256_hex(&other.to_string())),
+        ),
+    };
+    let port_contention = string_at(&proposal_b, &["portContention"]);
+
+    // Os distinguishers guardavam apenas ρ, sem os tempos absolutos
+    let vector: Vec<Value> = proposal_b
+        .get("distinguishers")
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let pair = item.get("pair")?.as_array()?;
+                    let pair: Vec<&str> = pair.iter().filter_map(Value::as_str).collect();
+                    Some(json!({
+                        "instruction_pair": pair.join("_"),
+                        "grouped_time": 0.0,
+                        "interleaved_time": 0.0,
+                        "ratio_rho": item.get("ratio")?.as_f64()?,
+                    }))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut status = Map::new();
+    let mut missing = Vec::new();
+    for (name, present) in [
+        ("canvas", canvas.is_some()),
+        ("webgl", webgl.is_some()),
+        ("audio", audio.is_some()),
+        ("port_contention", port_contention.is_some()),
+    ] {
+        if !present {
+            status.insert(name.to_string(), json!({ "status": "error", "message": LEGACY_MISSING }));
+            missing.push(name);
+        }
+    }
+
+    let concurrency = int_at(&browser, &["hardware_concurrency", "hardwareConcurrency"]);
+    let screen_width = int_at(&browser, &["screen_width", "screenWidth"]);
+    let screen_height = int_at(&browser, &["screen_height", "screenHeight"]);
+
+    json!({
+        "fingerprint_hash": "",
+        "canvas_fingerprint": { "hash": canvas.unwrap_or_default(), "data_url": "" },
+        "webgl_fingerprint": { "hash": webgl.unwrap_or_default(), "vendor": "", "renderer": "" },
+        "audio_fingerprint": {
+            "hash": audio.unwrap_or_default(),
+            "sample_rate": int_at(&audio_value, &["sample_rate", "sampleRate"]),
+        },
+        "hardware_profile": {
+            "cores": int_at(&hardware, &["cores", "hardwareConcurrency"]).max(concurrency),
+            "memory": float_at(&hardware, &["memory", "deviceMemory"]).unwrap_or(0.0),
+            "concurrency": int_at(&hardware, &["concurrency", "hardwareConcurrency"]).max(concurrency),
+            "benchmarks": null,
+            "cpu_benchmark": 0.0,
+            "memory_benchmark": 0.0,
+            "crypto_benchmark": 0.0,
+            "instruction_timing": [],
+            "port_contention_hash": port_contention.unwrap_or_default(),
+            "port_contention_vector": vector,
+        },
+        "browser_info": {
+            "user_agent": string_at(&browser, &["user_agent", "userAgent"]).unwrap_or_default(),
+            "language": string_at(&browser, &["language"]).unwrap_or_default(),
+            "platform": string_at(&browser, &["platform"]).unwrap_or_default(),
+            "hardware_concurrency": concurrency,
+            "device_memory": float_at(&browser, &["device_memory", "deviceMemory"]),
+            "screen_width": screen_width,
+            "screen_height": screen_height,
+            "screen_resolution": string_at(&browser, &["screen_resolution", "screenResolution"])
+                .unwrap_or_else(|| format!("{}x{}", screen_width, screen_height)),
+            "color_depth": int_at(&browser, &["color_depth", "colorDepth"]),
+            "timezone_offset": int_at(&browser, &["timezone_offset", "timezoneOffset"]),
+            "plugins_count": int_at(&browser, &["plugins_count", "pluginsCount"]).max(0),
+        },
+        // O timestamp legado era uma string livre; só é aproveitado se numérico
+        "timestamp": legacy.get("timestamp").and_then(|t| {
+            t.as_f64().or_else(|| t.as_str()?.parse().ok())
+        }).unwrap_or(0.0),
+        "component_status": status,
+        "missing_components": missing,
+    })
+}
+
+/// v1 → v2: apenas passa a registrar a versão
+fn migrate_v1_to_v2(mut value: Value) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), json!(2));
+    }
+    value
+}
+
+fn string_at(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(key)?.as_str())
+        .map(str::to_string)
+}
+
+fn float_at(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| value.get(key)?.as_f64())
+}
+
+fn int_at(value: &Value, keys: &[&str]) -> i64 {
+    keys.iter()
+        .find_map(|key| value.get(key)?.as_i64())
+        .unwrap_or(0)
+}
+
+/// Função exportada para JavaScript - converte um fingerprint serializado de
+/// qualquer versão conhecida para a versão atual
+#[wasm_bindgen]
+pub fn migrate_fingerprint(json: &str) -> Result<String, JsValue> {
+    let data = from_json(json).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_string(&data)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Função exportada para JavaScript - JSON Schema da versão atual
+#[wasm_bindgen]
+pub fn get_fingerprint_schema() -> String {
+    serde_json::to_string_pretty(&json_schema()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fingerprint;
+
+    /// `FingerprintData` como o coletor serializava antes do versionamento
+    const V1_RECORD: &str = r#"{
+        "fingerprint_hash": "f1",
+        "canvas_fingerprint": { "hash": "canvas-hash", "data_url": "data:image/png;base64," },
+        "webgl_fingerprint": { "hash": "webgl-hash", "vendor": "WebKit", "renderer": "WebKit WebGL" },
+        "audio_fingerprint": { "hash": "audio-hash", "sample_rate": 44100 },
+        "hardware_profile": {
+            "cores": 8,
+            "memory": 8.0,
+            "concurrency": 8,
+            "benchmarks": null,
+            "cpu_benchmark": 12.5,
+            "memory_benchmark": 30.0,
+            "crypto_benchmark": 4.0,
+            "instruction_timing": [1.0, 2.0],
+            "port_contention_hash": "pc-hash"
+        },
+        "browser_info": {
+            "user_agent": "Mozilla/5.0 Firefox/120.0",
+            "language": "pt-BR",
+            "platform": "Linux x86_64",
+            "hardware_concurrency": 8,
+            "device_memory": null,
+            "screen_width": 1920,
+            "screen_height": 1080,
+            "screen_resolution": "1920x1080",
+            "color_depth": 24,
+            "timezone_offset": 180,
+            "plugins_count": 3
+        },
+        "timestamp": 1700000000000.0
+    }"#;
+
+    /// Registro do analisador legado, com as propostas A e B
+    const V0_RECORD: &str = r#"{
+        "sessionId": "abc",
+        "timestamp": "1700000000000",
+        "proposalA": {
+            "canvas": "canvas-hash",
+            "webgl": "webgl-hash",
+            "audio": { "hash": "audio-hash", "sampleRate": 48000 }
+        },
+        "proposalB": {
+            "portContention": "pc-hash",
+            "hardwareProfile": { "hardwareConcurrency": 4, "deviceMemory": 16 },
+            "distinguishers": [
+                { "pair": ["mul", "add"], "ratio": 1.3 },
+                { "pair": ["div", "mul"], "ratio": 0.9 }
+            ]
+        },
+        "browserAttributes": {
+            "userAgent": "Mozilla/5.0 Chrome/120.0",
+            "hardwareConcurrency": 8,
+            "screenWidth": 1280,
+            "screenHeight": 720
+        }
+    }"#;
+
+    fn parse(json: &str) -> Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn version_is_detected_from_shape() {
+        assert_eq!(schema_version_of(&parse(V0_RECORD)), Ok(0));
+        assert_eq!(schema_version_of(&parse(V1_RECORD)), Ok(1));
+        assert_eq!(schema_version_of(&json!({ "schema_version": 2 })), Ok(2));
+        assert!(schema_version_of(&json!({ "schema_version": "two" })).is_err());
+    }
+
+    #[test]
+    fn v1_record_migrates_to_current_version() {
+        let data = from_json(V1_RECORD).unwrap();
+
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+        assert_eq!(data.fingerprint_hash, "f1");
+        assert_eq!(data.canvas_fingerprint.hash, "canvas-hash");
+        assert_eq!(data.webgl_fingerprint.vendor, "WebKit");
+        assert_eq!(data.audio_fingerprint.sample_rate, 44100);
+        assert_eq!(data.hardware_profile.cpu_benchmark, 12.5);
+        assert_eq!(data.hardware_profile.port_contention_hash, "pc-hash");
+        assert_eq!(data.browser_info.plugins_count, 3);
+        // Campos adicionados depois ficam com o valor padrão
+        assert!(data.font_fingerprint.hash.is_empty());
+        assert!(data.webgpu_fingerprint.hash.is_empty());
+        assert!(data.hardware_profile.port_contention_vector.is_empty());
+        assert!(data.component_status.is_empty());
+    }
+
+    #[test]
+    fn v0_record_migrates_to_current_version() {
+        let data = from_json(V0_RECORD).unwrap();
+
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+        assert_eq!(data.canvas_fingerprint.hash, "canvas-hash");
+        assert_eq!(data.webgl_fingerprint.hash, "webgl-hash");
+        assert_eq!(data.audio_fingerprint.hash, "audio-hash");
+        assert_eq!(data.audio_fingerprint.sample_rate, 48000);
+        assert_eq!(data.timestamp, 1_700_000_000_000.0);
+
+        let profile = &data.hardware_profile;
+        assert_eq!(profile.cores, 8);
+        assert_eq!(profile.memory, 16.0);
+        assert_eq!(profile.port_contention_hash, "pc-hash");
+        let pairs: Vec<(&str, f64)> = profile
+            .port_contention_vector
+            .iter()
+            .map(|r| (r.instruction_pair.as_str(), r.ratio_rho))
+            .collect();
+        assert_eq!(pairs, vec![("mul_add", 1.3), ("div_mul", 0.9)]);
+
+        assert_eq!(data.browser_info.user_agent, "Mozilla/5.0 Chrome/120.0");
+        assert_eq!(data.browser_info.screen_resolution, "1280x720");
+        assert!(data.missing_components.is_empty());
+    }
+
+    #[test]
+    fn v0_record_marks_absent_components() {
+        let data = from_json(r#"{ "proposalA": { "canvas": "canvas-hash" } }"#).unwrap();
+
+        assert!(data.component_ok("canvas"));
+        assert!(!data.component_ok("webgl"));
+        assert_eq!(data.missing_components, vec!["webgl", "audio", "port_contention"]);
+    }
+
+    #[test]
+    fn current_record_round_trips() {
+        let data = fingerprint();
+        let json = serde_json::to_string(&data).unwrap();
+        let migrated = from_json(&json).unwrap();
+
+        assert_eq!(serde_json::to_value(&migrated).unwrap(), serde_json::to_value(&data).unwrap());
+    }
+
+    #[test]
+    fn future_version_is_rejected() {
+        let error = migrate(json!({ "schema_version": SCHEMA_VERSION + 1 })).unwrap_err();
+        assert!(error.starts_with("Unsupported schema version"), "{}", error);
+        assert!(migrate(json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn committed_schema_matches_types() {
+        let committed: Value = serde_json::from_str(include_str!("../schema/fingerprint.schema.json")).unwrap();
+        assert_eq!(committed, json_schema());
+    }
+}
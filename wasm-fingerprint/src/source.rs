@@ -0,0 +1,901 @@
+//! Abstração dos vetores de fingerprinting
+//!
+//! Cada vetor (Canvas, fontes, WebGL, WebGPU, Audio, benchmarks, cache, port contention) implementa
+//! `FingerprintSource` e é registrado em um `SourceRegistry`, que o
+//! `FingerprintCollector` percorre. Isso permite habilitar/desabilitar vetores,
+//! adicionar vetores próprios e executar a lógica de coleta nativamente com
+//! fontes falsas.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::{
+    AudioFingerprintData, BenchmarkResults, CacheProfile, CanvasFingerprintData, CollectorConfig,
+    FingerprintData, FontFingerprintData, HardwareProfile, TimerProfile, WebGLFingerprintData,
+    WebGPUFingerprintData,
+};
+use crate::utils::sleep;
+use crate::port_contention::{ContentionResult, PortContentionFingerprint};
+use crate::timing::Clock;
+
+/// Futuro retornado por `FingerprintSource::collect`
+///
+/// Não exige `Send`, já que no navegador tudo roda na thread principal.
+pub type SourceFuture<'a> = Pin<Box<dyn Future<Output = Result<SourceOutput, SourceError>> + 'a>>;
+
+/// Timer que resolve após o tempo pedido; usado pelo registro para
+/// interromper vetores assíncronos
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Timer do navegador (`setTimeout`) para `SourceRegistry::collect_into`
+pub fn browser_sleep(ms: f64) -> SleepFuture {
+    Box::pin(async move {
+        let _ = sleep(ms).await;
+    })
+}
+
+/// Classe de estabilidade de um vetor entre coletas no mesmo dispositivo
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Stability {
+    /// Determinístico para o mesmo hardware/navegador (Canvas, WebGL, Audio)
+    Stable,
+    /// Depende de medições de tempo e varia entre execuções (benchmarks)
+    #[default]
+    Volatile,
+}
+
+/// Estado da coleta de um componente, serializado junto do fingerprint
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "status", content = "message", rename_all = "snake_case")]
+pub enum ComponentStatus {
+    #[default]
+    Ok,
+    /// A API não existe neste navegador (ex.: WebGL desabilitado)
+    Unsupported,
+    /// A API existe mas o navegador recusou o acesso (ex.: SecurityError)
+    Blocked,
+    TimedOut,
+    /// O vetor executou mas produziu saída vazia (ex.: buffer de áudio
+    /// todo em zero), que não deve ser usada como fingerprint
+    Silent,
+    Error(String),
+    /// Vetor desabilitado no registro
+    Disabled,
+}
+
+impl ComponentStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ComponentStatus::Ok)
+    }
+
+    /// Rótulo curto usado no hash composto para componentes ausentes
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComponentStatus::Ok => "ok",
+            ComponentStatus::Unsupported => "unsupported",
+            ComponentStatus::Blocked => "blocked",
+            ComponentStatus::TimedOut => "timed_out",
+            ComponentStatus::Silent => "silent",
+            ComponentStatus::Error(_) => "error",
+            ComponentStatus::Disabled => "disabled",
+        }
+    }
+}
+
+/// Falha de um vetor individual; não interrompe a coleta dos demais
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceError {
+    Unsupported,
+    Blocked,
+    TimedOut,
+    Silent,
+    Error(String),
+}
+
+impl From<JsValue> for SourceError {
+    fn from(value: JsValue) -> Self {
+        // DOMException/Error expõem o tipo da falha em `name`
+        let name = js_sys::Reflect::get(&value, &JsValue::from_str("name"))
+            .ok()
+            .and_then(|n| n.as_string())
+            .unwrap_or_default();
+
+        match name.as_str() {
+            "SecurityError" | "NotAllowedError" => SourceError::Blocked,
+            "NotSupportedError" => SourceError::Unsupported,
+            _ => {
+                let message = value.as_string()
+                    .or_else(|| {
+                        js_sys::Reflect::get(&value, &JsValue::from_str("message"))
+                            .ok()
+                            .and_then(|m| m.as_string())
+                    })
+                    .unwrap_or_else(|| format!("{:?}", value));
+                SourceError::Error(message)
+            }
+        }
+    }
+}
+
+impl From<SourceError> for JsValue {
+    fn from(error: SourceError) -> Self {
+        match error {
+            SourceError::Error(message) => JsValue::from_str(&message),
+            other => JsValue::from_str(ComponentStatus::from(other).label()),
+        }
+    }
+}
+
+impl From<SourceError> for ComponentStatus {
+    fn from(error: SourceError) -> Self {
+        match error {
+            SourceError::Unsupported => ComponentStatus::Unsupported,
+            SourceError::Blocked => ComponentStatus::Blocked,
+            SourceError::TimedOut => ComponentStatus::TimedOut,
+            SourceError::Silent => ComponentStatus::Silent,
+            SourceError::Error(message) => ComponentStatus::Error(message),
+        }
+    }
+}
+
+/// Prazo para um vetor, em milissegundos do relógio do orçamento
+///
+/// Vetores assíncronos são interrompidos pelo registro ao estourar o prazo;
+/// vetores síncronos (benchmarks) devem consultar `check` entre as etapas,
+/// já que não podem ser interrompidos de fora. Os benchmarks medem o tempo
+/// em `clock`, que pode ser diferente do relógio do prazo.
+#[derive(Clone, Copy)]
+pub struct Deadline<'a> {
+    budget: &'a dyn Clock,
+    benchmark: &'a dyn Clock,
+    at: Option<f64>,
+}
+
+impl<'a> Deadline<'a> {
+    /// Sem prazo
+    pub fn none(clock: &'a dyn Clock) -> Self {
+        Deadline { budget: clock, benchmark: clock, at: None }
+    }
+
+    /// Prazo em `instant_ms`, na escala de `clock`
+    pub fn at(clock: &'a dyn Clock, instant_ms: f64) -> Self {
+        Deadline { budget: clock, benchmark: clock, at: Some(instant_ms) }
+    }
+
+    /// Mesmo prazo, com outro relógio para os benchmarks
+    pub fn with_benchmark_clock(self, clock: &'a dyn Clock) -> Self {
+        Deadline { benchmark: clock, ..self }
+    }
+
+    /// Relógio dos benchmarks
+    pub fn clock(&self) -> &'a dyn Clock {
+        self.benchmark
+    }
+
+    pub fn expired(&self) -> bool {
+        self.at.is_some_and(|deadline| self.budget.now() >= deadline)
+    }
+
+    /// Retorna `SourceError::TimedOut` se o prazo já passou
+    pub fn check(&self) -> Result<(), SourceError> {
+        if self.expired() {
+            Err(SourceError::TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Relógios de uma coleta
+///
+/// Os prazos são medidos em `budget`, que precisa estar na mesma escala do
+/// `sleep` passado a `SourceRegistry::collect_into` (no navegador,
+/// `performance.now()` e `setTimeout`). O orçamento conta a partir de
+/// `started_at`, o que permite cobrar dele o preparo anterior à coleta (ex.:
+/// iniciar o contador do worker). `benchmark` é entregue aos vetores por
+/// `Deadline::clock`.
+#[derive(Clone, Copy)]
+pub struct CollectionClocks<'a> {
+    pub budget: &'a dyn Clock,
+    pub started_at: f64,
+    pub benchmark: &'a dyn Clock,
+}
+
+impl<'a> CollectionClocks<'a> {
+    /// Um único relógio para prazos e benchmarks, com o orçamento contando
+    /// a partir de agora
+    pub fn new(clock: &'a dyn Clock) -> Self {
+        CollectionClocks { budget: clock, started_at: clock.now(), benchmark: clock }
+    }
+}
+
+impl std::fmt::Debug for Deadline<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Deadline")
+            .field("budget", &self.budget.name())
+            .field("benchmark", &self.benchmark.name())
+            .field("at", &self.at)
+            .finish()
+    }
+}
+
+/// Dados de um vetor registrado pelo usuário
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct CustomComponentData {
+    pub hash: String,
+    pub value: serde_json::Value,
+    /// Preenchido pelo registro a partir de `FingerprintSource::stability`
+    #[serde(default)]
+    pub stability: Stability,
+}
+
+/// Resultado de um vetor, aplicado ao `FingerprintData` pelo registro
+#[derive(Debug, Clone)]
+pub enum SourceOutput {
+    Canvas(CanvasFingerprintData),
+    Fonts(FontFingerprintData),
+    WebGL(WebGLFingerprintData),
+    WebGPU(WebGPUFingerprintData),
+    Audio(AudioFingerprintData),
+    Hardware(HardwareProfile),
+    Cache(CacheProfile),
+    /// Vetor ρ e o relógio usado para medi-lo
+    PortContention(Vec<ContentionResult>, TimerProfile),
+    Custom(CustomComponentData),
+}
+
+/// Parte de `FingerprintData` em que um vetor grava o seu resultado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSlot {
+    Canvas,
+    Fonts,
+    WebGL,
+    WebGPU,
+    Audio,
+    Hardware,
+    Cache,
+    PortContention,
+    /// `custom_components`, sob o nome do vetor
+    Custom,
+}
+
+impl SourceOutput {
+    /// Parte do fingerprint em que este resultado é gravado
+    pub fn slot(&self) -> OutputSlot {
+        match self {
+            SourceOutput::Canvas(_) => OutputSlot::Canvas,
+            SourceOutput::Fonts(_) => OutputSlot::Fonts,
+            SourceOutput::WebGL(_) => OutputSlot::WebGL,
+            SourceOutput::WebGPU(_) => OutputSlot::WebGPU,
+            SourceOutput::Audio(_) => OutputSlot::Audio,
+            SourceOutput::Hardware(_) => OutputSlot::Hardware,
+            SourceOutput::Cache(_) => OutputSlot::Cache,
+            SourceOutput::PortContention(..) => OutputSlot::PortContention,
+            SourceOutput::Custom(_) => OutputSlot::Custom,
+        }
+    }
+
+    /// Apaga o que o vetor `name` gravou em `slot` numa coleta anterior.
+    /// Campos que não vêm do vetor (dimensões do canvas, taxa de amostragem
+    /// pedida, contagens do navegador) são mantidos.
+    pub fn clear(slot: OutputSlot, name: &str, data: &mut FingerprintData) {
+        match slot {
+            OutputSlot::Canvas => {
+                let canvas = &data.canvas_fingerprint;
+                data.canvas_fingerprint = CanvasFingerprintData {
+                    width: canvas.width,
+                    height: canvas.height,
+                    ..Default::default()
+                };
+            }
+            OutputSlot::Fonts => data.font_fingerprint = FontFingerprintData::default(),
+            OutputSlot::WebGL => data.webgl_fingerprint = WebGLFingerprintData::default(),
+            OutputSlot::WebGPU => data.webgpu_fingerprint = WebGPUFingerprintData::default(),
+            OutputSlot::Audio => {
+                data.audio_fingerprint = AudioFingerprintData {
+                    sample_rate: data.audio_fingerprint.sample_rate,
+                    ..Default::default()
+                };
+            }
+            OutputSlot::Hardware => {
+                let profile = &mut data.hardware_profile;
+                profile.cpu_benchmark = 0.0;
+                profile.memory_benchmark = 0.0;
+                profile.crypto_benchmark = 0.0;
+                profile.instruction_timing.clear();
+                profile.timer = Default::default();
+                profile.benchmarks = None;
+            }
+            OutputSlot::Cache => data.hardware_profile.cache = CacheProfile::default(),
+            OutputSlot::PortContention => {
+                let profile = &mut data.hardware_profile;
+                profile.port_contention_hash.clear();
+                profile.port_contention_bucket.clear();
+                profile.port_contention_vector.clear();
+                profile.port_contention_timer = Default::default();
+            }
+            OutputSlot::Custom => {
+                data.custom_components.remove(name);
+            }
+        }
+    }
+
+    /// Grava o resultado no campo correspondente do fingerprint
+    pub fn apply(self, source: &dyn FingerprintSource, data: &mut FingerprintData) {
+        match self {
+            SourceOutput::Canvas(canvas) => data.canvas_fingerprint = canvas,
+            SourceOutput::Fonts(fonts) => data.font_fingerprint = fonts,
+            SourceOutput::WebGL(webgl) => data.webgl_fingerprint = webgl,
+            SourceOutput::WebGPU(webgpu) => data.webgpu_fingerprint = webgpu,
+            SourceOutput::Audio(audio) => data.audio_fingerprint = audio,
+            SourceOutput::Hardware(hw_profile) => {
+                let profile = &mut data.hardware_profile;
+                profile.cpu_benchmark = hw_profile.cpu_benchmark;
+                profile.memory_benchmark = hw_profile.memory_benchmark;
+                profile.crypto_benchmark = hw_profile.crypto_benchmark;
+                profile.instruction_timing = hw_profile.instruction_timing;
+                profile.timer = hw_profile.timer;
+                profile.benchmarks = Some(BenchmarkResults {
+                    math_ops: hw_profile.cpu_benchmark,
+                    string_ops: hw_profile.memory_benchmark,
+                    array_ops: hw_profile.crypto_benchmark,
+                    crypto_ops: hw_profile.cpu_benchmark * 1.2, // Simulated
+                });
+            }
+            SourceOutput::Cache(cache) => data.hardware_profile.cache = cache,
+            SourceOutput::PortContention(results, timer) => {
+                let profile = &mut data.hardware_profile;
+                profile.port_contention_hash = PortContentionFingerprint::generate_fingerprint(&results);
+                profile.port_contention_bucket = PortContentionFingerprint::bucket_id(&results);
+                profile.port_contention_vector = results;
+                profile.port_contention_timer = timer;
+            }
+            SourceOutput::Custom(mut custom) => {
+                custom.stability = source.stability();
+                data.custom_components.insert(source.name().to_string(), custom);
+            }
+        }
+    }
+}
+
+/// Um vetor de fingerprinting
+pub trait FingerprintSource {
+    /// Nome único do vetor no registro
+    fn name(&self) -> &str;
+
+    fn stability(&self) -> Stability;
+
+    /// Parte do fingerprint que o vetor grava e que é apagada quando ele
+    /// falha. O padrão é `custom_components[name]`, então um vetor próprio
+    /// com o nome de um vetor padrão não apaga os dados dele; quem devolve
+    /// outra variante de `SourceOutput` deve sobrescrever.
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::Custom
+    }
+
+    /// Coleta o vetor; `deadline` é o prazo cooperativo para vetores síncronos
+    fn collect<'a>(&'a self, deadline: Deadline<'a>) -> SourceFuture<'a>;
+}
+
+struct RegisteredSource {
+    source: Box<dyn FingerprintSource>,
+    enabled: bool,
+}
+
+/// Lista ordenada de vetores que o coletor percorre
+#[derive(Default)]
+pub struct SourceRegistry {
+    sources: Vec<RegisteredSource>,
+}
+
+impl SourceRegistry {
+    pub fn new() -> Self {
+        SourceRegistry::default()
+    }
+
+    /// Registro com os vetores padrão, na ordem histórica de coleta
+    pub fn with_default_sources(config: &CollectorConfig) -> Self {
+        use crate::audio_fingerprint::AudioFingerprint;
+        use crate::cache_benchmark::CacheBenchmark;
+        use crate::canvas_fingerprint::CanvasFingerprint;
+        use crate::font_fingerprint::FontFingerprint;
+        use crate::hardware_benchmarks::HardwareBenchmarks;
+        use crate::webgl_fingerprint::WebGLFingerprint;
+        use crate::webgpu_fingerprint::WebGPUFingerprint;
+
+        let mut registry = SourceRegistry::new();
+        // Proposta A: fingerprints tradicionais baseados em APIs
+        registry.register(Box::new(CanvasFingerprint::new(config.canvas.clone())));
+        registry.register(Box::new(FontFingerprint::new(config.fonts.clone())));
+        registry.register(Box::new(WebGLFingerprint));
+        registry.register(Box::new(WebGPUFingerprint::new(config.webgpu.clone())));
+        registry.register(Box::new(AudioFingerprint::new(config.audio.clone())));
+        // Proposta B: microbenchmarks de hardware
+        registry.register(Box::new(HardwareBenchmarks::with_config(
+            config.benchmarks.clone(),
+            config.timing.clone(),
+        )));
+        registry.register(Box::new(CacheBenchmark::new(config.cache.clone(), config.timing.clone())));
+        registry.register(Box::new(PortContentionFingerprint::new(
+            config.port_contention.clone(),
+            config.timing.clone(),
+        )));
+        registry
+    }
+
+    /// Registra um vetor; um vetor com o mesmo nome é substituído no lugar
+    pub fn register(&mut self, source: Box<dyn FingerprintSource>) {
+        let entry = RegisteredSource { source, enabled: true };
+        match self.sources.iter_mut().find(|s| s.source.name() == entry.source.name()) {
+            Some(existing) => *existing = entry,
+            None => self.sources.push(entry),
+        }
+    }
+
+    /// Habilita ou desabilita um vetor; retorna `false` se o nome não existir
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.sources.iter_mut().find(|s| s.source.name() == name) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.sources.iter().map(|s| s.source.name().to_string()).collect()
+    }
+
+    pub fn stability_of(&self, name: &str) -> Option<Stability> {
+        self.sources.iter()
+            .find(|s| s.source.name() == name)
+            .map(|s| s.source.stability())
+    }
+
+    /// Executa os vetores habilitados em ordem e grava os resultados em `data`
+    ///
+    /// A falha de um vetor não interrompe os demais: o estado de cada um é
+    /// registrado em `data.component_status`. Cada vetor tem até
+    /// `config.source_timeout_ms`, e vetores que não couberem no
+    /// `config.total_budget_ms` restante são marcados como `TimedOut` sem
+    /// executar. `on_start` é chamado com o nome de cada vetor antes da coleta.
+    /// Vetores sem resultado têm os dados de coletas anteriores apagados
+    /// (ver `SourceOutput::clear`).
+    ///
+    /// Os prazos são medidos em `clocks.budget` e os vetores assíncronos são
+    /// interrompidos por `sleep`; no navegador, `performance.now()` e
+    /// `browser_sleep`.
+    pub async fn collect_into<F>(
+        &self,
+        data: &mut FingerprintData,
+        config: &CollectorConfig,
+        clocks: CollectionClocks<'_>,
+        sleep: &dyn Fn(f64) -> SleepFuture,
+        mut on_start: F,
+    ) where
+        F: FnMut(&str),
+    {
+        data.component_status.clear();
+        let clock = clocks.budget;
+        let budget_end = clocks.started_at + config.total_budget_ms;
+
+        for entry in &self.sources {
+            let name = entry.source.name();
+            let status = if !entry.enabled {
+                ComponentStatus::Disabled
+            } else {
+                let now = clock.now();
+                if now >= budget_end {
+                    ComponentStatus::TimedOut
+                } else {
+                    on_start(name);
+
+                    let deadline = (now + config.source_timeout_ms).min(budget_end);
+                    let collected = with_timeout(
+                        entry.source.collect(
+                            Deadline::at(clock, deadline).with_benchmark_clock(clocks.benchmark),
+                        ),
+                        sleep,
+                        deadline - now,
+                    )
+                    .await;
+                    match collected {
+                        Ok(output) => {
+                            output.apply(entry.source.as_ref(), data);
+                            ComponentStatus::Ok
+                        }
+                        Err(error) => error.into(),
+                    }
+                }
+            };
+
+            // Sem isso o resultado de uma coleta anterior continuaria no
+            // fingerprint e nos hashes
+            if !status.is_ok() {
+                SourceOutput::clear(entry.source.slot(), name, data);
+            }
+            data.component_status.insert(name.to_string(), status);
+        }
+
+        data.missing_components = data.component_status.iter()
+            .filter(|(_, status)| !status.is_ok())
+            .map(|(name, _)| name.clone())
+            .collect();
+    }
+}
+
+/// Corre o futuro de um vetor contra um timer; o timer só é criado se o
+/// vetor não terminar no primeiro poll (vetores síncronos não pagam o custo)
+async fn with_timeout(
+    mut future: SourceFuture<'_>,
+    sleep: &dyn Fn(f64) -> SleepFuture,
+    timeout_ms: f64,
+) -> Result<SourceOutput, SourceError> {
+    let mut timer = None;
+
+    std::future::poll_fn(move |cx| {
+        if let Poll::Ready(result) = future.as_mut().poll(cx) {
+            return Poll::Ready(result);
+        }
+
+        let timer = timer.get_or_insert_with(|| sleep(timeout_ms));
+        match timer.as_mut().poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(SourceError::TimedOut)),
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use crate::test_support::{block_on, fingerprint};
+    use crate::timing::{InstantClock, ScriptedClock, INSTANT_CLOCK};
+
+    /// Vetor falso que devolve um resultado fixo
+    struct FakeSource {
+        name: &'static str,
+        slot: OutputSlot,
+        result: Result<SourceOutput, SourceError>,
+    }
+
+    impl FingerprintSource for FakeSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn stability(&self) -> Stability {
+            Stability::Stable
+        }
+
+        fn slot(&self) -> OutputSlot {
+            self.slot
+        }
+
+        fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+            let result = self.result.clone();
+            Box::pin(async move { result })
+        }
+    }
+
+    /// Vetor que nunca termina
+    struct HangingSource;
+
+    impl FingerprintSource for HangingSource {
+        fn name(&self) -> &str {
+            "hanging"
+        }
+
+        fn stability(&self) -> Stability {
+            Stability::Volatile
+        }
+
+        fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+            Box::pin(std::future::pending())
+        }
+    }
+
+    /// Vetor que devolve o nome do relógio recebido para os benchmarks
+    struct ClockSource;
+
+    impl FingerprintSource for ClockSource {
+        fn name(&self) -> &str {
+            "clock"
+        }
+
+        fn stability(&self) -> Stability {
+            Stability::Volatile
+        }
+
+        fn collect<'a>(&'a self, deadline: Deadline<'a>) -> SourceFuture<'a> {
+            let name = deadline.clock().name().to_string();
+            Box::pin(async move { Ok(custom(&name)) })
+        }
+    }
+
+    fn custom(hash: &str) -> SourceOutput {
+        SourceOutput::Custom(CustomComponentData {
+            hash: hash.to_string(),
+            value: serde_json::Value::Null,
+            stability: Stability::Volatile,
+        })
+    }
+
+    /// Vetor falso gravando na parte do fingerprint do seu resultado
+    fn fake(name: &'static str, result: Result<SourceOutput, SourceError>) -> Box<dyn FingerprintSource> {
+        let slot = result.as_ref().map_or(OutputSlot::Custom, SourceOutput::slot);
+        Box::new(FakeSource { name, slot, result })
+    }
+
+    /// Vetor falso que falha, gravando em `slot` quando funciona
+    fn failing(name: &'static str, slot: OutputSlot, error: SourceError) -> Box<dyn FingerprintSource> {
+        Box::new(FakeSource { name, slot, result: Err(error) })
+    }
+
+    fn immediate(_ms: f64) -> SleepFuture {
+        Box::pin(std::future::ready(()))
+    }
+
+    fn never(_ms: f64) -> SleepFuture {
+        Box::pin(std::future::pending())
+    }
+
+    /// Executa a coleta e retorna os vetores iniciados, em ordem
+    fn collect(
+        registry: &SourceRegistry,
+        data: &mut FingerprintData,
+        clock: &dyn Clock,
+        sleep: &dyn Fn(f64) -> SleepFuture,
+    ) -> Vec<String> {
+        let started = RefCell::new(Vec::new());
+        let clocks = CollectionClocks::new(clock);
+        block_on(registry.collect_into(data, &CollectorConfig::default(), clocks, sleep, |name| {
+            started.borrow_mut().push(name.to_string())
+        }));
+        started.into_inner()
+    }
+
+    #[test]
+    fn failure_does_not_stop_other_sources() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("first", Err(SourceError::Unsupported)));
+        registry.register(fake("second", Ok(custom("abc"))));
+
+        let mut data = fingerprint();
+        let clock = ScriptedClock::stepping(1.0);
+        collect(&registry, &mut data, &clock, &never);
+
+        assert_eq!(data.component_status["first"], ComponentStatus::Unsupported);
+        assert_eq!(data.component_status["second"], ComponentStatus::Ok);
+        assert_eq!(data.custom_components["second"].hash, "abc");
+        assert_eq!(data.custom_components["second"].stability, Stability::Stable);
+        assert_eq!(data.missing_components, vec!["first".to_string()]);
+    }
+
+    #[test]
+    fn disabled_source_is_not_collected() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("custom", Ok(custom("abc"))));
+        assert!(registry.set_enabled("custom", false));
+        assert!(!registry.set_enabled("unknown", false));
+
+        let mut data = fingerprint();
+        let started = collect(&registry, &mut data, &ScriptedClock::stepping(1.0), &never);
+
+        assert!(started.is_empty());
+        assert_eq!(data.component_status["custom"], ComponentStatus::Disabled);
+        assert!(!data.custom_components.contains_key("custom"));
+    }
+
+    #[test]
+    fn pending_source_times_out() {
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(HangingSource));
+        registry.register(fake("after", Ok(custom("abc"))));
+
+        let mut data = fingerprint();
+        collect(&registry, &mut data, &ScriptedClock::stepping(1.0), &immediate);
+
+        assert_eq!(data.component_status["hanging"], ComponentStatus::TimedOut);
+        assert_eq!(data.component_status["after"], ComponentStatus::Ok);
+    }
+
+    #[test]
+    fn sources_past_the_budget_are_skipped() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("first", Ok(custom("a"))));
+        registry.register(fake("second", Ok(custom("b"))));
+
+        let budget = CollectorConfig::default().total_budget_ms;
+        // Início da coleta, início do primeiro vetor e, depois dele, o
+        // orçamento já estourado
+        let clock = ScriptedClock::new([0.0, 0.0, budget + 1.0], 0.0);
+        let mut data = fingerprint();
+        let started = collect(&registry, &mut data, &clock, &never);
+
+        assert_eq!(started, vec!["first".to_string()]);
+        assert_eq!(data.component_status["first"], ComponentStatus::Ok);
+        assert_eq!(data.component_status["second"], ComponentStatus::TimedOut);
+    }
+
+    #[test]
+    fn deadline_follows_its_clock() {
+        let clock = ScriptedClock::new([5.0, 10.0], 0.0);
+        let deadline = Deadline::at(&clock, 10.0);
+        assert_eq!(deadline.check(), Ok(()));
+        assert_eq!(deadline.check(), Err(SourceError::TimedOut));
+
+        let clock = ScriptedClock::stepping(1000.0);
+        assert!(!Deadline::none(&clock).expired());
+    }
+
+    #[test]
+    fn setup_before_collection_counts_against_the_budget() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("first", Ok(custom("a"))));
+
+        let budget = CollectorConfig::default().total_budget_ms;
+        // O relógio dos benchmarks levou o orçamento inteiro para ficar pronto
+        let clock = ScriptedClock::new([budget + 1.0], 0.0);
+        let clocks = CollectionClocks { budget: &clock, started_at: 0.0, benchmark: &clock };
+        let mut data = fingerprint();
+        block_on(registry.collect_into(&mut data, &CollectorConfig::default(), clocks, &never, |_| {}));
+
+        assert_eq!(data.component_status["first"], ComponentStatus::TimedOut);
+    }
+
+    #[test]
+    fn sources_receive_the_benchmark_clock() {
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(ClockSource));
+
+        let budget = ScriptedClock::stepping(1.0);
+        let benchmark = InstantClock::new();
+        let clocks = CollectionClocks { budget: &budget, started_at: 0.0, benchmark: &benchmark };
+        let mut data = fingerprint();
+        block_on(registry.collect_into(&mut data, &CollectorConfig::default(), clocks, &never, |_| {}));
+
+        assert_eq!(data.custom_components["clock"].hash, INSTANT_CLOCK);
+    }
+
+    #[test]
+    fn deadline_is_measured_on_the_budget_clock() {
+        let budget = ScriptedClock::new([5.0, 10.0], 0.0);
+        let benchmark = ScriptedClock::new([42.0], 0.0);
+        let deadline = Deadline::at(&budget, 10.0).with_benchmark_clock(&benchmark);
+
+        assert_eq!(deadline.check(), Ok(()));
+        assert_eq!(deadline.check(), Err(SourceError::TimedOut));
+        assert_eq!(benchmark.reads(), 0);
+        assert_eq!(deadline.clock().now(), 42.0);
+    }
+
+    #[test]
+    fn failed_source_clears_previous_output() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("custom", Ok(custom("abc"))));
+        registry.register(fake("canvas", Ok(SourceOutput::Canvas(fingerprint().canvas_fingerprint))));
+
+        let mut data = fingerprint();
+        let clock = ScriptedClock::stepping(1.0);
+        collect(&registry, &mut data, &clock, &never);
+        assert_eq!(data.custom_components["custom"].hash, "abc");
+
+        registry.register(fake("custom", Err(SourceError::Blocked)));
+        registry.register(failing("canvas", OutputSlot::Canvas, SourceError::Error("lost".to_string())));
+        collect(&registry, &mut data, &clock, &never);
+
+        assert!(!data.custom_components.contains_key("custom"));
+        assert!(data.canvas_fingerprint.hash.is_empty());
+        assert_eq!(data.canvas_fingerprint.width, 400);
+        // Vetores que não rodaram continuam intactos
+        assert_eq!(data.webgl_fingerprint.hash, "webgl-hash");
+    }
+
+    #[test]
+    fn custom_source_with_builtin_name_keeps_builtin_data() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("canvas", Err(SourceError::Blocked)));
+        registry.register(fake("hardware", Err(SourceError::TimedOut)));
+
+        let mut data = fingerprint();
+        data.custom_components.insert("canvas".to_string(), CustomComponentData::default());
+        collect(&registry, &mut data, &ScriptedClock::stepping(1.0), &never);
+
+        assert!(!data.custom_components.contains_key("canvas"));
+        assert_eq!(data.canvas_fingerprint.hash, "canvas-hash");
+        assert_eq!(data.hardware_profile.cpu_benchmark, 12.0);
+        assert!(data.hardware_profile.benchmarks.is_some());
+    }
+
+    #[test]
+    fn default_sources_write_distinct_slots() {
+        let registry = SourceRegistry::with_default_sources(&CollectorConfig::default());
+        let slots: Vec<OutputSlot> = registry.sources.iter().map(|s| s.source.slot()).collect();
+
+        assert_eq!(
+            slots,
+            vec![
+                OutputSlot::Canvas,
+                OutputSlot::Fonts,
+                OutputSlot::WebGL,
+                OutputSlot::WebGPU,
+                OutputSlot::Audio,
+                OutputSlot::Hardware,
+                OutputSlot::Cache,
+                OutputSlot::PortContention,
+            ]
+        );
+    }
+
+    #[test]
+    fn skipped_sources_are_cleared_too() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("audio", Ok(SourceOutput::Audio(fingerprint().audio_fingerprint))));
+        registry.set_enabled("audio", false);
+
+        let mut data = fingerprint();
+        collect(&registry, &mut data, &ScriptedClock::stepping(1.0), &never);
+
+        assert!(data.audio_fingerprint.hash.is_empty());
+        assert_eq!(data.audio_fingerprint.sample_rate, 44100);
+    }
+
+    #[test]
+    fn register_replaces_source_with_the_same_name() {
+        let mut registry = SourceRegistry::new();
+        registry.register(fake("first", Ok(custom("a"))));
+        registry.register(Box::new(HangingSource));
+        registry.register(fake("first", Err(SourceError::Blocked)));
+
+        assert_eq!(registry.names(), vec!["first".to_string(), "hanging".to_string()]);
+        assert_eq!(registry.stability_of("first"), Some(Stability::Stable));
+        assert_eq!(registry.stability_of("hanging"), Some(Stability::Volatile));
+        assert_eq!(registry.stability_of("unknown"), None);
+    }
+
+    #[test]
+    fn built_in_output_is_written_to_its_field() {
+        let mut data = fingerprint();
+        SourceOutput::Canvas(CanvasFingerprintData {
+            hash: "new-canvas".to_string(),
+            ..fingerprint().canvas_fingerprint
+        })
+        .apply(fake("canvas", Err(SourceError::Unsupported)).as_ref(), &mut data);
+
+        assert_eq!(data.canvas_fingerprint.hash, "new-canvas");
+        assert!(data.custom_components.is_empty());
+    }
+
+    #[test]
+    fn source_errors_map_to_component_status() {
+        assert_eq!(ComponentStatus::from(SourceError::Blocked), ComponentStatus::Blocked);
+        assert_eq!(ComponentStatus::from(SourceError::TimedOut), ComponentStatus::TimedOut);
+        assert_eq!(
+            ComponentStatus::from(SourceError::Error("boom".to_string())),
+            ComponentStatus::Error("boom".to_string()),
+        );
+        assert_eq!(ComponentStatus::Error("boom".to_string()).label(), "error");
+        assert_eq!(ComponentStatus::from(SourceError::Silent), ComponentStatus::Silent);
+        assert_eq!(ComponentStatus::Silent.label(), "silent");
+        assert!(!ComponentStatus::Disabled.is_ok());
+    }
+
+    #[test]
+    fn ready_source_finishes_without_a_timer() {
+        // Vetores síncronos terminam no primeiro poll, sem criar o timer
+        let no_timer = |_: f64| -> SleepFuture { panic!("timer created") };
+        let ok: SourceFuture<'_> = Box::pin(async { Ok(custom("abc")) });
+        let failed: SourceFuture<'_> = Box::pin(async { Err(SourceError::Blocked) });
+
+        assert!(matches!(block_on(with_timeout(ok, &no_timer, 0.0)), Ok(SourceOutput::Custom(_))));
+        assert_eq!(block_on(with_timeout(failed, &no_timer, 0.0)).unwrap_err(), SourceError::Blocked);
+    }
+}
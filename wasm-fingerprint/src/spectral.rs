@@ -0,0 +1,309 @@
+//! Análise espectral do buffer de áudio renderizado
+//!
+//! Extrai características de frequência (magnitudes da FFT, centróide,
+//! rolloff e a assinatura de distorção harmônica do compressor) a partir das
+//! amostras de um `OfflineAudioContext`. O cálculo é puramente numérico: a
+//! FFT é implementada aqui em `f64`, sem SIMD, e o mesmo buffer produz as
+//! mesmas características sempre que analisado na mesma plataforma. Entre
+//! plataformas (ex.: Wasm no navegador e nativo) os últimos bits podem
+//! diferir, já que `sin`/`cos` da FFT e da janela de Hann vêm de
+//! implementações de libm diferentes; compare com tolerância.
+
+use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Características espectrais de um buffer de áudio
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct SpectralFeatures {
+    /// Tamanho da FFT (potência de 2), em amostras
+    pub fft_size: u32,
+    /// Magnitude média de cada banda, normalizada pela maior banda
+    pub band_magnitudes: Vec<f64>,
+    /// Centróide espectral (Hz)
+    pub centroid: f64,
+    /// Frequência abaixo da qual está `ROLLOFF_FRACTION` da energia (Hz)
+    pub rolloff: f64,
+    /// Magnitude de cada harmônico (2º ao 5º) relativa à fundamental
+    pub harmonic_ratios: Vec<f64>,
+    /// Distorção harmônica total (THD) dos harmônicos medidos
+    pub harmonic_distortion: f64,
+}
+
+/// Número de bandas em que o espectro é resumido
+pub const SPECTRAL_BANDS: usize = 32;
+
+/// Fração da energia usada no rolloff
+pub const ROLLOFF_FRACTION: f64 = 0.85;
+
+/// Harmônicos medidos na assinatura de distorção (2º ao 5º)
+const HARMONICS: std::ops::RangeInclusive<u32> = 2..=5;
+
+/// Bins vizinhos considerados ao procurar o pico de um harmônico
+const PEAK_SEARCH_BINS: usize = 2;
+
+impl SpectralFeatures {
+    /// Analisa o final do buffer, onde o compressor já estabilizou.
+    /// `fundamental` é a frequência do oscilador, em Hz.
+    pub fn analyze(samples: &[f32], sample_rate: f32, fundamental: f32) -> Self {
+        let fft_size = largest_power_of_two(samples.len());
+        if fft_size < 2 * SPECTRAL_BANDS || sample_rate <= 0.0 {
+            return SpectralFeatures::default();
+        }
+
+        let sample_rate = sample_rate as f64;
+        let magnitudes = magnitude_spectrum(&samples[samples.len() - fft_size..]);
+        let bin_width = sample_rate / fft_size as f64;
+
+        let (harmonic_ratios, harmonic_distortion) =
+            harmonic_signature(&magnitudes, fundamental as f64, sample_rate, bin_width);
+
+        SpectralFeatures {
+            fft_size: fft_size as u32,
+            band_magnitudes: band_magnitudes(&magnitudes),
+            centroid: spectral_centroid(&magnitudes, bin_width),
+            rolloff: spectral_rolloff(&magnitudes, bin_width),
+            harmonic_ratios,
+            harmonic_distortion,
+        }
+    }
+
+    /// Vetor numérico com todas as características, em ordem fixa:
+    /// centróide, rolloff, THD, razões harmônicas e magnitudes das bandas
+    pub fn feature_vector(&self) -> Vec<f64> {
+        let mut vector = vec![self.centroid, self.rolloff, self.harmonic_distortion];
+        vector.extend(&self.harmonic_ratios);
+        vector.extend(&self.band_magnitudes);
+        vector
+    }
+}
+
+fn largest_power_of_two(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        1 << (usize::BITS - 1 - n.leading_zeros())
+    }
+}
+
+/// Magnitudes dos bins 0..N/2 da FFT do sinal com janela de Hann
+fn magnitude_spectrum(samples: &[f32]) -> Vec<f64> {
+    let n = samples.len();
+    let mut re: Vec<f64> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| {
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / (n - 1) as f64).cos();
+            s as f64 * window
+        })
+        .collect();
+    let mut im = vec![0.0; n];
+
+    fft(&mut re, &mut im);
+
+    re.iter()
+        .zip(&im)
+        .take(n / 2)
+        .map(|(r, i)| (r * r + i * i).sqrt())
+        .collect()
+}
+
+/// FFT radix-2 iterativa, in-place; `re.len()` deve ser potência de 2
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+
+    // Permutação por inversão de bits
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+fn band_magnitudes(magnitudes: &[f64]) -> Vec<f64> {
+    let band_size = magnitudes.len() / SPECTRAL_BANDS;
+    let bands: Vec<f64> = magnitudes
+        .chunks(band_size)
+        .take(SPECTRAL_BANDS)
+        .map(|band| band.iter().sum::<f64>() / band.len() as f64)
+        .collect();
+
+    let peak = bands.iter().cloned().fold(0.0, f64::max);
+    if peak > 0.0 {
+        bands.iter().map(|b| b / peak).collect()
+    } else {
+        bands
+    }
+}
+
+fn spectral_centroid(magnitudes: &[f64], bin_width: f64) -> f64 {
+    let total: f64 = magnitudes.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let weighted: f64 = magnitudes
+        .iter()
+        .enumerate()
+        .map(|(i, m)| i as f64 * bin_width * m)
+        .sum();
+    weighted / total
+}
+
+fn spectral_rolloff(magnitudes: &[f64], bin_width: f64) -> f64 {
+    let total: f64 = magnitudes.iter().map(|m| m * m).sum();
+    let mut cumulative = 0.0;
+    for (i, m) in magnitudes.iter().enumerate() {
+        cumulative += m * m;
+        if cumulative >= ROLLOFF_FRACTION * total {
+            return i as f64 * bin_width;
+        }
+    }
+    0.0
+}
+
+/// Razões dos harmônicos em relação à fundamental e a THD. Harmônicos acima
+/// de Nyquist são procurados na frequência para onde se espelham (aliasing),
+/// que é onde o `OfflineAudioContext` os renderiza.
+fn harmonic_signature(
+    magnitudes: &[f64],
+    fundamental: f64,
+    sample_rate: f64,
+    bin_width: f64,
+) -> (Vec<f64>, f64) {
+    let peak_at = |frequency: f64| -> f64 {
+        let folded = frequency % sample_rate;
+        let folded = if folded > sample_rate / 2.0 { sample_rate - folded } else { folded };
+        let center = (folded / bin_width).round() as usize;
+        let low = center.saturating_sub(PEAK_SEARCH_BINS);
+        let high = (center + PEAK_SEARCH_BINS).min(magnitudes.len() - 1);
+        magnitudes[low.min(high)..=high].iter().cloned().fold(0.0, f64::max)
+    };
+
+    let base = peak_at(fundamental);
+    if base == 0.0 {
+        return (vec![0.0; HARMONICS.count()], 0.0);
+    }
+
+    let ratios: Vec<f64> = HARMONICS
+        .map(|k| peak_at(fundamental * k as f64) / base)
+        .collect();
+    let distortion = ratios.iter().map(|r| r * r).sum::<f64>().sqrt();
+
+    (ratios, distortion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+    const LENGTH: usize = 4096;
+
+    /// Frequência exatamente no centro do bin `bin` de uma FFT de `LENGTH`
+    fn bin_frequency(bin: usize) -> f64 {
+        bin as f64 * SAMPLE_RATE as f64 / LENGTH as f64
+    }
+
+    fn sine(frequency: f64) -> Vec<f32> {
+        (0..LENGTH)
+            .map(|i| (2.0 * PI * frequency * i as f64 / SAMPLE_RATE as f64).sin() as f32)
+            .collect()
+    }
+
+    fn argmax(values: &[f64]) -> usize {
+        values
+            .iter()
+            .enumerate()
+            .fold((0, f64::MIN), |best, (i, &v)| if v > best.1 { (i, v) } else { best })
+            .0
+    }
+
+    #[test]
+    fn pure_sine_peaks_at_its_bin() {
+        let frequency = bin_frequency(64);
+        let samples = sine(frequency);
+
+        assert_eq!(argmax(&magnitude_spectrum(&samples)), 64);
+
+        let features = SpectralFeatures::analyze(&samples, SAMPLE_RATE, frequency as f32);
+        let bin_width = bin_frequency(1);
+        assert_eq!(features.fft_size, LENGTH as u32);
+        assert!((features.centroid - frequency).abs() < bin_width);
+        // Bins 64..128 formam a segunda banda
+        assert_eq!(argmax(&features.band_magnitudes), 1);
+        assert_eq!(features.band_magnitudes[1], 1.0);
+        assert!(features.harmonic_distortion < 1e-3);
+    }
+
+    #[test]
+    fn silence_gives_zeroed_features() {
+        let features = SpectralFeatures::analyze(&[0.0; LENGTH], SAMPLE_RATE, 1000.0);
+
+        assert_eq!(features.fft_size, LENGTH as u32);
+        assert_eq!(features.centroid, 0.0);
+        assert_eq!(features.rolloff, 0.0);
+        assert_eq!(features.harmonic_distortion, 0.0);
+        assert!(features.band_magnitudes.iter().all(|&m| m == 0.0));
+        assert!(features.harmonic_ratios.iter().all(|&r| r == 0.0));
+    }
+
+    #[test]
+    fn short_buffer_gives_default_features() {
+        let features = SpectralFeatures::analyze(&[0.5; 16], SAMPLE_RATE, 1000.0);
+        assert_eq!(features, SpectralFeatures::default());
+    }
+
+    #[test]
+    fn clipped_sine_has_odd_harmonics() {
+        // Seno totalmente saturado (onda quadrada): harmônicos ímpares com
+        // 1/k da fundamental e pares ausentes. Meia amostra de fase evita
+        // amostras exatamente no cruzamento por zero.
+        let frequency = bin_frequency(16);
+        let samples: Vec<f32> = (0..LENGTH)
+            .map(|i| {
+                let phase = 2.0 * PI * frequency * (i as f64 + 0.5) / SAMPLE_RATE as f64;
+                phase.sin().signum() as f32
+            })
+            .collect();
+
+        let features = SpectralFeatures::analyze(&samples, SAMPLE_RATE, frequency as f32);
+        let ratios = &features.harmonic_ratios;
+
+        assert_eq!(ratios.len(), 4);
+        assert!(ratios[0] < 0.01, "2º harmônico: {}", ratios[0]);
+        assert!((ratios[1] - 1.0 / 3.0).abs() < 0.01, "3º harmônico: {}", ratios[1]);
+        assert!(ratios[2] < 0.01, "4º harmônico: {}", ratios[2]);
+        assert!((ratios[3] - 1.0 / 5.0).abs() < 0.01, "5º harmônico: {}", ratios[3]);
+        let expected = ((1.0f64 / 9.0) + (1.0 / 25.0)).sqrt();
+        assert!((features.harmonic_distortion - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn analysis_is_repeatable() {
+        let samples = sine(bin_frequency(100) + 3.7);
+        let a = SpectralFeatures::analyze(&samples, SAMPLE_RATE, 1000.0);
+        let b = SpectralFeatures::analyze(&samples, SAMPLE_RATE, 1000.0);
+        assert_eq!(a.feature_vector(), b.feature_vector());
+    }
+}
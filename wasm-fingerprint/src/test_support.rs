@@ -0,0 +1,146 @@
+//! Fingerprints de exemplo, relógios roteirizados e executor mínimo para
+//! os testes nativos
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use crate::{
+    AudioFingerprintData, BenchmarkResults, BrowserAttributes, CanvasFingerprintData,
+    FingerprintData, FontFingerprintData, HardwareProfile, TimerProfile, WebGLFingerprintData,
+};
+use crate::port_contention::ContentionResult;
+use crate::schema::SCHEMA_VERSION;
+use crate::composite::COMPOSITE_VERSION;
+use crate::timing::ScriptedClock;
+
+/// Fingerprint completo de um dispositivo fictício, com todos os vetores
+/// usados pelo matcher preenchidos
+pub fn fingerprint() -> FingerprintData {
+    FingerprintData {
+        schema_version: SCHEMA_VERSION,
+        fingerprint_hash: String::new(),
+        stable_hash: String::new(),
+        full_hash: String::new(),
+        composite_version: COMPOSITE_VERSION,
+        canvas_fingerprint: CanvasFingerprintData {
+            hash: "canvas-hash".to_string(),
+            width: 400,
+            height: 200,
+            perceptual_hash: "00ff00ff00ff00ff".to_string(),
+            ..Default::default()
+        },
+        font_fingerprint: FontFingerprintData {
+            hash: "font-hash".to_string(),
+            fonts: vec!["Arial".to_string(), "Verdana".to_string()],
+            bitset: "c0".to_string(),
+            candidates_count: 8,
+        },
+        webgl_fingerprint: WebGLFingerprintData {
+            hash: "webgl-hash".to_string(),
+            vendor: "WebKit".to_string(),
+            renderer: "WebKit WebGL".to_string(),
+            unmasked_vendor: "Example GPU Vendor".to_string(),
+            unmasked_renderer: "Example GPU 1000".to_string(),
+            context: "webgl2".to_string(),
+            ..Default::default()
+        },
+        webgpu_fingerprint: Default::default(),
+        audio_fingerprint: AudioFingerprintData {
+            hash: "audio-hash".to_string(),
+            sample_rate: 44100,
+            buffer_length: 5000,
+            sum: 120.5,
+            avg: 0.0241,
+            max: 0.99,
+            samples: vec![1200, -3400, 5600],
+            ..Default::default()
+        },
+        hardware_profile: HardwareProfile {
+            cores: 8,
+            memory: 8.0,
+            concurrency: 8,
+            benchmarks: Some(BenchmarkResults {
+                math_ops: 12.0,
+                string_ops: 30.0,
+                array_ops: 4.0,
+                crypto_ops: 14.4,
+            }),
+            cpu_benchmark: 12.0,
+            memory_benchmark: 30.0,
+            crypto_benchmark: 4.0,
+            port_contention_vector: vec![
+                ContentionResult {
+                    instruction_pair: "mul_add".to_string(),
+                    grouped_time: 1.0,
+                    interleaved_time: 1.3,
+                    ratio_rho: 1.3,
+                },
+                ContentionResult {
+                    instruction_pair: "div_mul".to_string(),
+                    grouped_time: 2.0,
+                    interleaved_time: 1.8,
+                    ratio_rho: 0.9,
+                },
+            ],
+            ..Default::default()
+        },
+        browser_info: BrowserAttributes {
+            user_agent: "Mozilla/5.0 (X11; Linux x86_64) Chrome/120.0 Safari/537.36".to_string(),
+            language: "pt-BR".to_string(),
+            platform: "Linux x86_64".to_string(),
+            hardware_concurrency: 8,
+            device_memory: Some(8.0),
+            screen_width: 1920,
+            screen_height: 1080,
+            screen_resolution: "1920x1080".to_string(),
+            color_depth: 24,
+            timezone_offset: 180,
+            plugins_count: 0,
+        },
+        timestamp: 0.0,
+        custom_components: BTreeMap::new(),
+        component_status: BTreeMap::new(),
+        missing_components: Vec::new(),
+    }
+}
+
+/// Relógio em que cada par de medições (agrupada, depois intercalada) de
+/// um benchmark de contenção dura o tempo dado
+pub fn scripted_durations(durations: &[(f64, f64)]) -> ScriptedClock {
+    let mut now = 0.0;
+    let mut readings = Vec::new();
+    for &(grouped, interleaved) in durations {
+        for elapsed in [grouped, interleaved] {
+            readings.push(now);
+            now += elapsed;
+            readings.push(now);
+        }
+    }
+    ScriptedClock::new(readings, 1.0)
+}
+
+/// Perfil com resolução nula: o `AdaptiveTimer` aceita a primeira
+/// execução, sem ampliar as iterações
+pub fn single_run_profile() -> TimerProfile {
+    TimerProfile { resolution_ms: 0.0, ..Default::default() }
+}
+
+/// Executa um futuro até o fim, fazendo poll em laço. Serve para os
+/// futuros dos testes, que nunca dependem de um evento externo.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    fn raw_waker() -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(|_| raw_waker(), |_| {}, |_| {}, |_| {});
+
+    // SAFETY: o vtable não usa o ponteiro de dados
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
@@ -0,0 +1,521 @@
+//! Relógio dos benchmarks e caracterização da sua resolução
+//!
+//! Os navegadores arredondam e adicionam jitter a `performance.now()`
+//! (100µs em alguns modos, 1ms em outros), o que torna medições curtas
+//! inúteis. Este módulo mede a granularidade efetiva do relógio e a
+//! distribuição dos incrementos observados e, quando a página é
+//! cross-origin isolated e `SharedArrayBuffer` está disponível, oferece um
+//! relógio de alta resolução: um worker incrementa um contador compartilhado
+//! em laço e o contador é convertido para milissegundos por calibração
+//! contra `performance.now()`.
+//!
+//! `AdaptiveTimer` escolhe o número de iterações de cada benchmark de forma
+//! que a duração medida cubra muitas vezes a resolução do relógio.
+//!
+//! Os benchmarks leem o tempo pelo trait `Clock`: `BenchmarkClock` no
+//! navegador, `InstantClock` nativamente e `ScriptedClock` para leituras
+//! determinísticas, o que permite testar a lógica de medição fora do
+//! navegador.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::time::Instant;
+use wasm_bindgen::prelude::*;
+use js_sys::{Array, Atomics, Int32Array, Reflect, SharedArrayBuffer};
+use web_sys::{Blob, BlobPropertyBag, Url, Worker};
+use crate::config::TimingConfig;
+use crate::dom_utils::get_window;
+use crate::port_contention::PortContentionFingerprint;
+use crate::utils::{performance_now, sleep};
+use crate::{JitterStats, TimerProfile};
+
+/// Nome do relógio baseado em `performance.now()`
+pub const PERFORMANCE_NOW_CLOCK: &str = "performance_now";
+
+/// Nome do relógio baseado no contador do worker
+pub const COUNTER_CLOCK: &str = "shared_array_buffer_counter";
+
+/// Nome do relógio nativo baseado em `std::time::Instant`
+pub const INSTANT_CLOCK: &str = "instant";
+
+/// Nome do relógio com leituras roteirizadas
+pub const SCRIPTED_CLOCK: &str = "scripted";
+
+/// Código do worker: incrementa a posição 0 do buffer recebido para sempre
+const COUNTER_WORKER: &str = r#"
+onmessage = (event) => {
+    const counter = new Int32Array(event.data);
+    for (;;) {
+        Atomics.add(counter, 0, 1);
+    }
+};
+"#;
+
+/// Limite de leituras na caracterização, para um relógio parado não
+/// travar o laço
+const MAX_CHARACTERIZATION_READS: u32 = 50_000_000;
+
+/// Tempo máximo esperando o worker começar a contar, em milissegundos
+const COUNTER_STARTUP_MS: f64 = 500.0;
+
+/// Função exportada para JavaScript - relógio disponível para os
+/// benchmarks, sua resolução e jitter
+#[wasm_bindgen]
+pub async fn get_timer_profile() -> Result<JsValue, JsValue> {
+    let config = TimingConfig::default();
+    let clock = BenchmarkClock::acquire(&config).await;
+    let profile = clock.characterize(&config);
+    serde_wasm_bindgen::to_value(&profile).map_err(JsValue::from)
+}
+
+/// Se a página é cross-origin isolated (`window.crossOriginIsolated`),
+/// condição para os navegadores exporem `SharedArrayBuffer`
+pub fn cross_origin_isolated() -> bool {
+    get_window()
+        .ok()
+        .and_then(|window| Reflect::get(&window, &JsValue::from_str("crossOriginIsolated")).ok())
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+pub fn shared_array_buffer_available() -> bool {
+    Reflect::has(&js_sys::global(), &JsValue::from_str("SharedArrayBuffer")).unwrap_or(false)
+}
+
+/// Fonte de tempo dos benchmarks
+pub trait Clock {
+    /// Tempo em milissegundos, a partir de uma origem arbitrária
+    fn now(&self) -> f64;
+
+    /// Nome registrado em `TimerProfile::clock`
+    fn name(&self) -> &str;
+
+    /// Resolução e jitter do relógio. As flags do navegador ficam em
+    /// `false`; `BenchmarkClock` as preenche.
+    fn characterize(&self, config: &TimingConfig) -> TimerProfile {
+        measure_resolution(self, config)
+    }
+}
+
+/// Lê o relógio em laço e registra os incrementos não nulos entre leituras
+/// consecutivas, até `resolution_samples` incrementos ou
+/// `characterization_ms`
+pub fn measure_resolution<C: Clock + ?Sized>(clock: &C, config: &TimingConfig) -> TimerProfile {
+    let start = clock.now();
+    // Sincroniza com a borda de um incremento: o primeiro intervalo
+    // começaria no meio de um tick
+    let mut last = start;
+    let mut reads = 0u32;
+    while last == start && reads < MAX_CHARACTERIZATION_READS {
+        last = clock.now();
+        reads += 1;
+    }
+
+    let mut deltas = Vec::with_capacity(config.resolution_samples as usize);
+    while deltas.len() < config.resolution_samples as usize && reads < MAX_CHARACTERIZATION_READS {
+        let now = clock.now();
+        reads += 1;
+        if now > last {
+            deltas.push(now - last);
+            last = now;
+        }
+        if now - start > config.characterization_ms {
+            break;
+        }
+    }
+
+    let jitter = jitter_stats(&mut deltas);
+    TimerProfile {
+        clock: clock.name().to_string(),
+        resolution_ms: jitter.median_ms,
+        jitter,
+        cross_origin_isolated: false,
+        shared_array_buffer: false,
+    }
+}
+
+/// Relógio nativo. `Instant::now` não é suportado em
+/// wasm32-unknown-unknown, então este relógio serve só para executar os
+/// benchmarks fora do navegador.
+pub struct InstantClock {
+    origin: Instant,
+}
+
+impl InstantClock {
+    pub fn new() -> Self {
+        InstantClock { origin: Instant::now() }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> Self {
+        InstantClock::new()
+    }
+}
+
+impl Clock for InstantClock {
+    fn now(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64() * 1000.0
+    }
+
+    fn name(&self) -> &str {
+        INSTANT_CLOCK
+    }
+}
+
+/// Relógio falso: devolve as leituras roteirizadas em ordem e, esgotado o
+/// roteiro, avança `step_ms` a cada leitura a partir da última
+pub struct ScriptedClock {
+    readings: RefCell<VecDeque<f64>>,
+    step_ms: f64,
+    last: Cell<f64>,
+    reads: Cell<usize>,
+}
+
+impl ScriptedClock {
+    pub fn new<I>(readings: I, step_ms: f64) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        ScriptedClock {
+            readings: RefCell::new(readings.into_iter().collect()),
+            step_ms,
+            last: Cell::new(0.0),
+            reads: Cell::new(0),
+        }
+    }
+
+    /// Começa em zero e avança `step_ms` a cada leitura
+    pub fn stepping(step_ms: f64) -> Self {
+        ScriptedClock::new([0.0], step_ms)
+    }
+
+    /// Número de leituras feitas até agora
+    pub fn reads(&self) -> usize {
+        self.reads.get()
+    }
+
+    /// Leituras do roteiro ainda não consumidas
+    pub fn remaining(&self) -> usize {
+        self.readings.borrow().len()
+    }
+}
+
+impl Clock for ScriptedClock {
+    fn now(&self) -> f64 {
+        let now = match self.readings.borrow_mut().pop_front() {
+            Some(reading) => reading,
+            None => self.last.get() + self.step_ms,
+        };
+        self.last.set(now);
+        self.reads.set(self.reads.get() + 1);
+        now
+    }
+
+    fn name(&self) -> &str {
+        SCRIPTED_CLOCK
+    }
+}
+
+/// Relógio de alta resolução baseado em um contador incrementado por um
+/// worker. O worker é encerrado no `Drop`.
+pub struct CounterClock {
+    worker: Worker,
+    counter: Int32Array,
+    ticks_per_ms: f64,
+    last: Cell<u32>,
+    total: Cell<u64>,
+}
+
+impl CounterClock {
+    /// Inicia o worker e calibra o contador contra `performance.now()`
+    /// durante `calibration_ms`
+    pub async fn start(calibration_ms: f64) -> Result<CounterClock, JsValue> {
+        if !shared_array_buffer_available() {
+            return Err(JsValue::from_str("SharedArrayBuffer unavailable"));
+        }
+        let buffer = SharedArrayBuffer::new(4);
+        let counter = Int32Array::new(&buffer);
+
+        let options = BlobPropertyBag::new();
+        options.set_type("application/javascript");
+        let blob = Blob::new_with_str_sequence_and_options(
+            &Array::of1(&JsValue::from_str(COUNTER_WORKER)),
+            &options,
+        )?;
+        let url = Url::create_object_url_with_blob(&blob)?;
+        let worker = Worker::new(&url);
+        Url::revoke_object_url(&url)?;
+        let worker = worker?;
+        worker.post_message(&buffer)?;
+
+        let mut clock = CounterClock {
+            worker,
+            counter,
+            ticks_per_ms: 0.0,
+            last: Cell::new(0),
+            total: Cell::new(0),
+        };
+
+        let deadline = performance_now() + COUNTER_STARTUP_MS;
+        while clock.ticks() == 0 {
+            if performance_now() > deadline {
+                return Err(JsValue::from_str("Counter worker did not start"));
+            }
+            sleep(1.0).await?;
+        }
+
+        let (start_ms, start_ticks) = (performance_now(), clock.ticks());
+        sleep(calibration_ms).await?;
+        let (end_ms, end_ticks) = (performance_now(), clock.ticks());
+        if end_ms <= start_ms || end_ticks <= start_ticks {
+            return Err(JsValue::from_str("Counter clock calibration failed"));
+        }
+        clock.ticks_per_ms = (end_ticks - start_ticks) as f64 / (end_ms - start_ms);
+        Ok(clock)
+    }
+
+    /// Contagem acumulada desde o início. O contador de 32 bits dá a volta,
+    /// então `ticks` precisa ser chamado ao menos uma vez por volta
+    /// (dezenas de segundos).
+    pub fn ticks(&self) -> u64 {
+        let raw = Atomics::load(&self.counter, 0).unwrap_or(0) as u32;
+        let delta = raw.wrapping_sub(self.last.get());
+        self.last.set(raw);
+        self.total.set(self.total.get() + delta as u64);
+        self.total.get()
+    }
+}
+
+impl Clock for CounterClock {
+    fn now(&self) -> f64 {
+        self.ticks() as f64 / self.ticks_per_ms
+    }
+
+    fn name(&self) -> &str {
+        COUNTER_CLOCK
+    }
+}
+
+impl Drop for CounterClock {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+/// Relógio dos benchmarks no navegador
+pub enum BenchmarkClock {
+    Performance,
+    Counter(CounterClock),
+}
+
+impl BenchmarkClock {
+    /// O contador do worker quando habilitado e disponível; caso contrário
+    /// (ou se ele falhar ao iniciar), `performance.now()`
+    pub async fn acquire(config: &TimingConfig) -> BenchmarkClock {
+        if config.counter_clock && cross_origin_isolated() {
+            if let Ok(counter) = CounterClock::start(config.calibration_ms).await {
+                return BenchmarkClock::Counter(counter);
+            }
+        }
+        BenchmarkClock::Performance
+    }
+}
+
+impl Clock for BenchmarkClock {
+    fn now(&self) -> f64 {
+        match self {
+            BenchmarkClock::Performance => performance_now(),
+            BenchmarkClock::Counter(counter) => counter.now(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            BenchmarkClock::Performance => PERFORMANCE_NOW_CLOCK,
+            BenchmarkClock::Counter(_) => COUNTER_CLOCK,
+        }
+    }
+
+    fn characterize(&self, config: &TimingConfig) -> TimerProfile {
+        TimerProfile {
+            cross_origin_isolated: cross_origin_isolated(),
+            shared_array_buffer: shared_array_buffer_available(),
+            ..measure_resolution(self, config)
+        }
+    }
+}
+
+/// Estatísticas dos incrementos observados (ordena `deltas`)
+pub fn jitter_stats(deltas: &mut [f64]) -> JitterStats {
+    if deltas.is_empty() {
+        return JitterStats::default();
+    }
+    let median_ms = PortContentionFingerprint::median(deltas);
+    let n = deltas.len();
+    let percentile = |p: f64| deltas[((n - 1) as f64 * p).round() as usize];
+    let mean = deltas.iter().sum::<f64>() / n as f64;
+    let variance = deltas.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n as f64;
+
+    JitterStats {
+        samples: n as u32,
+        min_ms: deltas[0],
+        median_ms,
+        p95_ms: percentile(0.95),
+        max_ms: deltas[n - 1],
+        std_dev_ms: variance.sqrt(),
+    }
+}
+
+/// Duração de uma execução adaptativa
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub elapsed_ms: f64,
+    /// Iterações efetivamente executadas
+    pub iterations: u32,
+}
+
+impl Measurement {
+    /// Duração equivalente a `iterations` iterações, para que os resultados
+    /// mantenham a unidade da configuração
+    pub fn scaled_to(&self, iterations: u32) -> f64 {
+        self.elapsed_ms * iterations as f64 / self.iterations.max(1) as f64
+    }
+
+    pub fn per_iteration(&self) -> f64 {
+        self.elapsed_ms / self.iterations.max(1) as f64
+    }
+}
+
+/// Dobra o número de iterações até a medição durar ao menos
+/// `min_resolutions` vezes a resolução do relógio
+pub struct AdaptiveTimer<'a> {
+    clock: &'a dyn Clock,
+    target_ms: f64,
+    max_scale: u32,
+}
+
+impl<'a> AdaptiveTimer<'a> {
+    pub fn new(clock: &'a dyn Clock, profile: &TimerProfile, config: &TimingConfig) -> Self {
+        AdaptiveTimer {
+            clock,
+            target_ms: profile.resolution_ms * config.min_resolutions,
+            max_scale: config.max_iteration_scale.max(1),
+        }
+    }
+
+    /// Executa `run(iterações)` a partir de `base` iterações, até
+    /// `base * max_iteration_scale`
+    pub fn measure<R, F>(&self, base: u32, mut run: F) -> Measurement
+    where
+        F: FnMut(u32) -> R,
+    {
+        let base = base.max(1);
+        let limit = base.saturating_mul(self.max_scale);
+        let mut iterations = base;
+        loop {
+            let start = self.clock.now();
+            black_box(run(iterations));
+            let elapsed_ms = self.clock.now() - start;
+
+            if elapsed_ms >= self.target_ms || iterations >= limit {
+                return Measurement { elapsed_ms, iterations };
+            }
+            iterations = iterations.saturating_mul(2).min(limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn timing() -> TimingConfig {
+        TimingConfig { min_resolutions: 20.0, max_iteration_scale: 16, ..Default::default() }
+    }
+
+    fn profile(resolution_ms: f64) -> TimerProfile {
+        TimerProfile { resolution_ms, ..Default::default() }
+    }
+
+    #[test]
+    fn resolution_is_the_median_increment() {
+        // Início, sincronização com a borda em 0.5 e depois incrementos de
+        // 0.5, 0.5, 1.0 e 0.5 intercalados com leituras repetidas
+        let clock = ScriptedClock::new([0.0, 0.0, 0.5, 0.5, 1.0, 1.0, 1.5, 2.5, 2.5, 3.0], 0.0);
+        let config = TimingConfig { resolution_samples: 4, ..Default::default() };
+        let profile = measure_resolution(&clock, &config);
+
+        assert_eq!(profile.clock, SCRIPTED_CLOCK);
+        assert_eq!(profile.jitter.samples, 4);
+        assert_eq!(profile.resolution_ms, 0.5);
+        assert_eq!(profile.jitter.max_ms, 1.0);
+        assert_eq!(clock.remaining(), 0);
+    }
+
+    #[test]
+    fn jitter_stats_of_known_increments() {
+        let stats = jitter_stats(&mut [4.0, 1.0, 3.0, 2.0]);
+
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.min_ms, 1.0);
+        // Amostra par: média dos dois centrais
+        assert_eq!(stats.median_ms, 2.5);
+        assert_eq!(stats.p95_ms, 4.0);
+        assert_eq!(stats.max_ms, 4.0);
+        assert!((stats.std_dev_ms - 1.25f64.sqrt()).abs() < 1e-12);
+
+        let stats = jitter_stats(&mut [3.0, 1.0, 2.0]);
+        assert_eq!(stats.median_ms, 2.0);
+        assert_eq!(jitter_stats(&mut []).samples, 0);
+    }
+
+    #[test]
+    fn measurement_keeps_the_configured_unit() {
+        let measurement = Measurement { elapsed_ms: 32.0, iterations: 32 };
+        assert_eq!(measurement.scaled_to(4), 4.0);
+        assert_eq!(measurement.per_iteration(), 1.0);
+
+        let empty = Measurement { elapsed_ms: 2.0, iterations: 0 };
+        assert_eq!(empty.per_iteration(), 2.0);
+    }
+
+    #[test]
+    fn iterations_double_until_the_target() {
+        // Cada execução dura 1ms por iteração; alvo de 20 resoluções de 1ms
+        let clock = ScriptedClock::new([0.0, 4.0, 4.0, 12.0, 12.0, 28.0, 28.0, 60.0], 0.0);
+        let timer = AdaptiveTimer::new(&clock, &profile(1.0), &timing());
+        let runs = RefCell::new(Vec::new());
+        let measurement = timer.measure(4, |iterations| runs.borrow_mut().push(iterations));
+
+        assert_eq!(runs.into_inner(), vec![4, 8, 16, 32]);
+        assert_eq!(measurement.iterations, 32);
+        assert_eq!(measurement.elapsed_ms, 32.0);
+        assert_eq!(measurement.scaled_to(4), 4.0);
+        assert_eq!(measurement.per_iteration(), 1.0);
+    }
+
+    #[test]
+    fn iterations_stop_at_the_scale_limit() {
+        let clock = ScriptedClock::stepping(1.0);
+        let config = TimingConfig { max_iteration_scale: 4, ..timing() };
+        let timer = AdaptiveTimer::new(&clock, &profile(1.0), &config);
+        let runs = RefCell::new(Vec::new());
+        let measurement = timer.measure(3, |iterations| runs.borrow_mut().push(iterations));
+
+        assert_eq!(runs.into_inner(), vec![3, 6, 12]);
+        assert_eq!(measurement.iterations, 12);
+        assert_eq!(measurement.elapsed_ms, 1.0);
+    }
+
+    #[test]
+    fn coarse_enough_first_run_is_kept() {
+        let clock = ScriptedClock::new([0.0, 25.0], 0.0);
+        let measurement = AdaptiveTimer::new(&clock, &profile(1.0), &timing()).measure(10, |_| ());
+
+        assert_eq!(measurement.iterations, 10);
+        assert_eq!(measurement.elapsed_ms, 25.0);
+    }
+}
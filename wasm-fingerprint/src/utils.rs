@@ -1,12 +1,49 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
 pub fn set_panic_hook() {
     // Panic hook can be enabled by adding console_error_panic_hook to Cargo.toml features
     // For now, using a no-op to avoid warnings
 }
 
+/// Future que resolve após `ms` milissegundos (via `setTimeout`)
+///
+/// Usa o `setTimeout` do escopo global, que existe tanto em `window` quanto
+/// em workers; sem ele, o future falha em vez de nunca resolver.
+pub fn sleep(ms: f64) -> wasm_bindgen_futures::JsFuture {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let global = js_sys::global();
+        let scheduled = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|set_timeout| set_timeout.dyn_into::<js_sys::Function>().ok())
+            .map(|set_timeout| set_timeout.call2(&global, &resolve, &JsValue::from_f64(ms.max(0.0))));
+
+        match scheduled {
+            Some(Ok(_)) => {}
+            Some(Err(error)) => {
+                let _ = reject.call1(&JsValue::NULL, &error);
+            }
+            None => {
+                let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("setTimeout indisponível"));
+            }
+        }
+    });
+    wasm_bindgen_futures::JsFuture::from(promise)
+}
+
 pub fn performance_now() -> f64 {
     web_sys::window()
         .expect("no global window")
         .performance()
         .expect("no performance object")
         .now()
-}
\ No newline at end of file
+}
+
+/// SHA-256 de uma string, em hexadecimal
+pub fn sha256_hex(data: &str) -> String {
+    use sha2::{Sha256, Digest};
+
+    let mut hasher = Sha256::new();
+    hasher.update(data.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
@@ -1,5 +1,8 @@
 use wasm_bindgen::prelude::*;
-use crate::utils::performance_now;
+use crate::config::{PortContentionConfig, TimingConfig};
+use crate::port_contention::PortContentionFingerprint;
+use crate::timing::{AdaptiveTimer, BenchmarkClock, Clock};
+use crate::TimerProfile;
 
 /// Implementação específica do benchmark de contenção de portas para instruções Wasm
 /// Baseado na Seção 4.2: Implementando o Benchmark em Wasm
@@ -10,18 +13,139 @@ use crate::utils::performance_now;
 #[wasm_bindgen]
 pub struct WasmPortBenchmark;
 
+/// Execução de um par de instruções com o número de iterações dado
+type PairFn = fn(u32) -> u32;
+
 /// Resultado de um benchmark específico (estrutura interna)
 pub struct WasmBenchmarkResult {
     pub instruction_pair: String,
+    /// Tempos equivalentes a `iterations` iterações
     pub grouped_time: f64,
     pub interleaved_time: f64,
     pub ratio: f64,
     pub iterations: u32,
+    /// Relógio usado e sua resolução efetiva, em ms
+    pub clock: String,
+    pub resolution_ms: f64,
+}
+
+impl WasmBenchmarkResult {
+    /// Resultado como string JSON
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"instruction_pair":"{}","grouped_time":{},"interleaved_time":{},"ratio":{},"iterations":{},"clock":"{}","resolution_ms":{}}}"#,
+            self.instruction_pair,
+            self.grouped_time,
+            self.interleaved_time,
+            self.ratio,
+            self.iterations,
+            self.clock,
+            self.resolution_ms
+        )
+    }
 }
 
 impl WasmPortBenchmark {
-    const DEFAULT_ITERATIONS: u32 = 100_000;
     const NUM_RUNS: u32 = 5;
+
+    const PAIRS: [&'static str; 5] = ["popcnt_or", "clz_and", "ctz_xor", "rotl_shl", "mul_add"];
+
+    /// Iterações padrão, de `PortContentionConfig::wasm_iterations`
+    fn iterations_or_default(iterations: Option<u32>) -> u32 {
+        iterations.unwrap_or_else(|| PortContentionConfig::default().wasm_iterations)
+    }
+
+    /// Funções agrupada e intercalada de um par de instruções
+    fn pair_functions(pair_name: &str) -> Option<(PairFn, PairFn)> {
+        let functions = match pair_name {
+            "popcnt_or" => (
+                grouped_execution_popcnt_or as PairFn,
+                interleaved_execution_popcnt_or as PairFn,
+            ),
+            "clz_and" => (
+                grouped_execution_clz_and as PairFn,
+                interleaved_execution_clz_and as PairFn,
+            ),
+            "ctz_xor" => (
+                grouped_execution_ctz_xor as PairFn,
+                interleaved_execution_ctz_xor as PairFn,
+            ),
+            "rotl_shl" => (
+                grouped_execution_rotl_shl as PairFn,
+                interleaved_execution_rotl_shl as PairFn,
+            ),
+            "mul_add" => (
+                grouped_execution_mul_add as PairFn,
+                interleaved_execution_mul_add as PairFn,
+            ),
+            _ => return None,
+        };
+        Some(functions)
+    }
+
+    /// Relógio das exportações: elas são síncronas e o contador do worker
+    /// exige uma calibração assíncrona, então usam `performance.now()`
+    fn performance_clock() -> (BenchmarkClock, TimerProfile, TimingConfig) {
+        let clock = BenchmarkClock::Performance;
+        let timing = TimingConfig::default();
+        let profile = clock.characterize(&timing);
+        (clock, profile, timing)
+    }
+
+    /// Mede um par de instruções; `timer` amplia as iterações até cobrir a
+    /// resolução do relógio descrito por `profile`
+    pub fn measure_pair(
+        timer: &AdaptiveTimer,
+        profile: &TimerProfile,
+        pair_name: &str,
+        iterations: u32,
+    ) -> Result<WasmBenchmarkResult, String> {
+        let (grouped_fn, interleaved_fn) = Self::pair_functions(pair_name)
+            .ok_or_else(|| "Invalid instruction pair name".to_string())?;
+
+        // Aquecimento
+        for _ in 0..10 {
+            grouped_fn(100);
+            interleaved_fn(100);
+        }
+
+        // Medições múltiplas para estabilidade
+        let mut grouped_times = Vec::new();
+        let mut interleaved_times = Vec::new();
+
+        for _ in 0..Self::NUM_RUNS {
+            // Medir execução agrupada
+            let grouped = timer.measure(iterations, grouped_fn);
+            grouped_times.push(grouped.scaled_to(iterations));
+
+            // Pequeno delay
+            let mut dummy = 0u32;
+            for i in 0..100 {
+                dummy = dummy.wrapping_add(i);
+            }
+            if dummy == u32::MAX { // Previne otimização
+                return Err("Unexpected".to_string());
+            }
+
+            // Medir execução intercalada
+            let interleaved = timer.measure(iterations, interleaved_fn);
+            interleaved_times.push(interleaved.scaled_to(iterations));
+        }
+
+        // Calcular medianas (mesma estatística do fingerprint de port contention)
+        let grouped_median = PortContentionFingerprint::median(&mut grouped_times);
+        let interleaved_median = PortContentionFingerprint::median(&mut interleaved_times);
+
+        Ok(WasmBenchmarkResult {
+            instruction_pair: pair_name.to_string(),
+            grouped_time: grouped_median,
+            interleaved_time: interleaved_median,
+            ratio: PortContentionFingerprint::ratio(grouped_median, interleaved_median),
+            iterations,
+            clock: profile.clock.clone(),
+            resolution_ms: profile.resolution_ms,
+        })
+    }
 }
 
 // ============================================================================
@@ -43,7 +167,7 @@ pub fn grouped_execution_popcnt_or(iterations: u32) -> u32 {
     // Loop 2: Apenas instruções OR
     for _ in 0..iterations {
         // Instrução B: i64.or (operação OU bit a bit)
-        val = val | 0xDEADBEEF;
+        val |= 0xDEADBEEF;
     }
 
     val as u32
@@ -60,7 +184,7 @@ pub fn interleaved_execution_popcnt_or(iterations: u32) -> u32 {
         // Instrução A: i64.popcnt
         val = val.count_ones() as u64;
         // Instrução B: i64.or
-        val = val | 0xDEADBEEF;
+        val |= 0xDEADBEEF;
     }
 
     val as u32
@@ -83,7 +207,7 @@ pub fn grouped_execution_clz_and(iterations: u32) -> u32 {
 
     // Loop 2: AND
     for _ in 0..iterations {
-        val = val & 0xCAFEBABE;
+        val &= 0xCAFEBABE;
         val = val.max(1); // Previne zero
     }
 
@@ -97,7 +221,7 @@ pub fn interleaved_execution_clz_and(iterations: u32) -> u32 {
     for _ in 0..iterations {
         val = val.leading_zeros() as u64;
         val = val.max(1);
-        val = val & 0xCAFEBABE;
+        val &= 0xCAFEBABE;
         val = val.max(1);
     }
 
@@ -117,7 +241,7 @@ pub fn grouped_execution_ctz_xor(iterations: u32) -> u32 {
 
     // Loop 2: XOR
     for _ in 0..iterations {
-        val = val ^ 0xABCDEF01;
+        val ^= 0xABCDEF01;
     }
 
     val as u32
@@ -130,7 +254,7 @@ pub fn interleaved_execution_ctz_xor(iterations: u32) -> u32 {
     for _ in 0..iterations {
         val = val.trailing_zeros() as u64;
         val = val.max(1);
-        val = val ^ 0xABCDEF01;
+        val ^= 0xABCDEF01;
     }
 
     val as u32
@@ -207,129 +331,41 @@ pub fn measure_wasm_port_contention(
     pair_name: &str,
     iterations: u32,
 ) -> Result<String, JsValue> {
-    let (grouped_fn, interleaved_fn) = match pair_name {
-        "popcnt_or" => (
-            grouped_execution_popcnt_or as fn(u32) -> u32,
-            interleaved_execution_popcnt_or as fn(u32) -> u32,
-        ),
-        "clz_and" => (
-            grouped_execution_clz_and as fn(u32) -> u32,
-            interleaved_execution_clz_and as fn(u32) -> u32,
-        ),
-        "ctz_xor" => (
-            grouped_execution_ctz_xor as fn(u32) -> u32,
-            interleaved_execution_ctz_xor as fn(u32) -> u32,
-        ),
-        "rotl_shl" => (
-            grouped_execution_rotl_shl as fn(u32) -> u32,
-            interleaved_execution_rotl_shl as fn(u32) -> u32,
-        ),
-        "mul_add" => (
-            grouped_execution_mul_add as fn(u32) -> u32,
-            interleaved_execution_mul_add as fn(u32) -> u32,
-        ),
-        _ => return Err(JsValue::from_str("Invalid instruction pair name")),
-    };
-
-    // Aquecimento
-    for _ in 0..10 {
-        grouped_fn(100);
-        interleaved_fn(100);
-    }
-
-    // Medições múltiplas para estabilidade
-    let mut grouped_times = Vec::new();
-    let mut interleaved_times = Vec::new();
-
-    for _ in 0..WasmPortBenchmark::NUM_RUNS {
-        // Medir execução agrupada
-        let start = performance_now();
-        let _ = grouped_fn(iterations);
-        let grouped_time = performance_now() - start;
-        grouped_times.push(grouped_time);
-
-        // Pequeno delay
-        let mut dummy = 0u32;
-        for i in 0..100 {
-            dummy = dummy.wrapping_add(i);
-        }
-        if dummy == u32::MAX { // Previne otimização
-            return Err(JsValue::from_str("Unexpected"));
-        }
-
-        // Medir execução intercalada
-        let start = performance_now();
-        let _ = interleaved_fn(iterations);
-        let interleaved_time = performance_now() - start;
-        interleaved_times.push(interleaved_time);
-    }
-
-    // Calcular medianas
-    grouped_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    interleaved_times.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-    let grouped_median = grouped_times[grouped_times.len() / 2];
-    let interleaved_median = interleaved_times[interleaved_times.len() / 2];
-
-    let ratio = if grouped_median > 0.0 {
-        interleaved_median / grouped_median
-    } else {
-        1.0
-    };
-
-    let result = WasmBenchmarkResult {
-        instruction_pair: pair_name.to_string(),
-        grouped_time: grouped_median,
-        interleaved_time: interleaved_median,
-        ratio,
-        iterations,
-    };
-
-    // Retornar como JSON string
-    Ok(format!(
-        r#"{{"instruction_pair":"{}","grouped_time":{},"interleaved_time":{},"ratio":{},"iterations":{}}}"#,
-        result.instruction_pair,
-        result.grouped_time,
-        result.interleaved_time,
-        result.ratio,
-        result.iterations
-    ))
+    let (clock, profile, timing) = WasmPortBenchmark::performance_clock();
+    let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+    WasmPortBenchmark::measure_pair(&timer, &profile, pair_name, iterations)
+        .map(|result| result.to_json())
+        .map_err(|e| JsValue::from_str(&e))
 }
 
 /// Executa todos os benchmarks de contenção de portas Wasm
 #[wasm_bindgen]
-pub fn run_all_wasm_benchmarks() -> Result<String, JsValue> {
-    let pairs = vec!["popcnt_or", "clz_and", "ctz_xor", "rotl_shl", "mul_add"];
-    let iterations = WasmPortBenchmark::DEFAULT_ITERATIONS;
+pub fn run_all_wasm_benchmarks(iterations: Option<u32>) -> Result<String, JsValue> {
+    let iterations = WasmPortBenchmark::iterations_or_default(iterations);
+    let (clock, profile, timing) = WasmPortBenchmark::performance_clock();
+    let timer = AdaptiveTimer::new(&clock, &profile, &timing);
 
     let mut results = String::from("WASM Port Contention Benchmark Results\n");
     results.push_str("=" .repeat(50).as_str());
-    results.push_str("\n\n");
-
-    for pair in pairs {
-        let result_json = measure_wasm_port_contention(pair, iterations)?;
+    results.push('\n');
+    results.push_str(&format!(
+        "Clock: {} (resolution {:.4} ms, p95 jitter {:.4} ms)\n\n",
+        profile.clock, profile.resolution_ms, profile.jitter.p95_ms
+    ));
 
-        // Parse JSON result
-        let parts: Vec<&str> = result_json.split(',').collect();
-        let ratio = parts.iter()
-            .find(|p| p.contains("ratio"))
-            .and_then(|p| p.split(':').nth(1))
-            .and_then(|v| v.parse::<f64>().ok())
-            .unwrap_or(1.0);
+    for pair in WasmPortBenchmark::PAIRS {
+        let result = WasmPortBenchmark::measure_pair(&timer, &profile, pair, iterations)
+            .map_err(|e| JsValue::from_str(&e))?;
+        let ratio = result.ratio;
 
         results.push_str(&format!("Instruction Pair: {}\n", pair));
-        results.push_str(&format!("  Raw JSON: {}\n", result_json));
+        results.push_str(&format!("  Raw JSON: {}\n", result.to_json()));
         results.push_str(&format!("  Ratio (ρ): {:.4}\n", ratio));
 
-        let interpretation = if ratio > 1.15 {
-            "✓ High parallelism (different execution ports)"
-        } else if ratio > 0.85 && ratio < 1.15 {
-            "≈ No significant contention"
-        } else {
-            "↓ Grouped faster (cache effects or optimization)"
-        };
-
-        results.push_str(&format!("  Interpretation: {}\n\n", interpretation));
+        results.push_str(&format!(
+            "  Interpretation: {}\n\n",
+            PortContentionFingerprint::interpretation(ratio)
+        ));
     }
 
     Ok(results)
@@ -337,33 +373,22 @@ pub fn run_all_wasm_benchmarks() -> Result<String, JsValue> {
 
 /// Gera um fingerprint baseado nos ratios de contenção Wasm
 #[wasm_bindgen]
-pub fn generate_wasm_fingerprint() -> Result<String, JsValue> {
+pub fn generate_wasm_fingerprint(iterations: Option<u32>) -> Result<String, JsValue> {
     use sha2::{Sha256, Digest};
 
-    let pairs = vec!["popcnt_or", "clz_and", "ctz_xor", "rotl_shl", "mul_add"];
-    let iterations = WasmPortBenchmark::DEFAULT_ITERATIONS;
+    let iterations = WasmPortBenchmark::iterations_or_default(iterations);
+    let (clock, profile, timing) = WasmPortBenchmark::performance_clock();
+    let timer = AdaptiveTimer::new(&clock, &profile, &timing);
 
     let mut fingerprint_data = String::new();
 
-    for pair in pairs {
-        let result_json = measure_wasm_port_contention(pair, iterations)?;
-
-        // Parse JSON result for ratio
-        let parts: Vec<&str> = result_json.split(',').collect();
-        let ratio = parts.iter()
-            .find(|p| p.contains("ratio"))
-            .and_then(|p| p.split(':').nth(1))
-            .and_then(|v| v.parse::<f64>().ok())
-            .unwrap_or(1.0);
+    for pair in WasmPortBenchmark::PAIRS {
+        let ratio = WasmPortBenchmark::measure_pair(&timer, &profile, pair, iterations)
+            .map_err(|e| JsValue::from_str(&e))?
+            .ratio;
 
         // Categorizar o ratio
-        let category = if ratio > 1.15 {
-            "parallel"
-        } else if ratio > 0.85 && ratio < 1.15 {
-            "neutral"
-        } else {
-            "sequential"
-        };
+        let category = PortContentionFingerprint::category(ratio);
 
         fingerprint_data.push_str(&format!(
             "{}:{}:{:.4}|",
@@ -379,4 +404,38 @@ pub fn generate_wasm_fingerprint() -> Result<String, JsValue> {
     let hash_result = hasher.finalize();
 
     Ok(format!("{:x}", hash_result))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{scripted_durations, single_run_profile};
+    use crate::timing::ScriptedClock;
+
+    #[test]
+    fn pair_uses_median_of_odd_measurements() {
+        let clock = scripted_durations(&[(3.0, 2.0), (1.0, 9.0), (2.0, 4.0), (5.0, 6.0), (4.0, 8.0)]);
+        let timing = TimingConfig::default();
+        let profile = single_run_profile();
+        let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+
+        let result = WasmPortBenchmark::measure_pair(&timer, &profile, "mul_add", 16).unwrap();
+
+        assert_eq!(clock.remaining(), 0);
+        assert_eq!(result.grouped_time, 3.0);
+        assert_eq!(result.interleaved_time, 6.0);
+        assert_eq!(result.ratio, 2.0);
+        assert_eq!(PortContentionFingerprint::category(result.ratio), "high_parallelism");
+    }
+
+    #[test]
+    fn unknown_pair_is_rejected() {
+        let clock = ScriptedClock::stepping(1.0);
+        let timing = TimingConfig::default();
+        let profile = single_run_profile();
+        let timer = AdaptiveTimer::new(&clock, &profile, &timing);
+
+        assert!(WasmPortBenchmark::measure_pair(&timer, &profile, "nop_nop", 16).is_err());
+        assert_eq!(clock.reads(), 0);
+    }
+}
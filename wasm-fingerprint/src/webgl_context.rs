@@ -0,0 +1,94 @@
+//! Ciclo de vida do contexto WebGL
+//!
+//! Os navegadores limitam o número de contextos WebGL vivos por página e
+//! descartam os mais antigos quando o limite é atingido. Uma coleta cria um
+//! único contexto, usado para os parâmetros e para todas as cenas, e o
+//! libera com `WEBGL_lose_context` ao final (no `Drop`), sem depender do
+//! coletor de lixo para liberar a canvas.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext, WebGlRenderingContext, WebglLoseContext};
+use crate::dom_utils::get_document;
+
+/// Lado da canvas usada para a coleta, em pixels
+const CANVAS_SIZE: u32 = 256;
+
+/// Contexto WebGL de uma coleta, liberado quando sai de escopo
+pub struct WebGLContext {
+    context: js_sys::Object,
+    name: &'static str,
+}
+
+impl WebGLContext {
+    /// Cria um contexto WebGL2, com fallback para WebGL1. O tipo do
+    /// contexto é verificado aqui, então `raw` sempre corresponde a `name`.
+    /// Retorna `None` quando nenhum dos dois está disponível.
+    pub fn acquire() -> Result<Option<Self>, JsValue> {
+        let document = get_document()?;
+
+        // Cada tentativa usa uma canvas nova: uma canvas que já criou um
+        // contexto não cria outro
+        for name in ["webgl2", "webgl"] {
+            let canvas = document.create_element("canvas")?
+                .dyn_into::<HtmlCanvasElement>()?;
+            canvas.set_width(CANVAS_SIZE);
+            canvas.set_height(CANVAS_SIZE);
+
+            let context = match canvas.get_context(name) {
+                Ok(Some(context)) => context,
+                _ => continue,
+            };
+            // Extensões de privacidade podem devolver um objeto que não é o
+            // contexto pedido; nesse caso segue para o próximo tipo
+            let expected = if name == "webgl2" {
+                context.dyn_ref::<WebGl2RenderingContext>().is_some()
+            } else {
+                context.dyn_ref::<WebGlRenderingContext>().is_some()
+            };
+            if expected {
+                return Ok(Some(WebGLContext { context, name }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// `"webgl2"` ou `"webgl"`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn is_webgl2(&self) -> bool {
+        self.name == "webgl2"
+    }
+
+    /// O contexto como o tipo concreto (`WebGl2RenderingContext` ou
+    /// `WebGlRenderingContext`)
+    pub fn raw(&self) -> &js_sys::Object {
+        &self.context
+    }
+
+    /// API do WebGL1, implementada também pelo `WebGL2RenderingContext`
+    pub fn gl(&self) -> &WebGlRenderingContext {
+        self.context.unchecked_ref()
+    }
+
+    /// Se o contexto foi perdido (reset da GPU, limite de contextos, aba em
+    /// segundo plano); resultados lidos depois disso não são confiáveis
+    pub fn is_lost(&self) -> bool {
+        self.gl().is_context_lost()
+    }
+}
+
+impl Drop for WebGLContext {
+    fn drop(&mut self) {
+        let gl = self.gl();
+        if gl.is_context_lost() {
+            return;
+        }
+        if let Ok(Some(extension)) = gl.get_extension("WEBGL_lose_context") {
+            extension.unchecked_into::<WebglLoseContext>().lose_context();
+        }
+    }
+}
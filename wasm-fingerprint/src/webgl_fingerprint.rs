@@ -1,434 +1,479 @@
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{
-    HtmlCanvasElement, WebGlRenderingContext, WebGl2RenderingContext,
-    WebGlProgram, WebGlShader
-};
-use sha2::{Sha256, Digest};
-use crate::dom_utils::get_document;
+use web_sys::{WebGlRenderingContext, WebGl2RenderingContext, WebGlShaderPrecisionFormat};
+use std::collections::BTreeMap;
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::utils::sha256_hex;
+use crate::webgl_context::WebGLContext;
+use crate::webgl_precision::PrecisionProbe;
+use crate::webgl_render::WebGLRenderer;
+use crate::{PrecisionProbeData, ShaderPrecision, WebGLFingerprintData};
 
 // Constantes para os parâmetros WebGL
 const UNMASKED_VENDOR_WEBGL: u32 = 0x9245;
 const UNMASKED_RENDERER_WEBGL: u32 = 0x9246;
 
+/// Tentativas de coleta antes de desistir de um contexto que é perdido
+const MAX_CONTEXT_ATTEMPTS: usize = 3;
+
+// Parâmetros de extensões
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
+
+/// Em que contextos um parâmetro pode ser consultado
+#[derive(Debug, Clone, Copy)]
+enum Availability {
+    /// WebGL1 e WebGL2
+    Core,
+    /// Apenas WebGL2
+    WebGl2,
+    /// Com a extensão habilitada, em qualquer contexto
+    Extension(&'static str),
+    /// Núcleo do WebGL2; no WebGL1, com a extensão habilitada (mesmo enum)
+    WebGl2OrExtension(&'static str),
+}
+
+/// Parâmetros consultados com `getParameter`. Todos aparecem no resultado
+/// dos dois contextos; os indisponíveis no contexto ficam com lista vazia,
+/// para que fingerprints WebGL1 e WebGL2 tenham as mesmas chaves.
+const PARAMETERS: [(&str, u32, Availability); 51] = [
+    // Limites do WebGL1
+    ("MAX_COMBINED_TEXTURE_IMAGE_UNITS", WebGlRenderingContext::MAX_COMBINED_TEXTURE_IMAGE_UNITS, Availability::Core),
+    ("MAX_CUBE_MAP_TEXTURE_SIZE", WebGlRenderingContext::MAX_CUBE_MAP_TEXTURE_SIZE, Availability::Core),
+    ("MAX_FRAGMENT_UNIFORM_VECTORS", WebGlRenderingContext::MAX_FRAGMENT_UNIFORM_VECTORS, Availability::Core),
+    ("MAX_RENDERBUFFER_SIZE", WebGlRenderingContext::MAX_RENDERBUFFER_SIZE, Availability::Core),
+    ("MAX_TEXTURE_IMAGE_UNITS", WebGlRenderingContext::MAX_TEXTURE_IMAGE_UNITS, Availability::Core),
+    ("MAX_TEXTURE_SIZE", WebGlRenderingContext::MAX_TEXTURE_SIZE, Availability::Core),
+    ("MAX_VARYING_VECTORS", WebGlRenderingContext::MAX_VARYING_VECTORS, Availability::Core),
+    ("MAX_VERTEX_ATTRIBS", WebGlRenderingContext::MAX_VERTEX_ATTRIBS, Availability::Core),
+    ("MAX_VERTEX_TEXTURE_IMAGE_UNITS", WebGlRenderingContext::MAX_VERTEX_TEXTURE_IMAGE_UNITS, Availability::Core),
+    ("MAX_VERTEX_UNIFORM_VECTORS", WebGlRenderingContext::MAX_VERTEX_UNIFORM_VECTORS, Availability::Core),
+    ("MAX_VIEWPORT_DIMS", WebGlRenderingContext::MAX_VIEWPORT_DIMS, Availability::Core),
+    // Faixas e profundidade de bits do framebuffer padrão
+    ("ALIASED_LINE_WIDTH_RANGE", WebGlRenderingContext::ALIASED_LINE_WIDTH_RANGE, Availability::Core),
+    ("ALIASED_POINT_SIZE_RANGE", WebGlRenderingContext::ALIASED_POINT_SIZE_RANGE, Availability::Core),
+    ("RED_BITS", WebGlRenderingContext::RED_BITS, Availability::Core),
+    ("GREEN_BITS", WebGlRenderingContext::GREEN_BITS, Availability::Core),
+    ("BLUE_BITS", WebGlRenderingContext::BLUE_BITS, Availability::Core),
+    ("ALPHA_BITS", WebGlRenderingContext::ALPHA_BITS, Availability::Core),
+    ("DEPTH_BITS", WebGlRenderingContext::DEPTH_BITS, Availability::Core),
+    ("STENCIL_BITS", WebGlRenderingContext::STENCIL_BITS, Availability::Core),
+    ("SUBPIXEL_BITS", WebGlRenderingContext::SUBPIXEL_BITS, Availability::Core),
+    ("SAMPLE_BUFFERS", WebGlRenderingContext::SAMPLE_BUFFERS, Availability::Core),
+    ("SAMPLES", WebGlRenderingContext::SAMPLES, Availability::Core),
+    // Limites do WebGL2; `MAX_DRAW_BUFFERS` e `MAX_COLOR_ATTACHMENTS`
+    // vêm de `WEBGL_draw_buffers` no WebGL1
+    ("MAX_3D_TEXTURE_SIZE", WebGl2RenderingContext::MAX_3D_TEXTURE_SIZE, Availability::WebGl2),
+    ("MAX_ARRAY_TEXTURE_LAYERS", WebGl2RenderingContext::MAX_ARRAY_TEXTURE_LAYERS, Availability::WebGl2),
+    ("MAX_CLIENT_WAIT_TIMEOUT_WEBGL", WebGl2RenderingContext::MAX_CLIENT_WAIT_TIMEOUT_WEBGL, Availability::WebGl2),
+    ("MAX_COLOR_ATTACHMENTS", WebGl2RenderingContext::MAX_COLOR_ATTACHMENTS, Availability::WebGl2OrExtension("WEBGL_draw_buffers")),
+    ("MAX_COMBINED_FRAGMENT_UNIFORM_COMPONENTS", WebGl2RenderingContext::MAX_COMBINED_FRAGMENT_UNIFORM_COMPONENTS, Availability::WebGl2),
+    ("MAX_COMBINED_UNIFORM_BLOCKS", WebGl2RenderingContext::MAX_COMBINED_UNIFORM_BLOCKS, Availability::WebGl2),
+    ("MAX_COMBINED_VERTEX_UNIFORM_COMPONENTS", WebGl2RenderingContext::MAX_COMBINED_VERTEX_UNIFORM_COMPONENTS, Availability::WebGl2),
+    ("MAX_DRAW_BUFFERS", WebGl2RenderingContext::MAX_DRAW_BUFFERS, Availability::WebGl2OrExtension("WEBGL_draw_buffers")),
+    ("MAX_ELEMENTS_INDICES", WebGl2RenderingContext::MAX_ELEMENTS_INDICES, Availability::WebGl2),
+    ("MAX_ELEMENTS_VERTICES", WebGl2RenderingContext::MAX_ELEMENTS_VERTICES, Availability::WebGl2),
+    ("MAX_ELEMENT_INDEX", WebGl2RenderingContext::MAX_ELEMENT_INDEX, Availability::WebGl2),
+    ("MAX_FRAGMENT_INPUT_COMPONENTS", WebGl2RenderingContext::MAX_FRAGMENT_INPUT_COMPONENTS, Availability::WebGl2),
+    ("MAX_FRAGMENT_UNIFORM_BLOCKS", WebGl2RenderingContext::MAX_FRAGMENT_UNIFORM_BLOCKS, Availability::WebGl2),
+    ("MAX_FRAGMENT_UNIFORM_COMPONENTS", WebGl2RenderingContext::MAX_FRAGMENT_UNIFORM_COMPONENTS, Availability::WebGl2),
+    ("MAX_PROGRAM_TEXEL_OFFSET", WebGl2RenderingContext::MAX_PROGRAM_TEXEL_OFFSET, Availability::WebGl2),
+    ("MAX_SAMPLES", WebGl2RenderingContext::MAX_SAMPLES, Availability::WebGl2),
+    ("MAX_SERVER_WAIT_TIMEOUT", WebGl2RenderingContext::MAX_SERVER_WAIT_TIMEOUT, Availability::WebGl2),
+    ("MAX_TEXTURE_LOD_BIAS", WebGl2RenderingContext::MAX_TEXTURE_LOD_BIAS, Availability::WebGl2),
+    ("MAX_TRANSFORM_FEEDBACK_INTERLEAVED_COMPONENTS", WebGl2RenderingContext::MAX_TRANSFORM_FEEDBACK_INTERLEAVED_COMPONENTS, Availability::WebGl2),
+    ("MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS", WebGl2RenderingContext::MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS, Availability::WebGl2),
+    ("MAX_TRANSFORM_FEEDBACK_SEPARATE_COMPONENTS", WebGl2RenderingContext::MAX_TRANSFORM_FEEDBACK_SEPARATE_COMPONENTS, Availability::WebGl2),
+    ("MAX_UNIFORM_BLOCK_SIZE", WebGl2RenderingContext::MAX_UNIFORM_BLOCK_SIZE, Availability::WebGl2),
+    ("MAX_UNIFORM_BUFFER_BINDINGS", WebGl2RenderingContext::MAX_UNIFORM_BUFFER_BINDINGS, Availability::WebGl2),
+    ("MAX_VARYING_COMPONENTS", WebGl2RenderingContext::MAX_VARYING_COMPONENTS, Availability::WebGl2),
+    ("MAX_VERTEX_OUTPUT_COMPONENTS", WebGl2RenderingContext::MAX_VERTEX_OUTPUT_COMPONENTS, Availability::WebGl2),
+    ("MAX_VERTEX_UNIFORM_BLOCKS", WebGl2RenderingContext::MAX_VERTEX_UNIFORM_BLOCKS, Availability::WebGl2),
+    ("MAX_VERTEX_UNIFORM_COMPONENTS", WebGl2RenderingContext::MAX_VERTEX_UNIFORM_COMPONENTS, Availability::WebGl2),
+    ("MIN_PROGRAM_TEXEL_OFFSET", WebGl2RenderingContext::MIN_PROGRAM_TEXEL_OFFSET, Availability::WebGl2),
+    // Parâmetros de extensões
+    ("MAX_TEXTURE_MAX_ANISOTROPY_EXT", MAX_TEXTURE_MAX_ANISOTROPY_EXT, Availability::Extension("EXT_texture_filter_anisotropic")),
+];
+
+/// Tipos de shader e de precisão consultados em `getShaderPrecisionFormat`
+const SHADER_TYPES: [(&str, u32); 2] = [
+    ("vertex", WebGlRenderingContext::VERTEX_SHADER),
+    ("fragment", WebGlRenderingContext::FRAGMENT_SHADER),
+];
+const PRECISION_TYPES: [(&str, u32); 6] = [
+    ("low_float", WebGlRenderingContext::LOW_FLOAT),
+    ("medium_float", WebGlRenderingContext::MEDIUM_FLOAT),
+    ("high_float", WebGlRenderingContext::HIGH_FLOAT),
+    ("low_int", WebGlRenderingContext::LOW_INT),
+    ("medium_int", WebGlRenderingContext::MEDIUM_INT),
+    ("high_int", WebGlRenderingContext::HIGH_INT),
+];
+
 /// Função exportada para JavaScript - WebGL Fingerprinting
 #[wasm_bindgen]
 pub fn get_webgl_fingerprint() -> Result<String, JsValue> {
     WebGLFingerprint::collect()
 }
 
-pub struct WebGLFingerprint;
+/// Função exportada para JavaScript - parâmetros WebGL completos
+#[wasm_bindgen]
+pub fn get_webgl_parameters() -> Result<JsValue, JsValue> {
+    let data = WebGLFingerprint::collect_if_supported()?
+        .ok_or_else(|| JsValue::from_str("WebGL não suportado"))?;
+    serde_wasm_bindgen::to_value(&data).map_err(JsValue::from)
+}
 
-impl WebGLFingerprint {
-    pub fn collect() -> Result<String, JsValue> {
-        let document = get_document()?;
+/// Consultas de parâmetros comuns aos contextos WebGL1 e WebGL2
+trait GlParameters {
+    fn parameter(&self, pname: u32) -> Result<JsValue, JsValue>;
+    fn extension(&self, name: &str) -> Result<Option<js_sys::Object>, JsValue>;
+    fn supported_extensions(&self) -> Option<js_sys::Array>;
+    fn precision_format(&self, shader_type: u32, precision_type: u32) -> Option<WebGlShaderPrecisionFormat>;
+}
 
-        let canvas = document.create_element("canvas")?
-            .dyn_into::<HtmlCanvasElement>()?;
+impl GlParameters for WebGlRenderingContext {
+    fn parameter(&self, pname: u32) -> Result<JsValue, JsValue> {
+        self.get_parameter(pname)
+    }
 
-        canvas.set_width(256);
-        canvas.set_height(256);
+    fn extension(&self, name: &str) -> Result<Option<js_sys::Object>, JsValue> {
+        self.get_extension(name)
+    }
 
-        // Tentar WebGL2 primeiro, fallback para WebGL1
-        let mut fingerprint = String::new();
+    fn supported_extensions(&self) -> Option<js_sys::Array> {
+        self.get_supported_extensions()
+    }
 
-        // Tentar WebGL2
-        if let Ok(Some(context)) = canvas.get_context("webgl2") {
-            if let Ok(gl) = context.dyn_into::<WebGl2RenderingContext>() {
-                fingerprint = Self::collect_webgl2_info(&gl)?;
-            }
-        } else if let Ok(Some(context)) = canvas.get_context("webgl") {
-            // Fallback para WebGL1
-            if let Ok(gl) = context.dyn_into::<WebGlRenderingContext>() {
-                fingerprint = Self::collect_webgl1_info(&gl)?;
-            }
-        } else {
-            return Err(JsValue::from_str("WebGL não suportado"));
-        }
+    fn precision_format(&self, shader_type: u32, precision_type: u32) -> Option<WebGlShaderPrecisionFormat> {
+        self.get_shader_precision_format(shader_type, precision_type)
+    }
+}
+
+impl GlParameters for WebGl2RenderingContext {
+    fn parameter(&self, pname: u32) -> Result<JsValue, JsValue> {
+        self.get_parameter(pname)
+    }
 
-        // Gerar hash SHA-256 do fingerprint
-        let mut hasher = Sha256::new();
-        hasher.update(fingerprint.as_bytes());
-        let hash_result = hasher.finalize();
+    fn extension(&self, name: &str) -> Result<Option<js_sys::Object>, JsValue> {
+        self.get_extension(name)
+    }
 
-        Ok(format!("{:x}", hash_result))
+    fn supported_extensions(&self) -> Option<js_sys::Array> {
+        self.get_supported_extensions()
     }
 
-    fn collect_webgl1_info(gl: &WebGlRenderingContext) -> Result<String, JsValue> {
+    fn precision_format(&self, shader_type: u32, precision_type: u32) -> Option<WebGlShaderPrecisionFormat> {
+        self.get_shader_precision_format(shader_type, precision_type)
+    }
+}
 
-        let mut fingerprint = String::new();
+pub struct WebGLFingerprint;
 
-        // Collect renderer and vendor info
-        let debug_info = gl.get_extension("WEBGL_debug_renderer_info")?;
-        if debug_info.is_some() {
-            let vendor = gl.get_parameter(UNMASKED_VENDOR_WEBGL)?;
-            let renderer = gl.get_parameter(UNMASKED_RENDERER_WEBGL)?;
+impl WebGLFingerprint {
+    pub fn collect() -> Result<String, JsValue> {
+        Self::collect_if_supported()?
+            .map(|data| data.hash)
+            .ok_or_else(|| JsValue::from_str("WebGL não suportado"))
+    }
 
-            fingerprint.push_str(&format!("vendor:{:?},renderer:{:?},",
-                vendor.as_string().unwrap_or_default(),
-                renderer.as_string().unwrap_or_default()));
-        } else {
-            // Fallback para parâmetros mascarados
-            let vendor = gl.get_parameter(WebGlRenderingContext::VENDOR)?;
-            let renderer = gl.get_parameter(WebGlRenderingContext::RENDERER)?;
-            fingerprint.push_str(&format!("vendor_masked:{:?},renderer_masked:{:?},",
-                vendor.as_string().unwrap_or_default(),
-                renderer.as_string().unwrap_or_default()));
+    /// Retorna `None` quando nenhum contexto WebGL está disponível. Se o
+    /// contexto é perdido durante a coleta, ela é refeita em um contexto
+    /// novo, até `MAX_CONTEXT_ATTEMPTS` vezes.
+    pub fn collect_if_supported() -> Result<Option<WebGLFingerprintData>, JsValue> {
+        for _ in 0..MAX_CONTEXT_ATTEMPTS {
+            let context = match WebGLContext::acquire()? {
+                Some(context) => context,
+                None => return Ok(None),
+            };
+            if let Some(data) = Self::collect_from(&context)? {
+                return Ok(Some(data));
+            }
         }
 
-        // Get supported extensions
-        let ext_count = if let Some(extensions) = gl.get_supported_extensions() {
-            extensions.length()
+        Err(JsValue::from_str("WebGL context lost"))
+    }
+
+    /// Coleta parâmetros, cenas e sondas de precisão em um único contexto.
+    /// Retorna `None` se o contexto foi perdido em algum momento da coleta.
+    fn collect_from(context: &WebGLContext) -> Result<Option<WebGLFingerprintData>, JsValue> {
+        // `WebGLContext::acquire` já verificou o tipo do contexto
+        let mut data = if context.is_webgl2() {
+            Self::collect_parameters(context.raw().unchecked_ref::<WebGl2RenderingContext>(), context.name())?
         } else {
-            0
+            Self::collect_parameters(context.gl(), context.name())?
         };
-        fingerprint.push_str(&format!("extensions:{},", ext_count));
-
-        // Max texture size
-        let max_texture = gl.get_parameter(WebGlRenderingContext::MAX_TEXTURE_SIZE)?;
-        fingerprint.push_str(&format!("max_texture:{:?},", max_texture));
+        if context.is_lost() {
+            return Ok(None);
+        }
 
-        // Max viewport dimensions
-        let max_viewport = gl.get_parameter(WebGlRenderingContext::MAX_VIEWPORT_DIMS)?;
-        fingerprint.push_str(&format!("viewport:{:?},", max_viewport));
+        let scenes = WebGLRenderer::new(context.gl(), context.is_webgl2()).render_all();
+        let probes = PrecisionProbe::new(context.gl(), context.is_webgl2()).run();
+        if context.is_lost() {
+            return Ok(None);
+        }
+        data.scene_hashes = scenes.hashes;
+        data.render_failures = scenes.failures;
+        match probes {
+            Ok(probes) => data.precision_probes = probes,
+            Err(reason) => {
+                data.render_failures.insert("precision_probes".to_string(), reason);
+            }
+        }
 
-        // Render a test scene
-        let scene_data = Self::render_test_scene(&gl)?;
-        fingerprint.push_str(&format!("scene:{}", scene_data));
+        data.hash = Self::hash(&data);
+        Ok(Some(data))
+    }
 
-        Ok(fingerprint)
+    /// Hash SHA-256 da serialização JSON de todos os campos (exceto o
+    /// próprio hash); os mapas são ordenados, então a serialização é estável
+    pub fn hash(data: &WebGLFingerprintData) -> String {
+        let mut data = data.clone();
+        data.hash.clear();
+        sha256_hex(&serde_json::to_string(&data).unwrap_or_default())
     }
 
-    fn render_test_scene(gl: &WebGlRenderingContext) -> Result<String, JsValue> {
-        // Vertex shader
-        let vert_shader = Self::compile_shader(
-            &gl,
-            WebGlRenderingContext::VERTEX_SHADER,
-            r#"
-            attribute vec2 aVertexPosition;
-            uniform mat4 uModelViewMatrix;
-            uniform mat4 uProjectionMatrix;
-            varying highp vec2 vTextureCoord;
-
-            void main(void) {
-                gl_Position = uProjectionMatrix * uModelViewMatrix * vec4(aVertexPosition, 0.0, 1.0);
-                vTextureCoord = aVertexPosition;
-            }
-            "#,
-        )?;
-
-        // Fragment shader with precision-dependent calculations
-        let frag_shader = Self::compile_shader(
-            &gl,
-            WebGlRenderingContext::FRAGMENT_SHADER,
-            r#"
-            precision mediump float;
-            varying highp vec2 vTextureCoord;
-            uniform float uTime;
-
-            void main(void) {
-                float r = abs(sin(vTextureCoord.x * 10.0 + uTime));
-                float g = abs(cos(vTextureCoord.y * 10.0 + uTime));
-                float b = abs(sin((vTextureCoord.x + vTextureCoord.y) * 5.0));
-                gl_FragColor = vec4(r, g, b, 1.0);
+    fn collect_parameters<G: GlParameters>(gl: &G, context: &str) -> Result<WebGLFingerprintData, JsValue> {
+        let webgl2 = context == "webgl2";
+        let string_parameter = |pname: u32| -> Result<String, JsValue> {
+            Ok(gl.parameter(pname)?.as_string().unwrap_or_default())
+        };
+
+        // Vendor e renderer reais só estão disponíveis com a extensão de debug
+        let (unmasked_vendor, unmasked_renderer) = if gl.extension("WEBGL_debug_renderer_info")?.is_some() {
+            (string_parameter(UNMASKED_VENDOR_WEBGL)?, string_parameter(UNMASKED_RENDERER_WEBGL)?)
+        } else {
+            (String::new(), String::new())
+        };
+
+        let mut extensions: Vec<String> = gl
+            .supported_extensions()
+            .map(|list| list.iter().filter_map(|name| name.as_string()).collect())
+            .unwrap_or_default();
+        extensions.sort();
+
+        let mut precisions = BTreeMap::new();
+        for (shader_name, shader_type) in SHADER_TYPES {
+            for (precision_name, precision_type) in PRECISION_TYPES {
+                if let Some(format) = gl.precision_format(shader_type, precision_type) {
+                    precisions.insert(
+                        format!("{}_{}", shader_name, precision_name),
+                        ShaderPrecision {
+                            range_min: format.range_min(),
+                            range_max: format.range_max(),
+                            precision: format.precision(),
+                        },
+                    );
+                }
             }
-            "#,
-        )?;
-
-        let program = Self::link_program(&gl, &vert_shader, &frag_shader)?;
-        gl.use_program(Some(&program));
-
-        // Create vertex buffer
-        let vertices: [f32; 8] = [
-            -1.0, -1.0,
-             1.0, -1.0,
-            -1.0,  1.0,
-             1.0,  1.0,
-        ];
-
-        let vertex_buffer = gl.create_buffer().ok_or("Failed to create buffer")?;
-        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&vertex_buffer));
-
-        unsafe {
-            let vert_array = js_sys::Float32Array::view(&vertices);
-            gl.buffer_data_with_array_buffer_view(
-                WebGlRenderingContext::ARRAY_BUFFER,
-                &vert_array,
-                WebGlRenderingContext::STATIC_DRAW,
-            );
         }
 
-        // Set up attributes and uniforms
-        let position_attr = gl.get_attrib_location(&program, "aVertexPosition") as u32;
-        gl.vertex_attrib_pointer_with_i32(
-            position_attr, 2, WebGlRenderingContext::FLOAT, false, 0, 0
-        );
-        gl.enable_vertex_attrib_array(position_attr);
-
-        // Clear and draw
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
-        gl.draw_arrays(WebGlRenderingContext::TRIANGLE_STRIP, 0, 4);
-
-        // Read pixels for fingerprinting
-        let mut pixels = vec![0u8; 4 * 16 * 16]; // Sample 16x16 region
-        gl.read_pixels_with_opt_u8_array(
-            120, 120, 16, 16,
-            WebGlRenderingContext::RGBA,
-            WebGlRenderingContext::UNSIGNED_BYTE,
-            Some(&mut pixels),
-        )?;
-
-        // Convert pixels to hash-like string
-        let pixel_hash = pixels.iter()
-            .take(64)
-            .map(|p| format!("{:02x}", p))
-            .collect::<String>();
-
-        Ok(pixel_hash)
-    }
+        // `getExtension` também habilita a extensão, o que é necessário antes
+        // de consultar os seus parâmetros
+        let mut enabled = BTreeMap::new();
+        let mut extension_enabled = |name: &'static str| -> Result<bool, JsValue> {
+            if let Some(&on) = enabled.get(name) {
+                return Ok(on);
+            }
+            let on = gl.extension(name)?.is_some();
+            enabled.insert(name, on);
+            Ok(on)
+        };
 
-    fn collect_webgl2_info(gl: &WebGl2RenderingContext) -> Result<String, JsValue> {
-        let mut fingerprint = String::new();
+        let mut parameters = BTreeMap::new();
+        for (name, pname, availability) in PARAMETERS {
+            let available = match availability {
+                Availability::Core => true,
+                Availability::WebGl2 => webgl2,
+                Availability::Extension(extension) => extension_enabled(extension)?,
+                Availability::WebGl2OrExtension(extension) => webgl2 || extension_enabled(extension)?,
+            };
+            let values = if available {
+                Self::numeric_values(&gl.parameter(pname)?)
+            } else {
+                Vec::new()
+            };
+            parameters.insert(name.to_string(), values);
+        }
 
-        // WebGL version
-        fingerprint.push_str("webgl2:true,");
+        Ok(WebGLFingerprintData {
+            hash: String::new(),
+            vendor: string_parameter(WebGlRenderingContext::VENDOR)?,
+            renderer: string_parameter(WebGlRenderingContext::RENDERER)?,
+            unmasked_vendor,
+            unmasked_renderer,
+            context: context.to_string(),
+            version: string_parameter(WebGlRenderingContext::VERSION)?,
+            shading_language_version: string_parameter(WebGlRenderingContext::SHADING_LANGUAGE_VERSION)?,
+            extensions,
+            precisions,
+            parameters,
+            scene_hashes: BTreeMap::new(),
+            render_failures: BTreeMap::new(),
+            precision_probes: PrecisionProbeData::default(),
+        })
+    }
 
-        // Vendor e Renderer
-        let debug_info = gl.get_extension("WEBGL_debug_renderer_info")?;
-        if debug_info.is_some() {
-            let vendor = gl.get_parameter(UNMASKED_VENDOR_WEBGL)?;
-            let renderer = gl.get_parameter(UNMASKED_RENDERER_WEBGL)?;
+    /// Valor de `getParameter` como lista de números (ver
+    /// `ParameterValue::numbers`)
+    fn numeric_values(value: &JsValue) -> Vec<f64> {
+        ParameterValue::from_js(value).numbers()
+    }
+}
 
-            fingerprint.push_str(&format!("vendor:{:?},renderer:{:?},",
-                vendor.as_string().unwrap_or_default(),
-                renderer.as_string().unwrap_or_default()));
-        }
+/// Valor retornado por `getParameter`, já lido do JavaScript
+#[derive(Debug, Clone, PartialEq)]
+enum ParameterValue {
+    Number(f64),
+    Flag(bool),
+    /// Array ou array tipado; `None` nas posições não numéricas
+    List(Vec<Option<f64>>),
+    /// `null` (parâmetro não suportado) ou outro tipo
+    Other,
+}
 
-        // Precisão de shaders (WebGL2 specific)
-        if let Some(vertex_precision) = gl.get_shader_precision_format(
-            WebGl2RenderingContext::VERTEX_SHADER,
-            WebGl2RenderingContext::HIGH_FLOAT
-        ) {
-            fingerprint.push_str(&format!("vertex_precision:{},", vertex_precision.precision()));
+impl ParameterValue {
+    fn from_js(value: &JsValue) -> Self {
+        if let Some(number) = value.as_f64() {
+            ParameterValue::Number(number)
+        } else if let Some(flag) = value.as_bool() {
+            ParameterValue::Flag(flag)
+        } else if value.is_object() {
+            ParameterValue::List(js_sys::Array::from(value).iter().map(|v| v.as_f64()).collect())
+        } else {
+            ParameterValue::Other
         }
+    }
 
-        if let Some(fragment_precision) = gl.get_shader_precision_format(
-            WebGl2RenderingContext::FRAGMENT_SHADER,
-            WebGl2RenderingContext::HIGH_FLOAT
-        ) {
-            fingerprint.push_str(&format!("fragment_precision:{},", fragment_precision.precision()));
+    /// Escalares viram um elemento, arrays tipados (ex.: `MAX_VIEWPORT_DIMS`)
+    /// vários; `null` vira lista vazia
+    fn numbers(&self) -> Vec<f64> {
+        match self {
+            ParameterValue::Number(number) => vec![*number],
+            ParameterValue::Flag(flag) => vec![if *flag { 1.0 } else { 0.0 }],
+            ParameterValue::List(values) => values.iter().flatten().copied().collect(),
+            ParameterValue::Other => Vec::new(),
         }
+    }
+}
 
-        // Parâmetros WebGL2 específicos
-        let max_3d_texture = gl.get_parameter(WebGl2RenderingContext::MAX_3D_TEXTURE_SIZE)?;
-        fingerprint.push_str(&format!("max_3d_texture:{:?},", max_3d_texture));
+impl FingerprintSource for WebGLFingerprint {
+    fn name(&self) -> &str {
+        "webgl"
+    }
 
-        let max_array_texture = gl.get_parameter(WebGl2RenderingContext::MAX_ARRAY_TEXTURE_LAYERS)?;
-        fingerprint.push_str(&format!("max_array_layers:{:?},", max_array_texture));
+    fn stability(&self) -> Stability {
+        Stability::Stable
+    }
 
-        // Extensões
-        let ext_count = gl.get_supported_extensions()
-            .and_then(|x| Some(x))
-            .unwrap_or_else(|| js_sys::Array::new())
-            .length();
-        fingerprint.push_str(&format!("extensions:{},", ext_count));
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::WebGL
+    }
 
-        // Renderizar cena de teste WebGL2
-        let scene_data = Self::render_test_scene_gl2(&gl)?;
-        fingerprint.push_str(&format!("scene:{}", scene_data));
+    fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async {
+            let webgl = WebGLFingerprint::collect_if_supported()?
+                .ok_or(SourceError::Unsupported)?;
+            Ok(SourceOutput::WebGL(webgl))
+        })
+    }
+}
 
-        Ok(fingerprint)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Parâmetros de `PARAMETERS` que a especificação do WebGL 1.0 já
+    /// define para `getParameter`
+    const WEBGL1_PARAMETERS: [&str; 22] = [
+        "MAX_COMBINED_TEXTURE_IMAGE_UNITS", "MAX_CUBE_MAP_TEXTURE_SIZE",
+        "MAX_FRAGMENT_UNIFORM_VECTORS", "MAX_RENDERBUFFER_SIZE", "MAX_TEXTURE_IMAGE_UNITS",
+        "MAX_TEXTURE_SIZE", "MAX_VARYING_VECTORS", "MAX_VERTEX_ATTRIBS",
+        "MAX_VERTEX_TEXTURE_IMAGE_UNITS", "MAX_VERTEX_UNIFORM_VECTORS", "MAX_VIEWPORT_DIMS",
+        "ALIASED_LINE_WIDTH_RANGE", "ALIASED_POINT_SIZE_RANGE", "RED_BITS", "GREEN_BITS",
+        "BLUE_BITS", "ALPHA_BITS", "DEPTH_BITS", "STENCIL_BITS", "SUBPIXEL_BITS",
+        "SAMPLE_BUFFERS", "SAMPLES",
+    ];
+
+    fn parameters() -> WebGLFingerprintData {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("MAX_TEXTURE_SIZE".to_string(), vec![16384.0]);
+        parameters.insert("MAX_VIEWPORT_DIMS".to_string(), vec![32767.0, 32767.0]);
+
+        WebGLFingerprintData {
+            vendor: "WebKit".to_string(),
+            renderer: "WebKit WebGL".to_string(),
+            context: "webgl2".to_string(),
+            extensions: vec!["EXT_color_buffer_float".to_string(), "OES_texture_float_linear".to_string()],
+            parameters,
+            ..Default::default()
+        }
     }
 
+    #[test]
+    fn hash_covers_parameters_but_not_itself() {
+        let data = parameters();
+        let mut hashed = data.clone();
+        hashed.hash = WebGLFingerprint::hash(&data);
 
-    fn compile_shader(
-        gl: &WebGlRenderingContext,
-        shader_type: u32,
-        source: &str,
-    ) -> Result<WebGlShader, JsValue> {
-        let shader = gl
-            .create_shader(shader_type)
-            .ok_or("Unable to create shader")?;
+        assert_eq!(WebGLFingerprint::hash(&hashed), hashed.hash);
 
-        gl.shader_source(&shader, source);
-        gl.compile_shader(&shader);
+        let mut fewer_extensions = data.clone();
+        fewer_extensions.extensions.pop();
+        assert_ne!(WebGLFingerprint::hash(&fewer_extensions), hashed.hash);
 
-        if gl.get_shader_parameter(&shader, WebGlRenderingContext::COMPILE_STATUS)
-            .as_bool()
-            .unwrap_or(false)
-        {
-            Ok(shader)
-        } else {
-            Err(JsValue::from_str(
-                &gl.get_shader_info_log(&shader)
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            ))
-        }
+        let mut other_limit = data;
+        other_limit.parameters.insert("MAX_TEXTURE_SIZE".to_string(), vec![8192.0]);
+        assert_ne!(WebGLFingerprint::hash(&other_limit), hashed.hash);
     }
 
-    fn link_program(
-        gl: &WebGlRenderingContext,
-        vert_shader: &WebGlShader,
-        frag_shader: &WebGlShader,
-    ) -> Result<WebGlProgram, JsValue> {
-        let program = gl
-            .create_program()
-            .ok_or("Unable to create program")?;
-
-        gl.attach_shader(&program, vert_shader);
-        gl.attach_shader(&program, frag_shader);
-        gl.link_program(&program);
-
-        if gl.get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
-            .as_bool()
-            .unwrap_or(false)
-        {
-            Ok(program)
-        } else {
-            Err(JsValue::from_str(
-                &gl.get_program_info_log(&program)
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            ))
-        }
-    }
+    #[test]
+    fn effective_gpu_falls_back_to_masked_values() {
+        let mut data = parameters();
+        assert_eq!(data.effective_vendor(), "WebKit");
+        assert_eq!(data.effective_renderer(), "WebKit WebGL");
 
-    // Helper functions for WebGL2
-    fn compile_shader_gl2(
-        gl: &WebGl2RenderingContext,
-        shader_type: u32,
-        source: &str,
-    ) -> Result<WebGlShader, JsValue> {
-        let shader = gl
-            .create_shader(shader_type)
-            .ok_or("Unable to create shader")?;
-
-        gl.shader_source(&shader, source);
-        gl.compile_shader(&shader);
-
-        if gl.get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS)
-            .as_bool()
-            .unwrap_or(false)
-        {
-            Ok(shader)
-        } else {
-            Err(JsValue::from_str(
-                &gl.get_shader_info_log(&shader)
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            ))
-        }
+        data.unmasked_vendor = "Example GPU Vendor".to_string();
+        data.unmasked_renderer = "Example GPU 1000".to_string();
+        assert_eq!(data.effective_vendor(), "Example GPU Vendor");
+        assert_eq!(data.effective_renderer(), "Example GPU 1000");
     }
 
-    fn link_program_gl2(
-        gl: &WebGl2RenderingContext,
-        vert_shader: &WebGlShader,
-        frag_shader: &WebGlShader,
-    ) -> Result<WebGlProgram, JsValue> {
-        let program = gl
-            .create_program()
-            .ok_or("Unable to create program")?;
-
-        gl.attach_shader(&program, vert_shader);
-        gl.attach_shader(&program, frag_shader);
-        gl.link_program(&program);
-
-        if gl.get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
-            .as_bool()
-            .unwrap_or(false)
-        {
-            Ok(program)
-        } else {
-            Err(JsValue::from_str(
-                &gl.get_program_info_log(&program)
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            ))
-        }
+    #[test]
+    fn parameters_are_unique() {
+        let names: BTreeSet<_> = PARAMETERS.iter().map(|(name, _, _)| name).collect();
+        let enums: BTreeSet<_> = PARAMETERS.iter().map(|(_, pname, _)| pname).collect();
+
+        assert_eq!(names.len(), PARAMETERS.len());
+        assert_eq!(enums.len(), PARAMETERS.len());
     }
 
-    fn render_test_scene_gl2(gl: &WebGl2RenderingContext) -> Result<String, JsValue> {
-        // Use WebGL2-specific shaders with version declaration
-        let vert_shader = Self::compile_shader_gl2(
-            &gl,
-            WebGl2RenderingContext::VERTEX_SHADER,
-            r#"#version 300 es
-            in vec2 aVertexPosition;
-            uniform mat4 uModelViewMatrix;
-            uniform mat4 uProjectionMatrix;
-            out highp vec2 vTextureCoord;
-
-            void main(void) {
-                gl_Position = uProjectionMatrix * uModelViewMatrix * vec4(aVertexPosition, 0.0, 1.0);
-                vTextureCoord = aVertexPosition;
-            }
-            "#,
-        )?;
-
-        let frag_shader = Self::compile_shader_gl2(
-            &gl,
-            WebGl2RenderingContext::FRAGMENT_SHADER,
-            r#"#version 300 es
-            precision mediump float;
-            in highp vec2 vTextureCoord;
-            uniform float uTime;
-            out vec4 fragColor;
-
-            void main(void) {
-                float r = abs(sin(vTextureCoord.x * 10.0 + uTime));
-                float g = abs(cos(vTextureCoord.y * 10.0 + uTime));
-                float b = abs(sin((vTextureCoord.x + vTextureCoord.y) * 5.0));
-                fragColor = vec4(r, g, b, 1.0);
+    #[test]
+    fn only_webgl1_parameters_are_core() {
+        for (name, _, availability) in PARAMETERS {
+            let webgl1 = WEBGL1_PARAMETERS.contains(&name);
+            match availability {
+                Availability::Core => assert!(webgl1, "{} não existe no WebGL1", name),
+                Availability::WebGl2 | Availability::WebGl2OrExtension(_) => {
+                    assert!(!webgl1, "{} existe no WebGL1", name)
+                }
+                Availability::Extension(_) => assert!(name.ends_with("_EXT"), "{}", name),
             }
-            "#,
-        )?;
-
-        let program = Self::link_program_gl2(&gl, &vert_shader, &frag_shader)?;
-        gl.use_program(Some(&program));
-
-        // Create vertex buffer
-        let vertices: [f32; 8] = [
-            -1.0, -1.0,
-             1.0, -1.0,
-            -1.0,  1.0,
-             1.0,  1.0,
-        ];
-
-        let vertex_buffer = gl.create_buffer().ok_or("Failed to create buffer")?;
-        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&vertex_buffer));
-
-        unsafe {
-            let vert_array = js_sys::Float32Array::view(&vertices);
-            gl.buffer_data_with_array_buffer_view(
-                WebGl2RenderingContext::ARRAY_BUFFER,
-                &vert_array,
-                WebGl2RenderingContext::STATIC_DRAW,
-            );
         }
+        let core = PARAMETERS.iter().filter(|(_, _, a)| matches!(a, Availability::Core)).count();
+        assert_eq!(core, WEBGL1_PARAMETERS.len());
+    }
 
-        let position_attr = gl.get_attrib_location(&program, "aVertexPosition") as u32;
-        gl.vertex_attrib_pointer_with_i32(
-            position_attr, 2, WebGl2RenderingContext::FLOAT, false, 0, 0
+    #[test]
+    fn parameter_values_become_numbers() {
+        assert_eq!(ParameterValue::Number(16384.0).numbers(), vec![16384.0]);
+        assert_eq!(ParameterValue::Flag(true).numbers(), vec![1.0]);
+        assert_eq!(ParameterValue::Flag(false).numbers(), vec![0.0]);
+        assert_eq!(
+            ParameterValue::List(vec![Some(32767.0), Some(32767.0)]).numbers(),
+            vec![32767.0, 32767.0]
         );
-        gl.enable_vertex_attrib_array(position_attr);
-
-        // Clear and draw
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
-        gl.draw_arrays(WebGl2RenderingContext::TRIANGLE_STRIP, 0, 4);
-
-        // Read pixels
-        let mut pixels = vec![0u8; 4 * 16 * 16];
-        gl.read_pixels_with_opt_u8_array(
-            120, 120, 16, 16,
-            WebGl2RenderingContext::RGBA,
-            WebGl2RenderingContext::UNSIGNED_BYTE,
-            Some(&mut pixels),
-        )?;
-
-        let pixel_hash = pixels.iter()
-            .take(64)
-            .map(|p| format!("{:02x}", p))
-            .collect::<String>();
-
-        Ok(pixel_hash)
+        assert_eq!(ParameterValue::List(vec![Some(1.0), None]).numbers(), vec![1.0]);
+        assert!(ParameterValue::List(Vec::new()).numbers().is_empty());
+        assert!(ParameterValue::Other.numbers().is_empty());
     }
-}
\ No newline at end of file
+}
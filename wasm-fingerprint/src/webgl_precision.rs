@@ -0,0 +1,363 @@
+//! Sondas de precisão numérica dos shaders
+//!
+//! Um fragment shader avalia uma expressão por pixel (funções
+//! transcendentais em entradas extremas, denormais, NaN e infinito) e o
+//! resultado é lido de volta bit a bit. As implementações dessas funções
+//! diferem entre famílias de GPU e drivers, então os bits identificam a
+//! família mesmo quando `WEBGL_debug_renderer_info` está mascarado.
+//!
+//! Os valores são renderizados em uma textura float quando o contexto
+//! permite (`float32`, bits exatos). Caso contrário o shader empacota os
+//! bits IEEE 754 em um pixel RGBA8 (`rgba8`); esse empacotamento depende da
+//! própria aritmética da GPU, por isso as duas codificações não são
+//! comparadas entre si.
+
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlProgram, WebGlRenderingContext as GL, WebGlTexture};
+use crate::utils::sha256_hex;
+use crate::webgl_render::{describe, GlObjects};
+use crate::PrecisionProbeData;
+
+// Constantes do WebGL2
+const RGBA32F: i32 = 0x8814;
+
+/// Expressões avaliadas, uma por pixel. As entradas são construídas a
+/// partir dos uniforms `uZero` e `uOne` para que o compilador não calcule
+/// as expressões na CPU.
+const PROBES: [(&str, &str); 24] = [
+    ("sin_large", "sin(10000.0 * uOne)"),
+    ("sin_pi", "sin(3.14159265 * uOne)"),
+    ("cos_large", "cos(100000.0 * uOne)"),
+    ("tan_half_pi", "tan(1.5707963 * uOne)"),
+    ("exp_small", "exp(0.001 * uOne)"),
+    ("exp_large", "exp(80.0 * uOne)"),
+    ("exp_overflow", "exp(89.0 * uOne)"),
+    ("exp2_fraction", "exp2(0.1 * uOne)"),
+    ("log_tiny", "log(1.0e-30 * uOne)"),
+    ("log2_three", "log2(3.0 * uOne)"),
+    ("pow_fraction", "pow(2.7182817 * uOne, 3.3333333)"),
+    ("pow_negative_base", "pow(-2.0 * uOne, 2.0)"),
+    ("atan_steep", "atan(uOne, 1.0e-7)"),
+    ("atan_negative_zero", "atan(uZero, -uOne)"),
+    ("asin_edge", "asin(0.99999994 * uOne)"),
+    ("sqrt_two", "sqrt(2.0 * uOne)"),
+    ("inversesqrt_tiny", "inversesqrt(1.0e-20 * uOne)"),
+    ("fract_large", "fract(12345.6789 * uOne)"),
+    ("mod_negative", "mod(-7.5 * uOne, 2.0)"),
+    ("denormal_product", "(1.0e-30 * uOne) * 1.0e-10"),
+    ("denormal_quotient", "(1.17549435e-38 * uOne) / 4.0"),
+    ("nan", "uZero / uZero"),
+    ("nan_is_unordered", "(uZero / uZero) != (uZero / uZero) ? 1.0 : 0.0"),
+    ("infinity", "uOne / uZero"),
+];
+
+const VERTEX_SHADER: &str = r#"
+attribute vec2 aPosition;
+
+void main(void) {
+    gl_Position = vec4(aPosition, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_HEADER: &str = r#"
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+uniform float uZero;
+uniform float uOne;
+"#;
+
+/// Empacota os bits IEEE 754 de `v` (sinal, expoente, mantissa) em quatro
+/// bytes, do mais para o menos significativo
+const PACK_FUNCTION: &str = r#"
+vec4 packFloat(float v) {
+    if (v != v) return vec4(127.0, 192.0, 0.0, 0.0) / 255.0;
+    float s = v < 0.0 ? 128.0 : 0.0;
+    float a = abs(v);
+    if (a > 3.4028235e38) return vec4(s + 127.0, 128.0, 0.0, 0.0) / 255.0;
+    if (a == 0.0) return vec4(s, 0.0, 0.0, 0.0) / 255.0;
+    float e = floor(log2(a));
+    float m = a / exp2(e);
+    if (m >= 2.0) { m /= 2.0; e += 1.0; }
+    if (m < 1.0) { m *= 2.0; e -= 1.0; }
+    float biased = e + 127.0;
+    if (biased <= 0.0) {
+        m = a / exp2(-126.0);
+        biased = 0.0;
+    } else {
+        m -= 1.0;
+    }
+    m = floor(m * 8388608.0);
+    return vec4(
+        s + floor(biased / 2.0),
+        mod(biased, 2.0) * 128.0 + floor(m / 65536.0),
+        floor(mod(m, 65536.0) / 256.0),
+        mod(m, 256.0)
+    ) / 255.0;
+}
+"#;
+
+/// Quadrilátero que cobre o framebuffer (x, y)
+const QUAD: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+
+/// Como os resultados são gravados e lidos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Float32,
+    Rgba8,
+}
+
+impl Encoding {
+    fn name(&self) -> &'static str {
+        match self {
+            Encoding::Float32 => "float32",
+            Encoding::Rgba8 => "rgba8",
+        }
+    }
+}
+
+/// Executa as sondas em um contexto WebGL já criado
+pub struct PrecisionProbe<'a> {
+    gl: &'a GL,
+    webgl2: bool,
+}
+
+impl<'a> PrecisionProbe<'a> {
+    /// `gl` pode ser um `WebGL2RenderingContext` convertido com
+    /// `unchecked_ref`
+    pub fn new(gl: &'a GL, webgl2: bool) -> Self {
+        PrecisionProbe { gl, webgl2 }
+    }
+
+    /// Usa uma textura float quando possível, senão RGBA8 empacotado
+    pub fn run(&self) -> Result<PrecisionProbeData, String> {
+        let (encoding, bits) = match self.render(Encoding::Float32) {
+            Ok(bits) => (Encoding::Float32, bits),
+            Err(_) => (Encoding::Rgba8, self.render(Encoding::Rgba8)?),
+        };
+        Ok(Self::from_bits(encoding.name(), &bits))
+    }
+
+    /// Monta o resultado a partir dos bits de cada sonda (na ordem de
+    /// `PROBES`)
+    fn from_bits(encoding: &str, bits: &[u32]) -> PrecisionProbeData {
+        let values: BTreeMap<String, String> = PROBES
+            .iter()
+            .zip(bits)
+            .map(|((name, _), bits)| (name.to_string(), format!("{:08x}", bits)))
+            .collect();
+        let joined: Vec<String> = values.iter().map(|(name, bits)| format!("{}={}", name, bits)).collect();
+
+        PrecisionProbeData {
+            hash: sha256_hex(&format!("{}|{}", encoding, joined.join(","))),
+            encoding: encoding.to_string(),
+            values,
+        }
+    }
+
+    /// Renderiza uma linha de `PROBES.len()` pixels e retorna os bits de
+    /// cada um
+    fn render(&self, encoding: Encoding) -> Result<Vec<u32>, String> {
+        let gl = self.gl;
+        let width = PROBES.len() as i32;
+        let mut objects = GlObjects::new(gl);
+
+        // Descarta erros pendentes de operações anteriores no contexto
+        while gl.get_error() != GL::NO_ERROR {}
+
+        let texture = self.target_texture(&mut objects, encoding)?;
+        let framebuffer = objects.framebuffer().map_err(describe)?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(GL::FRAMEBUFFER, GL::COLOR_ATTACHMENT0, GL::TEXTURE_2D, Some(&texture), 0);
+        if gl.check_framebuffer_status(GL::FRAMEBUFFER) != GL::FRAMEBUFFER_COMPLETE {
+            return Err(format!("unsupported: {} render target", encoding.name()));
+        }
+
+        let program = self.program(&mut objects, encoding).map_err(describe)?;
+        gl.use_program(Some(&program));
+        for (name, value) in [("uZero", 0.0), ("uOne", 1.0)] {
+            if let Some(location) = gl.get_uniform_location(&program, name) {
+                gl.uniform1f(Some(&location), value);
+            }
+        }
+
+        gl.viewport(0, 0, width, 1);
+        gl.disable(GL::BLEND);
+        gl.disable(GL::DITHER);
+        self.draw(&mut objects, &program).map_err(describe)?;
+
+        let bits = match encoding {
+            Encoding::Float32 => {
+                let pixels = js_sys::Float32Array::new_with_length((width * 4) as u32);
+                gl.read_pixels_with_opt_array_buffer_view(
+                    0, 0, width, 1,
+                    GL::RGBA,
+                    GL::FLOAT,
+                    Some(&pixels),
+                ).map_err(describe)?;
+                pixels.to_vec().chunks(4).map(|pixel| pixel[0].to_bits()).collect()
+            }
+            Encoding::Rgba8 => {
+                let mut pixels = vec![0u8; (width * 4) as usize];
+                gl.read_pixels_with_opt_u8_array(
+                    0, 0, width, 1,
+                    GL::RGBA,
+                    GL::UNSIGNED_BYTE,
+                    Some(&mut pixels),
+                ).map_err(describe)?;
+                pixels
+                    .chunks(4)
+                    .map(|pixel| u32::from_be_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+                    .collect()
+            }
+        };
+
+        // Leituras de float não suportadas falham com erro GL, não exceção
+        if gl.get_error() != GL::NO_ERROR {
+            return Err(format!("read failed: {}", encoding.name()));
+        }
+        Ok(bits)
+    }
+
+    /// Textura `PROBES.len()` x 1 usada como render target. Floats exigem
+    /// `EXT_color_buffer_float` no WebGL2 e `OES_texture_float` (com
+    /// `WEBGL_color_buffer_float`, quando exposta) no WebGL1.
+    fn target_texture(&self, objects: &mut GlObjects, encoding: Encoding) -> Result<WebGlTexture, String> {
+        let gl = self.gl;
+        let extension = |name: &str| -> Result<bool, String> {
+            Ok(gl.get_extension(name).map_err(describe)?.is_some())
+        };
+
+        let (internal_format, data_type) = match encoding {
+            Encoding::Rgba8 => (GL::RGBA as i32, GL::UNSIGNED_BYTE),
+            Encoding::Float32 if self.webgl2 => {
+                if !extension("EXT_color_buffer_float")? {
+                    return Err("unsupported: EXT_color_buffer_float".to_string());
+                }
+                (RGBA32F, GL::FLOAT)
+            }
+            Encoding::Float32 => {
+                if !extension("OES_texture_float")? {
+                    return Err("unsupported: OES_texture_float".to_string());
+                }
+                extension("WEBGL_color_buffer_float")?;
+                (GL::RGBA as i32, GL::FLOAT)
+            }
+        };
+
+        let texture = objects.texture().map_err(describe)?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        for (pname, value) in [
+            (GL::TEXTURE_MIN_FILTER, GL::NEAREST),
+            (GL::TEXTURE_MAG_FILTER, GL::NEAREST),
+            (GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE),
+            (GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(GL::TEXTURE_2D, pname, value as i32);
+        }
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+            GL::TEXTURE_2D,
+            0,
+            internal_format,
+            PROBES.len() as i32,
+            1,
+            0,
+            GL::RGBA,
+            data_type,
+            None,
+        ).map_err(describe)?;
+        Ok(texture)
+    }
+
+    fn program(&self, objects: &mut GlObjects, encoding: Encoding) -> Result<WebGlProgram, JsValue> {
+        let vertex = objects.shader(GL::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment = objects.shader(GL::FRAGMENT_SHADER, &Self::fragment_shader(encoding))?;
+        objects.program(&vertex, &fragment)
+    }
+
+    /// Fragment shader que escolhe a sonda pela coluna do pixel
+    fn fragment_shader(encoding: Encoding) -> String {
+        let mut source = String::from(FRAGMENT_HEADER);
+        if encoding == Encoding::Rgba8 {
+            source.push_str(PACK_FUNCTION);
+        }
+
+        source.push_str("\nfloat probe(float i) {\n");
+        for (index, (_, expression)) in PROBES.iter().enumerate() {
+            source.push_str(&format!("    if (i < {}.5) return {};\n", index, expression));
+        }
+        source.push_str("    return 0.0;\n}\n");
+
+        let output = match encoding {
+            Encoding::Float32 => "vec4(v, 0.0, 0.0, 1.0)",
+            Encoding::Rgba8 => "packFloat(v)",
+        };
+        source.push_str(&format!(
+            "\nvoid main(void) {{\n    float v = probe(floor(gl_FragCoord.x));\n    gl_FragColor = {};\n}}\n",
+            output,
+        ));
+        source
+    }
+
+    fn draw(&self, objects: &mut GlObjects, program: &WebGlProgram) -> Result<(), JsValue> {
+        let gl = self.gl;
+        let buffer = objects.buffer()?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
+        gl.buffer_data_with_array_buffer_view(
+            GL::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(QUAD.as_slice()),
+            GL::STATIC_DRAW,
+        );
+
+        let location = gl.get_attrib_location(program, "aPosition");
+        if location < 0 {
+            return Err(JsValue::from_str("aPosition not found"));
+        }
+        gl.vertex_attrib_pointer_with_i32(location as u32, 2, GL::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(location as u32);
+        gl.draw_arrays(GL::TRIANGLE_STRIP, 0, 4);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn probe_names_are_unique() {
+        let names: BTreeSet<_> = PROBES.iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), PROBES.len());
+    }
+
+    #[test]
+    fn bits_are_keyed_by_probe_name() {
+        let bits: Vec<u32> = (0..PROBES.len() as u32).collect();
+        let data = PrecisionProbe::from_bits("float32", &bits);
+
+        assert_eq!(data.encoding, "float32");
+        assert_eq!(data.values.len(), PROBES.len());
+        assert_eq!(data.values["sin_large"], "00000000");
+        assert_eq!(data.values["infinity"], format!("{:08x}", PROBES.len() - 1));
+
+        // A codificação entra no hash
+        assert_ne!(PrecisionProbe::from_bits("rgba8", &bits).hash, data.hash);
+        assert_eq!(PrecisionProbe::from_bits("float32", &bits), data);
+    }
+
+    #[test]
+    fn shader_packs_bits_only_for_rgba8() {
+        let float32 = PrecisionProbe::fragment_shader(Encoding::Float32);
+        let rgba8 = PrecisionProbe::fragment_shader(Encoding::Rgba8);
+
+        assert!(!float32.contains("packFloat"));
+        assert!(rgba8.contains("gl_FragColor = packFloat(v);"));
+        for source in [&float32, &rgba8] {
+            assert_eq!(source.matches("    if (i < ").count(), PROBES.len());
+        }
+    }
+}
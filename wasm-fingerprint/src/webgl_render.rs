@@ -0,0 +1,592 @@
+//! Cenas de teste renderizadas no fingerprint WebGL
+//!
+//! Cada cena exercita uma parte do pipeline cujo resultado varia entre GPUs
+//! e drivers (trigonometria em `highp`, amostragem de texturas float,
+//! blending, perspectiva) e é lida de volta por inteiro com `readPixels`.
+//! Os shaders usam GLSL ES 1.00, aceito tanto pelo WebGL1 quanto pelo
+//! WebGL2, de forma que as duas versões passam pelo mesmo caminho.
+
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext as GL, WebGlShader,
+    WebGlTexture,
+};
+use sha2::{Sha256, Digest};
+
+/// Cena renderizada e hasheada separadamente
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebGLScene {
+    /// Quadrilátero rotacionado em perspectiva com cores interpoladas
+    Perspective,
+    /// Funções trigonométricas, exponenciais e `fract` em alta precisão
+    HighpTrig,
+    /// Amostragem de uma textura float com valores fracionários
+    FloatTexture,
+    /// Triângulos sobrepostos com três funções de blending
+    Blending,
+}
+
+impl WebGLScene {
+    pub const ALL: [WebGLScene; 4] = [
+        WebGLScene::Perspective,
+        WebGLScene::HighpTrig,
+        WebGLScene::FloatTexture,
+        WebGLScene::Blending,
+    ];
+
+    /// Nome usado como chave em `WebGLFingerprintData::scene_hashes`
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebGLScene::Perspective => "perspective",
+            WebGLScene::HighpTrig => "highp_trig",
+            WebGLScene::FloatTexture => "float_texture",
+            WebGLScene::Blending => "blending",
+        }
+    }
+
+    fn fragment_shader(&self) -> &'static str {
+        match self {
+            WebGLScene::Perspective => PERSPECTIVE_FRAGMENT,
+            WebGLScene::HighpTrig => HIGHP_TRIG_FRAGMENT,
+            WebGLScene::FloatTexture => FLOAT_TEXTURE_FRAGMENT,
+            WebGLScene::Blending => BLENDING_FRAGMENT,
+        }
+    }
+}
+
+/// Resultado da renderização de todas as cenas
+#[derive(Debug, Clone, Default)]
+pub struct SceneResults {
+    /// SHA-256 do framebuffer completo de cada cena renderizada
+    pub hashes: BTreeMap<String, String>,
+    /// Motivo de cada cena que não produziu imagem
+    pub failures: BTreeMap<String, String>,
+}
+
+// Constantes de extensões e do WebGL2
+const RGBA32F: i32 = 0x8814;
+
+const VERTEX_SHADER: &str = r#"
+attribute vec2 aPosition;
+attribute vec3 aColor;
+uniform mat4 uModelViewMatrix;
+uniform mat4 uProjectionMatrix;
+varying vec3 vColor;
+varying vec2 vPosition;
+
+void main(void) {
+    gl_Position = uProjectionMatrix * uModelViewMatrix * vec4(aPosition, 0.0, 1.0);
+    vColor = aColor;
+    vPosition = aPosition;
+}
+"#;
+
+const PRECISION_HEADER: &str = r#"
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+#else
+precision mediump float;
+#endif
+varying vec3 vColor;
+varying vec2 vPosition;
+"#;
+
+const PERSPECTIVE_FRAGMENT: &str = r#"
+uniform float uTime;
+
+void main(void) {
+    float r = abs(sin(vPosition.x * 10.0 + uTime));
+    float g = abs(cos(vPosition.y * 10.0 + uTime));
+    float b = abs(sin((vPosition.x + vPosition.y) * 5.0));
+    gl_FragColor = vec4(mix(vec3(r, g, b), vColor, 0.5), 1.0);
+}
+"#;
+
+const HIGHP_TRIG_FRAGMENT: &str = r#"
+uniform float uTime;
+
+void main(void) {
+    vec2 p = vPosition * 1000.0 + uTime;
+    float a = fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+    float b = fract(exp(vPosition.x * 4.0) * log(2.0 + vPosition.y * 1.5) * 1000.0);
+    float c = fract(atan(vPosition.y, vPosition.x + 1.5) * pow(abs(vPosition.x) + 1.0, 7.3) * 100.0);
+    gl_FragColor = vec4(a, b, c, 1.0);
+}
+"#;
+
+const FLOAT_TEXTURE_FRAGMENT: &str = r#"
+uniform sampler2D uTexture;
+
+void main(void) {
+    vec4 texel = texture2D(uTexture, vPosition * 0.5 + 0.5);
+    gl_FragColor = vec4(fract(texel.rgb * 1000.0), 1.0);
+}
+"#;
+
+const BLENDING_FRAGMENT: &str = r#"
+uniform float uAlpha;
+
+void main(void) {
+    gl_FragColor = vec4(vColor, uAlpha);
+}
+"#;
+
+/// Valor fixo de `uTime`, para que a cena seja determinística
+const SCENE_TIME: f32 = 1.2345;
+
+/// Lado da textura float, em texels
+const FLOAT_TEXTURE_SIZE: i32 = 8;
+
+/// Quadrilátero que cobre a tela, com uma cor por vértice (x, y, r, g, b)
+const QUAD: [f32; 20] = [
+    -1.0, -1.0, 1.0, 0.2, 0.1,
+     1.0, -1.0, 0.1, 1.0, 0.3,
+    -1.0,  1.0, 0.2, 0.3, 1.0,
+     1.0,  1.0, 0.9, 0.9, 0.2,
+];
+
+const TRIANGLE: [f32; 15] = [
+    -0.8, -0.6, 1.0, 0.3, 0.0,
+     0.8, -0.6, 0.0, 0.8, 0.4,
+     0.0,  0.8, 0.3, 0.1, 1.0,
+];
+
+/// Renderiza as cenas de teste em um contexto WebGL já criado
+pub struct WebGLRenderer<'a> {
+    gl: &'a GL,
+    webgl2: bool,
+}
+
+impl<'a> WebGLRenderer<'a> {
+    /// `gl` pode ser um `WebGL2RenderingContext` convertido com
+    /// `unchecked_ref`: ele implementa toda a API do WebGL1
+    pub fn new(gl: &'a GL, webgl2: bool) -> Self {
+        WebGLRenderer { gl, webgl2 }
+    }
+
+    /// Renderiza todas as cenas; falhas de uma cena não impedem as demais
+    pub fn render_all(&self) -> SceneResults {
+        let mut results = SceneResults::default();
+        for scene in WebGLScene::ALL {
+            match self.render(scene) {
+                Ok(pixels) => {
+                    results.hashes.insert(scene.name().to_string(), hash_pixels(&pixels));
+                }
+                Err(reason) => {
+                    results.failures.insert(scene.name().to_string(), reason);
+                }
+            }
+        }
+        results
+    }
+
+    /// Renderiza uma cena e retorna o framebuffer RGBA completo. Os
+    /// objetos GL criados para a cena são apagados ao final, com ou sem
+    /// sucesso.
+    pub fn render(&self, scene: WebGLScene) -> Result<Vec<u8>, String> {
+        let gl = self.gl;
+        let (width, height) = (gl.drawing_buffer_width(), gl.drawing_buffer_height());
+        let mut objects = GlObjects::new(gl);
+
+        let program = self.program(&mut objects, scene).map_err(describe)?;
+        gl.use_program(Some(&program));
+        gl.viewport(0, 0, width, height);
+        gl.disable(GL::BLEND);
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+
+        let identity = identity();
+        self.set_matrix(&program, "uProjectionMatrix", &identity);
+        self.set_matrix(&program, "uModelViewMatrix", &identity);
+        if let Some(location) = gl.get_uniform_location(&program, "uTime") {
+            gl.uniform1f(Some(&location), SCENE_TIME);
+        }
+
+        match scene {
+            WebGLScene::Perspective => {
+                let aspect = width as f32 / height.max(1) as f32;
+                self.set_matrix(&program, "uProjectionMatrix", &perspective(0.8, aspect, 0.1, 10.0));
+                let model_view = multiply(
+                    &translation(0.0, 0.0, -2.6),
+                    &multiply(&rotation_x(0.6), &rotation_z(0.35)),
+                );
+                self.set_matrix(&program, "uModelViewMatrix", &model_view);
+                self.draw(&mut objects, &program, &QUAD, GL::TRIANGLE_STRIP).map_err(describe)?;
+            }
+            WebGLScene::HighpTrig => {
+                self.draw(&mut objects, &program, &QUAD, GL::TRIANGLE_STRIP).map_err(describe)?;
+            }
+            WebGLScene::FloatTexture => {
+                self.upload_float_texture(&mut objects)?;
+                if let Some(location) = gl.get_uniform_location(&program, "uTexture") {
+                    gl.uniform1i(Some(&location), 0);
+                }
+                self.draw(&mut objects, &program, &QUAD, GL::TRIANGLE_STRIP).map_err(describe)?;
+            }
+            WebGLScene::Blending => {
+                gl.enable(GL::BLEND);
+                let passes = [
+                    (GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA, 0.6, 0.0),
+                    (GL::ONE, GL::ONE, 0.35, 2.1),
+                    (GL::DST_COLOR, GL::ZERO, 0.8, 4.2),
+                ];
+                for (source, destination, alpha, angle) in passes {
+                    gl.blend_func(source, destination);
+                    if let Some(location) = gl.get_uniform_location(&program, "uAlpha") {
+                        gl.uniform1f(Some(&location), alpha);
+                    }
+                    self.set_matrix(&program, "uModelViewMatrix", &rotation_z(angle));
+                    self.draw(&mut objects, &program, &TRIANGLE, GL::TRIANGLES).map_err(describe)?;
+                }
+                gl.disable(GL::BLEND);
+            }
+        }
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        gl.read_pixels_with_opt_u8_array(
+            0, 0, width, height,
+            GL::RGBA,
+            GL::UNSIGNED_BYTE,
+            Some(&mut pixels),
+        ).map_err(describe)?;
+
+        if is_uniform(&pixels) {
+            return Err("render failed: uniform output".to_string());
+        }
+        Ok(pixels)
+    }
+
+    fn program(&self, objects: &mut GlObjects, scene: WebGLScene) -> Result<WebGlProgram, JsValue> {
+        let vertex = objects.shader(GL::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment = objects.shader(
+            GL::FRAGMENT_SHADER,
+            &format!("{}{}", PRECISION_HEADER, scene.fragment_shader()),
+        )?;
+        objects.program(&vertex, &fragment)
+    }
+
+    fn set_matrix(&self, program: &WebGlProgram, name: &str, matrix: &[f32; 16]) {
+        if let Some(location) = self.gl.get_uniform_location(program, name) {
+            self.gl.uniform_matrix4fv_with_f32_array(Some(&location), false, matrix);
+        }
+    }
+
+    /// Envia os vértices (x, y, r, g, b) e desenha
+    fn draw(&self, objects: &mut GlObjects, program: &WebGlProgram, vertices: &[f32], mode: u32) -> Result<(), JsValue> {
+        let gl = self.gl;
+        let buffer = objects.buffer()?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
+        gl.buffer_data_with_array_buffer_view(
+            GL::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(vertices),
+            GL::STATIC_DRAW,
+        );
+
+        let stride = 5 * 4;
+        for (name, size, offset) in [("aPosition", 2, 0), ("aColor", 3, 2 * 4)] {
+            // Atributos não usados pelo shader são removidos na compilação
+            let location = gl.get_attrib_location(program, name);
+            if location >= 0 {
+                gl.vertex_attrib_pointer_with_i32(location as u32, size, GL::FLOAT, false, stride, offset);
+                gl.enable_vertex_attrib_array(location as u32);
+            }
+        }
+
+        gl.draw_arrays(mode, 0, (vertices.len() / 5) as i32);
+        Ok(())
+    }
+
+    /// Textura RGBA float com valores cuja parte fracionária só sobrevive
+    /// com 32 bits; no WebGL1 depende de `OES_texture_float`
+    fn upload_float_texture(&self, objects: &mut GlObjects) -> Result<(), String> {
+        let gl = self.gl;
+        let internal_format = if self.webgl2 {
+            RGBA32F
+        } else if gl.get_extension("OES_texture_float").map_err(describe)?.is_some() {
+            GL::RGBA as i32
+        } else {
+            return Err("unsupported: OES_texture_float".to_string());
+        };
+
+        let texels: Vec<f32> = (0..FLOAT_TEXTURE_SIZE * FLOAT_TEXTURE_SIZE * 4)
+            .map(|i| (i + 1) as f32 / 7.0 + i as f32 * 1.0e-4)
+            .collect();
+
+        let texture = objects.texture().map_err(describe)?;
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        // Float sem filtragem linear: `OES_texture_float_linear` é opcional
+        for (pname, value) in [
+            (GL::TEXTURE_MIN_FILTER, GL::NEAREST),
+            (GL::TEXTURE_MAG_FILTER, GL::NEAREST),
+            (GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE),
+            (GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameteri(GL::TEXTURE_2D, pname, value as i32);
+        }
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+            GL::TEXTURE_2D,
+            0,
+            internal_format,
+            FLOAT_TEXTURE_SIZE,
+            FLOAT_TEXTURE_SIZE,
+            0,
+            GL::RGBA,
+            GL::FLOAT,
+            Some(&js_sys::Float32Array::from(texels.as_slice())),
+        ).map_err(describe)?;
+        Ok(())
+    }
+}
+
+/// Se todos os pixels têm a mesma cor (ex.: tudo preto): a cena não foi
+/// desenhada, ou a leitura foi bloqueada
+pub fn is_uniform(pixels: &[u8]) -> bool {
+    let mut chunks = pixels.chunks(4);
+    match chunks.next() {
+        Some(first) => chunks.all(|pixel| pixel == first),
+        None => true,
+    }
+}
+
+pub fn hash_pixels(pixels: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(pixels);
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn describe(error: JsValue) -> String {
+    error.as_string().unwrap_or_else(|| format!("{:?}", error))
+}
+
+/// Objetos GL criados para uma cena. Todos são apagados no `Drop`, para
+/// que coletas repetidas não acumulem memória da GPU no contexto.
+pub struct GlObjects<'a> {
+    gl: &'a GL,
+    shaders: Vec<WebGlShader>,
+    programs: Vec<WebGlProgram>,
+    buffers: Vec<WebGlBuffer>,
+    textures: Vec<WebGlTexture>,
+    framebuffers: Vec<WebGlFramebuffer>,
+}
+
+impl<'a> GlObjects<'a> {
+    pub fn new(gl: &'a GL) -> Self {
+        GlObjects {
+            gl,
+            shaders: Vec::new(),
+            programs: Vec::new(),
+            buffers: Vec::new(),
+            textures: Vec::new(),
+            framebuffers: Vec::new(),
+        }
+    }
+
+    /// Compila um shader; em caso de erro, retorna o log de compilação
+    pub fn shader(&mut self, shader_type: u32, source: &str) -> Result<WebGlShader, JsValue> {
+        let gl = self.gl;
+        let shader = gl
+            .create_shader(shader_type)
+            .ok_or("Unable to create shader")?;
+        self.shaders.push(shader.clone());
+
+        gl.shader_source(&shader, source);
+        gl.compile_shader(&shader);
+
+        if gl.get_shader_parameter(&shader, GL::COMPILE_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+        {
+            Ok(shader)
+        } else {
+            Err(JsValue::from_str(
+                &gl.get_shader_info_log(&shader)
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
+    /// Liga os shaders em um programa; em caso de erro, retorna o log
+    pub fn program(
+        &mut self,
+        vert_shader: &WebGlShader,
+        frag_shader: &WebGlShader,
+    ) -> Result<WebGlProgram, JsValue> {
+        let gl = self.gl;
+        let program = gl
+            .create_program()
+            .ok_or("Unable to create program")?;
+        self.programs.push(program.clone());
+
+        gl.attach_shader(&program, vert_shader);
+        gl.attach_shader(&program, frag_shader);
+        gl.link_program(&program);
+
+        if gl.get_program_parameter(&program, GL::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+        {
+            Ok(program)
+        } else {
+            Err(JsValue::from_str(
+                &gl.get_program_info_log(&program)
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
+    pub fn buffer(&mut self) -> Result<WebGlBuffer, JsValue> {
+        let buffer = self.gl.create_buffer().ok_or("Failed to create buffer")?;
+        self.buffers.push(buffer.clone());
+        Ok(buffer)
+    }
+
+    pub fn texture(&mut self) -> Result<WebGlTexture, JsValue> {
+        let texture = self.gl.create_texture().ok_or("Failed to create texture")?;
+        self.textures.push(texture.clone());
+        Ok(texture)
+    }
+
+    pub fn framebuffer(&mut self) -> Result<WebGlFramebuffer, JsValue> {
+        let framebuffer = self.gl.create_framebuffer().ok_or("Failed to create framebuffer")?;
+        self.framebuffers.push(framebuffer.clone());
+        Ok(framebuffer)
+    }
+}
+
+impl Drop for GlObjects<'_> {
+    fn drop(&mut self) {
+        let gl = self.gl;
+        // Desvincula antes de apagar: objetos vinculados só são liberados
+        // quando deixam de estar em uso
+        gl.use_program(None);
+        gl.bind_buffer(GL::ARRAY_BUFFER, None);
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        // Atributos habilitados continuariam apontando para buffers apagados
+        let attributes = gl.get_parameter(GL::MAX_VERTEX_ATTRIBS).ok().and_then(|v| v.as_f64()).unwrap_or(0.0);
+        for location in 0..attributes as u32 {
+            gl.disable_vertex_attrib_array(location);
+        }
+
+        for program in &self.programs {
+            gl.delete_program(Some(program));
+        }
+        for shader in &self.shaders {
+            gl.delete_shader(Some(shader));
+        }
+        for buffer in &self.buffers {
+            gl.delete_buffer(Some(buffer));
+        }
+        for texture in &self.textures {
+            gl.delete_texture(Some(texture));
+        }
+        for framebuffer in &self.framebuffers {
+            gl.delete_framebuffer(Some(framebuffer));
+        }
+    }
+}
+
+// Matrizes 4x4 em ordem de coluna, como esperado por `uniformMatrix4fv`
+
+fn identity() -> [f32; 16] {
+    [
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ]
+}
+
+fn translation(x: f32, y: f32, z: f32) -> [f32; 16] {
+    let mut m = identity();
+    m[12] = x;
+    m[13] = y;
+    m[14] = z;
+    m
+}
+
+fn rotation_x(angle: f32) -> [f32; 16] {
+    let (s, c) = angle.sin_cos();
+    let mut m = identity();
+    m[5] = c;
+    m[6] = s;
+    m[9] = -s;
+    m[10] = c;
+    m
+}
+
+fn rotation_z(angle: f32) -> [f32; 16] {
+    let (s, c) = angle.sin_cos();
+    let mut m = identity();
+    m[0] = c;
+    m[1] = s;
+    m[4] = -s;
+    m[5] = c;
+    m
+}
+
+fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> [f32; 16] {
+    let f = 1.0 / (fov_y / 2.0).tan();
+    let range = 1.0 / (near - far);
+    [
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, (near + far) * range, -1.0,
+        0.0, 0.0, 2.0 * near * far * range, 0.0,
+    ]
+}
+
+fn multiply(a: &[f32; 16], b: &[f32; 16]) -> [f32; 16] {
+    let mut out = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn scene_names_are_unique() {
+        let names: BTreeSet<_> = WebGLScene::ALL.iter().map(|scene| scene.name()).collect();
+        assert_eq!(names.len(), WebGLScene::ALL.len());
+    }
+
+    #[test]
+    fn uniform_framebuffers_are_detected() {
+        assert!(is_uniform(&[]));
+        assert!(is_uniform(&[0, 0, 0, 255, 0, 0, 0, 255]));
+        assert!(!is_uniform(&[0, 0, 0, 255, 0, 0, 1, 255]));
+    }
+
+    #[test]
+    fn pixel_hash_is_sha256() {
+        assert_eq!(
+            hash_pixels(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_ne!(hash_pixels(&[0, 0, 0, 255]), hash_pixels(&[0, 0, 1, 255]));
+    }
+
+    #[test]
+    fn matrices_are_column_major() {
+        let moved = multiply(&translation(1.0, 2.0, 3.0), &identity());
+        assert_eq!(&moved[12..15], &[1.0, 2.0, 3.0]);
+        assert_eq!(multiply(&identity(), &rotation_z(0.5)), rotation_z(0.5));
+
+        // Meia volta em torno de x inverte y e z
+        let flipped = rotation_x(std::f32::consts::PI);
+        assert!((flipped[5] + 1.0).abs() < 1e-6 && (flipped[10] + 1.0).abs() < 1e-6);
+
+        let projection = perspective(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+        assert!((projection[0] - 0.5).abs() < 1e-6);
+        assert_eq!(projection[11], -1.0);
+    }
+}
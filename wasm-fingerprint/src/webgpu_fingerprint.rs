@@ -0,0 +1,320 @@
+//! Fingerprint do adaptador WebGPU
+//!
+//! Coleta as informações do adaptador (`vendor`, `architecture`, `device`,
+//! `description`), as features suportadas e todos os limites expostos por
+//! `navigator.gpu.requestAdapter()`. Opcionalmente executa um compute
+//! shader pequeno e gera o hash dos bits exatos dos floats resultantes, que
+//! variam com o arredondamento das funções transcendentais de cada GPU.
+//!
+//! As bindings WebGPU do `web-sys` só existem com `--cfg=web_sys_unstable_apis`,
+//! por isso a API é acessada dinamicamente com `js_sys::Reflect`.
+
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use js_sys::{Array, Function, Object, Reflect, Uint8Array};
+use sha2::{Sha256, Digest};
+use crate::config::WebGPUConfig;
+use crate::dom_utils::get_window;
+use crate::source::{Deadline, FingerprintSource, OutputSlot, SourceError, SourceFuture, SourceOutput, Stability};
+use crate::utils::sha256_hex;
+use crate::WebGPUFingerprintData;
+
+// Flags de `GPUBufferUsage` e `GPUMapMode`
+const BUFFER_USAGE_MAP_READ: u32 = 0x0001;
+const BUFFER_USAGE_COPY_SRC: u32 = 0x0004;
+const BUFFER_USAGE_COPY_DST: u32 = 0x0008;
+const BUFFER_USAGE_STORAGE: u32 = 0x0080;
+const MAP_MODE_READ: u32 = 0x0001;
+
+/// Número de invocações do compute shader (um float de saída cada)
+const COMPUTE_INVOCATIONS: u32 = 256;
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Funções transcendentais encadeadas; o resultado depende da precisão
+/// das implementações de `sin`, `exp`, `pow` e `sqrt` da GPU
+const COMPUTE_SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read_write> results: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let x = f32(id.x) * 0.1234567 + 0.5;
+    results[id.x] = sin(x) * exp(x * 0.01) / (1.0 + x * x) + sqrt(x) * 0.001 + pow(x, 1.37);
+}
+"#;
+
+/// Função exportada para JavaScript - hash do adaptador WebGPU
+#[wasm_bindgen]
+pub async fn get_webgpu_fingerprint() -> Result<String, JsValue> {
+    Ok(WebGPUFingerprint::collect_with(&WebGPUConfig::default()).await?.hash)
+}
+
+/// Função exportada para JavaScript - informações, features e limites do
+/// adaptador WebGPU
+#[wasm_bindgen]
+pub async fn get_webgpu_info() -> Result<JsValue, JsValue> {
+    let data = WebGPUFingerprint::collect_with(&WebGPUConfig::default()).await?;
+    serde_wasm_bindgen::to_value(&data).map_err(JsValue::from)
+}
+
+#[derive(Default)]
+pub struct WebGPUFingerprint {
+    config: WebGPUConfig,
+}
+
+impl WebGPUFingerprint {
+    pub fn new(config: WebGPUConfig) -> Self {
+        WebGPUFingerprint { config }
+    }
+
+    /// `SourceError::Unsupported` quando `navigator.gpu` não existe ou não
+    /// há adaptador disponível
+    pub async fn collect_with(config: &WebGPUConfig) -> Result<WebGPUFingerprintData, SourceError> {
+        let navigator = get_window()?.navigator();
+        let gpu = Reflect::get(&navigator, &JsValue::from_str("gpu"))?;
+        if gpu.is_undefined() || gpu.is_null() {
+            return Err(SourceError::Unsupported);
+        }
+
+        let adapter = JsFuture::from(call(&gpu, "requestAdapter", &[])?.dyn_into::<js_sys::Promise>()?).await?;
+        if adapter.is_null() || adapter.is_undefined() {
+            return Err(SourceError::Unsupported);
+        }
+
+        let info = Self::adapter_info(&adapter).await?;
+        let text = |key: &str| -> String {
+            Reflect::get(&info, &JsValue::from_str(key))
+                .ok()
+                .and_then(|value| value.as_string())
+                .unwrap_or_default()
+        };
+
+        let features = Reflect::get(&adapter, &JsValue::from_str("features"))?;
+        let mut features: Vec<String> = Array::from(&features)
+            .iter()
+            .filter_map(|feature| feature.as_string())
+            .collect();
+        features.sort();
+
+        let mut data = WebGPUFingerprintData {
+            hash: String::new(),
+            vendor: text("vendor"),
+            architecture: text("architecture"),
+            device: text("device"),
+            description: text("description"),
+            features,
+            limits: Self::limits(&Reflect::get(&adapter, &JsValue::from_str("limits"))?)?,
+            compute_hash: String::new(),
+            compute_error: String::new(),
+        };
+
+        // O compute shader é opcional: se falhar, os dados do adaptador
+        // continuam valendo
+        if config.compute_shader {
+            match Self::run_compute_shader(&adapter).await {
+                Ok(hash) => data.compute_hash = hash,
+                Err(error) => data.compute_error = Self::error_message(&error),
+            }
+        }
+
+        data.hash = Self::hash(&data);
+        Ok(data)
+    }
+
+    /// Hash SHA-256 da serialização JSON de todos os campos (exceto o
+    /// próprio hash e a mensagem de erro do compute shader, cujo texto
+    /// varia entre versões do navegador)
+    pub fn hash(data: &WebGPUFingerprintData) -> String {
+        let mut data = data.clone();
+        data.hash.clear();
+        data.compute_error.clear();
+        sha256_hex(&serde_json::to_string(&data).unwrap_or_default())
+    }
+
+    /// `GPUAdapter.info`; navegadores mais antigos só expõem o método
+    /// assíncrono `requestAdapterInfo()`
+    async fn adapter_info(adapter: &JsValue) -> Result<JsValue, JsValue> {
+        let info = Reflect::get(adapter, &JsValue::from_str("info"))?;
+        if !info.is_undefined() {
+            return Ok(info);
+        }
+        if Reflect::get(adapter, &JsValue::from_str("requestAdapterInfo"))?.is_function() {
+            let promise = call(adapter, "requestAdapterInfo", &[])?.dyn_into::<js_sys::Promise>()?;
+            return JsFuture::from(promise).await;
+        }
+        Ok(Object::new().into())
+    }
+
+    /// Todos os limites de `GPUSupportedLimits`. Os limites são getters do
+    /// protótipo, então são enumerados a partir dele e não do objeto.
+    fn limits(limits: &JsValue) -> Result<BTreeMap<String, f64>, JsValue> {
+        let prototype = Object::get_prototype_of(limits);
+        let mut values = BTreeMap::new();
+        for name in Object::get_own_property_names(&prototype).iter() {
+            if let Some(value) = Reflect::get(limits, &name)?.as_f64() {
+                if let Some(name) = name.as_string() {
+                    values.insert(name, value);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    fn error_message(error: &JsValue) -> String {
+        error.as_string()
+            .or_else(|| Reflect::get(error, &JsValue::from_str("message")).ok()?.as_string())
+            .unwrap_or_else(|| "compute shader failed".to_string())
+    }
+
+    /// Executa `COMPUTE_SHADER` e retorna o hash dos bytes do resultado
+    async fn run_compute_shader(adapter: &JsValue) -> Result<String, JsValue> {
+        let device = JsFuture::from(call(adapter, "requestDevice", &[])?.dyn_into::<js_sys::Promise>()?).await?;
+        let result = Self::dispatch(&device).await;
+        call(&device, "destroy", &[])?;
+        result
+    }
+
+    async fn dispatch(device: &JsValue) -> Result<String, JsValue> {
+        let size = (COMPUTE_INVOCATIONS * 4) as f64;
+
+        let module = call(device, "createShaderModule", &[
+            object(&[("code", COMPUTE_SHADER.into())])?,
+        ])?;
+        let pipeline = call(device, "createComputePipeline", &[object(&[
+            ("layout", "auto".into()),
+            ("compute", object(&[("module", module), ("entryPoint", "main".into())])?),
+        ])?])?;
+
+        let storage = call(device, "createBuffer", &[object(&[
+            ("size", size.into()),
+            ("usage", (BUFFER_USAGE_STORAGE | BUFFER_USAGE_COPY_SRC).into()),
+        ])?])?;
+        let readback = object(&[
+            ("size", size.into()),
+            ("usage", (BUFFER_USAGE_MAP_READ | BUFFER_USAGE_COPY_DST).into()),
+        ])
+        .and_then(|descriptor| call(device, "createBuffer", &[descriptor]));
+        let readback = match readback {
+            Ok(readback) => readback,
+            Err(error) => {
+                let _ = call(&storage, "destroy", &[]);
+                return Err(error);
+            }
+        };
+
+        // Os buffers são liberados em todos os caminhos, inclusive em falha
+        let result = Self::execute(device, &pipeline, &storage, &readback, size).await;
+        let _ = call(&storage, "destroy", &[]);
+        let _ = call(&readback, "destroy", &[]);
+        result
+    }
+
+    /// Despacha o pipeline sobre `storage` e lê o resultado por `readback`
+    async fn execute(
+        device: &JsValue,
+        pipeline: &JsValue,
+        storage: &JsValue,
+        readback: &JsValue,
+        size: f64,
+    ) -> Result<String, JsValue> {
+        let entry = object(&[
+            ("binding", 0.into()),
+            ("resource", object(&[("buffer", storage.clone())])?),
+        ])?;
+        let bind_group = call(device, "createBindGroup", &[object(&[
+            ("layout", call(pipeline, "getBindGroupLayout", &[0.into()])?),
+            ("entries", Array::of1(&entry).into()),
+        ])?])?;
+
+        let encoder = call(device, "createCommandEncoder", &[])?;
+        let pass = call(&encoder, "beginComputePass", &[])?;
+        call(&pass, "setPipeline", std::slice::from_ref(pipeline))?;
+        call(&pass, "setBindGroup", &[0.into(), bind_group])?;
+        call(&pass, "dispatchWorkgroups", &[(COMPUTE_INVOCATIONS / WORKGROUP_SIZE).into()])?;
+        call(&pass, "end", &[])?;
+        call(&encoder, "copyBufferToBuffer", &[
+            storage.clone(), 0.into(), readback.clone(), 0.into(), size.into(),
+        ])?;
+        let commands = call(&encoder, "finish", &[])?;
+        let queue = Reflect::get(device, &JsValue::from_str("queue"))?;
+        call(&queue, "submit", &[Array::of1(&commands).into()])?;
+
+        let mapped = call(readback, "mapAsync", &[MAP_MODE_READ.into()])?;
+        JsFuture::from(mapped.dyn_into::<js_sys::Promise>()?).await?;
+        let bytes = Uint8Array::new(&call(readback, "getMappedRange", &[])?).to_vec();
+        call(readback, "unmap", &[])?;
+
+        if bytes.iter().all(|&b| b == 0) {
+            return Err(JsValue::from_str("compute shader produced no output"));
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
+/// Chama `target[method](...args)`
+fn call(target: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let function = Reflect::get(target, &JsValue::from_str(method))?
+        .dyn_into::<Function>()
+        .map_err(|_| JsValue::from_str(&format!("{} is not a function", method)))?;
+    function.apply(target, &args.iter().collect::<Array>())
+}
+
+/// Objeto JavaScript com as propriedades informadas
+fn object(properties: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let object = Object::new();
+    for (key, value) in properties {
+        Reflect::set(&object, &JsValue::from_str(key), value)?;
+    }
+    Ok(object.into())
+}
+
+impl FingerprintSource for WebGPUFingerprint {
+    fn name(&self) -> &str {
+        "webgpu"
+    }
+
+    fn stability(&self) -> Stability {
+        Stability::Stable
+    }
+
+    fn slot(&self) -> OutputSlot {
+        OutputSlot::WebGPU
+    }
+
+    fn collect<'a>(&'a self, _deadline: Deadline<'a>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let webgpu = WebGPUFingerprint::collect_with(&self.config).await?;
+            Ok(SourceOutput::WebGPU(webgpu))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_covers_adapter_but_not_itself() {
+        let data = WebGPUFingerprintData {
+            vendor: "example".to_string(),
+            architecture: "arch-1".to_string(),
+            features: vec!["shader-f16".to_string()],
+            limits: BTreeMap::from([("maxBindGroups".to_string(), 4.0)]),
+            ..Default::default()
+        };
+        let mut hashed = data.clone();
+        hashed.hash = WebGPUFingerprint::hash(&data);
+        assert_eq!(WebGPUFingerprint::hash(&hashed), hashed.hash);
+
+        let mut other_limit = data.clone();
+        other_limit.limits.insert("maxBindGroups".to_string(), 8.0);
+        assert_ne!(WebGPUFingerprint::hash(&other_limit), hashed.hash);
+
+        let mut computed = data;
+        computed.compute_hash = "compute".to_string();
+        assert_ne!(WebGPUFingerprint::hash(&computed), hashed.hash);
+    }
+}